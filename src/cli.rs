@@ -1,16 +1,21 @@
 use clap::{command, Command};
 use indoc::indoc;
 
-use self::codecs::Codecs;
+use self::{analyze::analyze, codecs::Codecs};
 
+pub mod analyze;
 pub mod codecs;
 pub mod common;
+pub mod config;
+#[cfg(feature = "resize")]
+pub mod montage;
 pub mod pipeline;
 pub mod preprocessors;
+pub mod sidecar;
 pub mod utils;
 
 pub fn cli() -> Command {
-    command!()
+    let cli = command!()
         .arg_required_else_help(true)
         .after_help(indoc! {r#"List of supported codecs
 
@@ -20,6 +25,7 @@ pub fn cli() -> Command {
 | avif          | O     | O      | Static only     |
 | bmp           | O     | X      |                 |
 | farbfeld      | O     | O      |                 |
+| gif           | O     | O      |                 |
 | hdr           | O     | O      |                 |
 | jpeg          | O     | O      |                 |
 | jpeg_xl(jxl)  | O     | O      |                 |
@@ -37,6 +43,12 @@ List of supported preprocessing options
 - Quantization
 - Alpha premultiply"#})
         .codecs()
+        .subcommand(analyze());
+
+    #[cfg(feature = "resize")]
+    let cli = cli.subcommand(montage::montage());
+
+    cli
 }
 
 #[cfg(test)]
@@ -47,4 +59,51 @@ mod tests {
     fn verify_app() {
         cli().debug_assert();
     }
+
+    #[test]
+    fn summary_mode() {
+        let matches = cli()
+            .try_get_matches_from(["rimage", "png", "image.png"])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        assert_eq!(matches.get_one::<String>("summary").unwrap(), "auto");
+
+        let matches = cli()
+            .try_get_matches_from(["rimage", "png", "--summary", "never", "image.png"])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        assert_eq!(matches.get_one::<String>("summary").unwrap(), "never");
+
+        assert!(cli()
+            .try_get_matches_from(["rimage", "png", "--summary", "sometimes", "image.png"])
+            .is_err());
+    }
+
+    #[test]
+    fn avif_alpha_mode() {
+        let alpha_mode = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(["rimage", "avif"].iter().chain(args).chain(&["image.png"]))
+                .unwrap();
+            let (_, matches) = matches.subcommand().unwrap();
+
+            matches.get_one::<String>("alpha-mode").unwrap().clone()
+        };
+
+        assert_eq!(alpha_mode(&[]), "unassociated-clean");
+        assert_eq!(
+            alpha_mode(&["--alpha-mode", "premultiplied"]),
+            "premultiplied"
+        );
+        assert_eq!(
+            alpha_mode(&["--alpha_mode", "UnassociatedDirty"]),
+            "UnassociatedDirty"
+        );
+
+        assert!(cli()
+            .try_get_matches_from(["rimage", "avif", "--alpha-mode", "straight", "image.png"])
+            .is_err());
+    }
 }
@@ -0,0 +1,177 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use clap::{arg, value_parser, Command};
+use indoc::indoc;
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, image::Image};
+
+/// Maximum difference between color components for a pixel to be considered gray
+const GRAYSCALE_TOLERANCE: u8 = 2;
+
+pub fn analyze() -> Command {
+    Command::new("analyze")
+        .about("Analyze images and suggest a codec for them.")
+        .long_about(indoc! {r#"Analyze images and suggest a codec for them.
+
+        Reports the number of unique colors, whether the image is effectively grayscale,
+        whether alpha is fully opaque and which codec fits the image best."#})
+        .arg(
+            arg!(files: <FILES> ... "Input file(s) to analyze.")
+                .value_parser(value_parser!(PathBuf)),
+        )
+}
+
+/// Content analysis of an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Analysis {
+    /// Number of unique RGBA colors across all frames
+    pub unique_colors: usize,
+    /// Every pixel has (almost) equal color components
+    pub grayscale: bool,
+    /// Every pixel is fully opaque
+    pub opaque: bool,
+}
+
+impl Analysis {
+    /// Analyzes the image over its 8-bit RGBA pixels
+    pub fn new(image: &Image) -> Result<Self, ImageErrors> {
+        let mut image = image.clone();
+
+        image.convert_depth(BitDepth::Eight)?;
+        image.convert_color(ColorSpace::RGBA)?;
+
+        let mut colors = HashSet::new();
+        let mut grayscale = true;
+        let mut opaque = true;
+
+        for frame in image.flatten_to_u8() {
+            for px in frame.chunks_exact(4) {
+                colors.insert(u32::from_ne_bytes([px[0], px[1], px[2], px[3]]));
+
+                let (min, max) = (px[..3].iter().min(), px[..3].iter().max());
+                grayscale &= max
+                    .zip(min)
+                    .is_some_and(|(max, min)| max - min <= GRAYSCALE_TOLERANCE);
+                opaque &= px[3] == u8::MAX;
+            }
+        }
+
+        Ok(Self {
+            unique_colors: colors.len(),
+            grayscale,
+            opaque,
+        })
+    }
+
+    /// Codec that fits the analyzed image best
+    ///
+    /// Images with few colors compress well losslessly into a palette,
+    /// everything else is treated as a photo and goes to a lossy codec
+    /// that keeps transparency when needed.
+    pub fn suggested_codec(&self) -> &'static str {
+        if self.unique_colors <= 256 {
+            "oxipng"
+        } else if self.opaque {
+            "mozjpeg"
+        } else {
+            "webp"
+        }
+    }
+}
+
+/// Checks if the image is entirely one color or fully transparent
+///
+/// Scans raw samples of every frame, stopping at the first one that differs,
+/// so it's cheap for most images that aren't blank.
+pub fn is_blank(image: &Image) -> bool {
+    let colorspace = image.colorspace();
+    let alpha = colorspace
+        .has_alpha()
+        .then(|| colorspace.num_components() - 1);
+    let size = image.depth().size_of();
+
+    let channels = image
+        .frames_ref()
+        .iter()
+        .flat_map(|frame| frame.channels_ref(colorspace, false).iter().enumerate());
+
+    let (mut transparent, mut solid) = (alpha.is_some(), true);
+    let mut first: Vec<Option<&[u8]>> = vec![None; colorspace.num_components()];
+
+    for (idx, channel) in channels {
+        let samples = unsafe { channel.alias() };
+
+        if Some(idx) == alpha {
+            transparent &= samples.iter().all(|v| *v == 0);
+        }
+
+        if solid {
+            let first = *first[idx].get_or_insert(&samples[..size.min(samples.len())]);
+            solid = samples.chunks_exact(size).all(|v| v == first);
+        }
+
+        if !transparent && !solid {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn three_colors() {
+        let pixels = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 0, 0]].repeat(4 * 4);
+
+        let image = Image::from_u8(pixels.as_flattened(), 8, 8, ColorSpace::RGB);
+
+        let analysis = Analysis::new(&image).unwrap();
+
+        assert_eq!(
+            analysis,
+            Analysis {
+                unique_colors: 3,
+                grayscale: false,
+                opaque: true,
+            }
+        );
+        assert_eq!(analysis.suggested_codec(), "oxipng");
+    }
+
+    #[test]
+    fn blank() {
+        assert!(is_blank(&Image::fill(200u8, ColorSpace::RGB, 64, 32)));
+        assert!(is_blank(&Image::fill(40_000u16, ColorSpace::RGBA, 8, 8)));
+
+        let pixels = (0..64u8)
+            .flat_map(|v| [v, 255 - v, v, 0])
+            .collect::<Vec<_>>();
+        assert!(is_blank(&Image::from_u8(&pixels, 8, 8, ColorSpace::RGBA)));
+
+        let mut pixels = [17u8, 34, 51].repeat(64);
+        pixels[100] = 18;
+        assert!(!is_blank(&Image::from_u8(&pixels, 8, 8, ColorSpace::RGB)));
+
+        let mut pixels = [0u8, 0, 0, 0].repeat(64);
+        pixels[255] = 1;
+        assert!(!is_blank(&Image::from_u8(&pixels, 8, 8, ColorSpace::RGBA)));
+    }
+
+    #[test]
+    fn grayscale_transparent() {
+        let pixels = (0..=255u8)
+            .flat_map(|v| [v, v, v.saturating_add(1), v])
+            .collect::<Vec<_>>();
+
+        let image = Image::from_u8(&pixels, 16, 16, ColorSpace::RGBA);
+
+        let analysis = Analysis::new(&image).unwrap();
+
+        assert_eq!(analysis.unique_colors, 256);
+        assert!(analysis.grayscale);
+        assert!(!analysis.opaque);
+    }
+}
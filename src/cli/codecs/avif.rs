@@ -1,8 +1,10 @@
-use clap::{arg, value_parser, Command};
+use clap::{arg, builder::PossibleValue, value_parser, Command};
 use indoc::indoc;
 
 use crate::cli::common::CommonArgs;
 
+use super::quality_preset;
+
 pub fn avif() -> Command {
     Command::new("avif")
         .about("Encode images into AVIF format. (Small and Efficient)")
@@ -10,8 +12,15 @@ pub fn avif() -> Command {
             arg!(-q --quality <NUM> "Quality which the image will be encoded with.")
                 .value_parser(value_parser!(u8).range(1..=100))
                 .default_value("50"),
-            arg!(--alpha_quality <NUM> "Separate alpha quality which the image will be encoded with.")
-                .value_parser(value_parser!(u8).range(1..=100)),
+            quality_preset(),
+            arg!(--"alpha-quality" <NUM> "Separate alpha quality which the image will be encoded with.")
+                .long_help(indoc! {r#"Separate alpha quality which the image will be encoded with.
+
+                Lower values make alpha channel lossier independently of --quality,
+                which is useful for soft shadows and gradients where alpha artifacts are hard to notice.
+                If not provided, alpha channel is encoded with the same quality as colors."#})
+                .value_parser(value_parser!(u8).range(1..=100))
+                .alias("alpha_quality"),
             arg!(--speed <NUM> "Compression speed (effort).")
                 .long_help(indoc! {r#"Compression speed (effort).
 
@@ -19,11 +28,38 @@ pub fn avif() -> Command {
                 10 = quick, but larger file sizes and lower quality."#})
                 .value_parser(value_parser!(u8).range(1..=10))
                 .default_value("6"),
+            arg!(--passes <NUM> "Number of encodes at decreasing speed, keeping the smallest.")
+                .long_help(indoc! {r#"Number of encodes at decreasing speed, keeping the smallest.
+
+                The first pass uses --speed, every next pass is a separate encode one step slower, down to speed 1.
+                Spends more time for smaller files, without forcing the slowest --speed outright."#})
+                .value_parser(value_parser!(u8).range(1..=10))
+                .default_value("1"),
+            arg!(--depth <BITS> "Bit depth of AVIF being written.")
+                .long_help(indoc! {r#"Bit depth of AVIF being written.
+
+                10 bits reduce banding in smooth gradients at a small size cost.
+                If not provided, opaque images use 10 bits and transparent ones use 8 bits."#})
+                .value_parser(["8", "10"]),
             arg!(--colorspace <COLOR> "Set color space of AVIF being written.")
                 .value_parser(["ycbcr", "rgb"])
                 .default_value("ycbcr"),
-            arg!(--alpha_mode <MODE> "Configure handling of color channels in transparent images.")
-                .value_parser(["UnassociatedDirty", "UnassociatedClean", "Premultiplied"])
-                .default_value("UnassociatedClean")
+            arg!(--"alpha-mode" <MODE> "Configure handling of color channels in transparent images.")
+                .long_help(indoc! {r#"Configure handling of color channels in transparent images.
+
+                Only affects images with transparent pixels, opaque images are encoded the same in every mode."#})
+                .value_parser([
+                    PossibleValue::new("unassociated-clean")
+                        .alias("UnassociatedClean")
+                        .help("Replace colors of fully transparent pixels with colors of their neighbours, compresses better without halos"),
+                    PossibleValue::new("unassociated-dirty")
+                        .alias("UnassociatedDirty")
+                        .help("Keep colors of transparent pixels as is, fastest, but they cost bits and may bleed into edges as halos"),
+                    PossibleValue::new("premultiplied")
+                        .alias("Premultiplied")
+                        .help("Store colors multiplied by alpha, best for soft edges, but requires decoders supporting premultiplied AVIF"),
+                ])
+                .default_value("unassociated-clean")
+                .alias("alpha_mode")
         ]).common_args()
 }
@@ -0,0 +1,24 @@
+use clap::{arg, value_parser, Command};
+use indoc::indoc;
+
+use crate::cli::common::CommonArgs;
+
+pub fn gif() -> Command {
+    Command::new("gif")
+        .about("Encode images into GIF format. (Animated and Compatible)")
+        .args([
+            arg!(-q --quality <NUM> "Quality of the generated palette.")
+                .value_parser(value_parser!(u8).range(1..=100))
+                .default_value("75"),
+            arg!(--no_dither "Disable dithering when reducing colors."),
+            arg!(--delay <MS> "Delay of each animation frame in milliseconds, comma separated.")
+                .long_help(indoc! {r#"Delay of each animation frame in milliseconds, comma separated.
+
+                Delays of animated GIF, APNG and WebP inputs are kept, this only applies to frames without one."#})
+                .value_parser(value_parser!(u16))
+                .value_delimiter(','),
+            arg!(--loops <NUM> "Number of times the animation is repeated, loops forever by default.")
+                .value_parser(value_parser!(u16)),
+        ])
+        .common_args()
+}
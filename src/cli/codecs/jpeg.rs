@@ -2,6 +2,8 @@ use clap::{arg, value_parser, Command};
 
 use crate::cli::common::CommonArgs;
 
+use super::quality_preset;
+
 pub fn jpeg() -> Command {
     Command::new("jpeg")
         .alias("jpg")
@@ -9,6 +11,7 @@ pub fn jpeg() -> Command {
         .args([
             arg!(-q --quality <NUM> "Quality which the image will be encoded with.")
                 .value_parser(value_parser!(u8).range(1..=100)),
+            quality_preset(),
             arg!(--progressive "Set to use progressive encoding."),
         ])
         .common_args()
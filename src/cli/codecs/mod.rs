@@ -1,12 +1,14 @@
-use clap::Command;
+use clap::{arg, Arg, Command};
+use indoc::indoc;
 
 use self::{
-    avif::avif, farbfeld::farbfeld, jpeg::jpeg, jpeg_xl::jpeg_xl, mozjpeg::mozjpeg, oxipng::oxipng,
-    png::png, ppm::ppm, qoi::qoi, webp::webp,
+    avif::avif, farbfeld::farbfeld, gif::gif, jpeg::jpeg, jpeg_xl::jpeg_xl, mozjpeg::mozjpeg,
+    oxipng::oxipng, png::png, ppm::ppm, qoi::qoi, webp::webp,
 };
 
 mod avif;
 mod farbfeld;
+mod gif;
 mod jpeg;
 mod jpeg_xl;
 mod mozjpeg;
@@ -21,6 +23,7 @@ impl Codecs for Command {
         self.subcommands([
             avif(),
             farbfeld(),
+            gif(),
             jpeg(),
             jpeg_xl(),
             mozjpeg(),
@@ -36,3 +39,14 @@ impl Codecs for Command {
 pub trait Codecs {
     fn codecs(self) -> Self;
 }
+
+/// `--quality-preset` option of codecs with a quality setting
+fn quality_preset() -> Arg {
+    arg!(--"quality-preset" <PRESET> "Select quality by a named preset instead of a number.")
+        .long_help(indoc! {r#"Select quality by a named preset instead of a number.
+
+        Presets map to numbers suited for each codec, as the same number looks different across codecs.
+        `lossless` encodes losslessly with WebP and uses the highest quality with other codecs."#})
+        .value_parser(["low", "medium", "high", "lossless"])
+        .conflicts_with("quality")
+}
@@ -1,7 +1,10 @@
 use clap::{arg, value_parser, Command};
+use indoc::indoc;
 
 use crate::cli::common::CommonArgs;
 
+use super::quality_preset;
+
 pub fn mozjpeg() -> Command {
     Command::new("mozjpeg")
         .alias("moz")
@@ -10,10 +13,31 @@ pub fn mozjpeg() -> Command {
             arg!(-q --quality <NUM> "Quality, values 60-80 are recommended.")
                 .value_parser(value_parser!(u8).range(1..=100))
                 .default_value("75"),
-            arg!(--chroma_quality <NUM> "Separate chrome quality.")
-                .value_parser(value_parser!(u8).range(1..=100)),
+            quality_preset(),
+            arg!(--"luma-quality" <NUM> "Separate luma quality.")
+                .long_help(indoc! {r#"Separate luma quality.
+
+                Scales the luma quantization table for this quality instead of --quality,
+                the table selected with --qtable or the default NRobidoux table.
+                Combine with --chroma-quality to set the quality of each component explicitly."#})
+                .value_parser(value_parser!(u8).range(1..=100))
+                .alias("luma_quality"),
+            arg!(--"chroma-quality" <NUM> "Separate chroma quality.")
+                .long_help(indoc! {r#"Separate chroma quality.
+
+                Scales the chroma quantization table for this quality instead of --quality,
+                the table selected with --qtable or the default NRobidoux table."#})
+                .value_parser(value_parser!(u8).range(1..=100))
+                .alias("chroma_quality"),
             arg!(--baseline "Set to use baseline encoding (by default is progressive)."),
             arg!(--no_optimize_coding "Set to make files larger for no reason."),
+            arg!(--fast "Encode faster at the cost of larger files.")
+                .long_help(indoc! {r#"Encode faster at the cost of larger files.
+
+                Disables Huffman table optimization, trellis quantization and scan optimization.
+                Images are still progressive unless --baseline is set.
+                Useful when throughput matters more than size."#})
+                .conflicts_with("multipass"),
             arg!(--smoothing <NUM> "Use MozJPEG's smoothing.")
                 .value_parser(value_parser!(u8).range(1..=100)),
             arg!(--colorspace <COLOR> "Set color space of JPEG being written.")
@@ -23,6 +47,9 @@ pub fn mozjpeg() -> Command {
             arg!(--subsample <PIX> "Sets chroma subsampling.")
                 .value_parser(value_parser!(u8).range(1..=4)),
             arg!(--qtable <TABLE> "Use a specific quantization table.")
+                .long_help(indoc! {r#"Use a specific quantization table.
+
+                By default, MozJpeg picks the NRobidoux table scaled for --quality."#})
                 .value_parser([
                     "AhumadaWatsonPeterson",
                     "AnnexK",
@@ -33,7 +60,13 @@ pub fn mozjpeg() -> Command {
                     "PSNRHVS",
                     "PetersonAhumadaWatson",
                     "WatsonTaylorBorthwick"
-                ])
-                .default_value("NRobidoux")
+                ]),
+            arg!(--qtable_scale <SCALE> "Scale coefficients of the quantization table directly, ignoring quality.")
+                .long_help(indoc! {r#"Scale coefficients of the quantization table directly, ignoring quality.
+
+                Each coefficient of the table selected with --qtable is multiplied by the given factor.
+                Values above 1.0 produce smaller files with lower quality, values below 1.0 do the opposite."#})
+                .value_parser(value_parser!(f32))
+                .alias("qtable-scale")
         ]).common_args()
 }
@@ -14,7 +14,7 @@ pub fn oxipng() -> Command {
             
             Lower levels are faster, higher levels provide better compression, though with increasingly diminishing returns.
 
-            0   => (1 trial, determined heuristically)
+            0   => (no optimization, pixels are stored as is, useful to only rewrite metadata)
             1   => (1 trial, determined heuristically)
             2   => (4 fast trials, 1 main trial)
             3   => (4 trials)
@@ -23,5 +23,19 @@ pub fn oxipng() -> Command {
             6   => (10 trials)"#})
             .value_parser(value_parser!(u8).range(0..=6))
             .default_value("2"),
+            arg!(--bilevel [THRESHOLD] "Encode 1-bit grayscale image with optional threshold.").long_help(indoc! {r#"Encode 1-bit grayscale image with optional threshold (0-255).
+            Pixels with luma of at least the threshold become white, others become black.
+            Useful for scanned line art, which becomes dramatically smaller.
+
+            If threshold is not provided, default 128 is used."#})
+            .value_parser(value_parser!(u8))
+            .default_missing_value("128"),
+            arg!(--"strip-chunks" <MODE> "Strip ancillary chunks from the output.").long_help(indoc! {r#"Strip ancillary chunks from the output.
+
+            none => Keep all chunks
+            safe => Remove chunks that don't affect how the image is displayed, like EXIF
+            all  => Remove all ancillary chunks, including the ICC profile"#})
+            .value_parser(["none", "safe", "all"])
+            .default_value("none"),
         ]).common_args()
 }
@@ -1,21 +1,35 @@
 use clap::{arg, value_parser, Command};
+use indoc::indoc;
 
 use crate::cli::common::CommonArgs;
 
+use super::quality_preset;
+
 pub fn webp() -> Command {
     Command::new("webp")
         .about("Encode images into WebP format. (Lossless-able)")
         .args([
-            arg!(--lossless "Encode image without quality loss.").conflicts_with_all(["quality"]),
+            arg!(--lossless "Encode image without quality loss.")
+                .conflicts_with_all(["quality", "quality-preset"]),
             arg!(-q --quality <NUM> "Quality, values 60-80 are recommended.")
                 .value_parser(value_parser!(u8).range(1..=100))
                 .default_value("75"),
+            quality_preset(),
             arg!(--slight_loss <NUM> "Slight loss in quality for lossless encoding.")
                 .value_parser(value_parser!(u8).range(0..=100))
                 .default_value("0")
                 .requires("lossless"),
             arg!(--discrete "Discrete tone image.").requires("lossless"),
             arg!(--exact "Preserve transparent data."),
+            arg!(--method <NUM> "Compression method, trading encoding speed for quality.")
+                .long_help(
+                    indoc! {r#"Compression method, trading encoding speed for quality.
+
+                Higher values are slower, but produce smaller or better looking images.
+                0 = fastest, 6 = slowest."#},
+                )
+                .value_parser(value_parser!(i32).range(0..=6))
+                .default_value("4"),
         ])
         .common_args()
 }
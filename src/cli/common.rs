@@ -1,20 +1,29 @@
 use std::path::PathBuf;
 
-use clap::{arg, value_parser, Command};
+use clap::{arg, value_parser, ArgAction, Command};
 use indoc::indoc;
 
-use super::{preprocessors::Preprocessors, utils::threads};
+#[cfg(feature = "resize")]
+use super::utils::srcset::Srcset;
+use super::{
+    preprocessors::Preprocessors,
+    utils::{complexity::QualityRange, threads},
+};
+
+/// Largest ratio of `--size-hint`, so bogus values can't reserve huge buffers
+const MAX_SIZE_HINT: f64 = 16.;
 
 impl CommonArgs for Command {
     fn common_args(self) -> Self {
         self
         .next_help_heading("General")
         .arg(
-            arg!(files: <FILES> ... "Input file(s) to process.")
+            arg!(files: [FILES] ... "Input file(s) to process.")
                 .long_help(indoc! {r#"Input file(s) to process.
 
                 If the file path contains spaces, enclose the path with double quotation marks on both sides."#})
-                .value_parser(value_parser!(PathBuf)),
+                .value_parser(value_parser!(PathBuf))
+                .required_unless_present("from-zip"),
         )
         .arg(
             arg!(-d --directory <DIR> "The directory to write output file(s) to.")
@@ -30,6 +39,39 @@ impl CommonArgs for Command {
                 This option should be used in conjunction with the --directory option."#})
                 .requires("directory"),
         )
+        .arg(
+            arg!(--"max-depth" <N> "Limits how deep input directories are traversed.")
+                .long_help(indoc! {r#"Limits how deep input directories are traversed.
+
+                Files directly inside an input directory have depth 1.
+                By default, input directories are traversed completely."#})
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            arg!(--exclude <GLOB> "Skips files and directories matching the pattern.")
+                .long_help(indoc! {r#"Skips files and directories matching the pattern.
+
+                Patterns are matched against file names and paths relative to
+                the input directory, e.g. "*.gif" or "drafts/**".
+                Can be used multiple times."#})
+                .value_parser(|s: &str| glob::Pattern::new(s))
+                .action(ArgAction::Append),
+        )
+        .arg(
+            arg!(--"respect-ignore" "Skips files ignored by .gitignore and .ignore files.")
+                .long_help(indoc! {r#"Skips files ignored by .gitignore and .ignore files.
+
+                Ignore files are looked up in input directories and their parents,
+                the same way git does, even outside of a git repository."#}),
+        )
+        .arg(
+            arg!(--"all-files" "Processes every file found in input directories, not only recognized images.")
+                .long_help(indoc! {r#"Processes every file found in input directories, not only recognized images.
+
+                By default files inside input directories are checked by their signature and the ones
+                no enabled codec can decode, like text files, are skipped. This reads the start of each file,
+                with this flag files are passed to decoders as is and non-images are reported as errors."#}),
+        )
         .arg(
             arg!(-s --suffix [SUFFIX] "Adds the '@suffix' to the names of output file(s).")
                 .long_help(indoc! {r#"Adds the '@suffix' to the names of output file(s).
@@ -41,7 +83,197 @@ impl CommonArgs for Command {
                 .default_missing_value("updated"),
         )
         .arg(
-            arg!(-b --backup "Adds the '@backup' to the names of input file(s).")
+            arg!(--"name-template" <TEMPLATE> "Names output file(s) after the template.")
+                .long_help(indoc! {r#"Names output file(s) after the template.
+
+                Supported placeholders are {stem}, {width}, {height}, {codec}, {ext} and {quality},
+                for example '{stem}-{width}x{height}.{ext}' turns 'photo.png' into 'photo-640x480.jpg'.
+                The template sets the whole file name, including the extension. Use '{{' and '}}' for literal braces."#})
+                .conflicts_with_all(["suffix", "append-extension"]),
+        )
+        .arg(
+            arg!(--"overwrite-policy" <POLICY> "What to do when output file(s) already exist.")
+                .long_help(indoc! {r#"What to do when output file(s) already exist.
+
+                - skip:      Leave existing file(s) as is and skip the output
+                - overwrite: Replace existing file(s)
+                - rename:    Write output next to existing file(s) with a numeric suffix, like 'image-1.png'"#})
+                .value_parser(["skip", "overwrite", "rename"])
+                .default_value("overwrite"),
+        )
+        .args([
+            #[cfg(feature = "resize")]
+            arg!(--srcset <SET> "Writes a responsive set of output file(s) resized to each width.")
+                .long_help(indoc! {r#"Writes a responsive set of output file(s) resized to each width.
+
+                The value is a comma separated list of widths with optional qualities, like '480:75,800:80,1200'.
+                Every image is decoded once and written as '{stem}-480w.{ext}' for each width,
+                keeping its aspect ratio. Images narrower than a width are never upscaled.
+                Qualities are ignored for codecs without a quality option."#})
+                .value_parser(value_parser!(Srcset))
+                .conflicts_with_all([
+                    "resize",
+                    "preset-size",
+                    "name-template",
+                    "suffix",
+                    "append-extension",
+                    "target-quality",
+                    "adaptive-quality",
+                ]),
+            arg!(-b --backup "Adds the '@backup' to the names of input file(s)."),
+        ])
+        .arg(
+            arg!(--"strip-gps" "Removes GPS location from the metadata of output file(s).")
+                .long_help(indoc! {r#"Removes GPS location from the metadata of output file(s).
+
+                Other metadata, like camera information or orientation, is kept."#})
+        )
+        .arg(
+            arg!(--"keep-makernote" "Keeps MakerNote and Interoperability EXIF data of input file(s).")
+                .long_help(indoc! {r#"Keeps MakerNote and Interoperability EXIF data of input file(s).
+
+                MakerNotes hold vendor specific data, like as-shot white balance of RAW-derived images.
+                By default they are removed, as their internal offsets are often broken by re-encoding."#})
+        )
+        .arg(
+            arg!(--"no-auto-orient" "Keeps pixel orientation of input file(s) as is.")
+                .long_help(indoc! {r#"Keeps pixel orientation of input file(s) as is.
+
+                By default, images are rotated and flipped according to their EXIF orientation tag.
+                With this flag pixels are left untouched, so orientation relies on the tag if it is preserved."#})
+        )
+        .arg(
+            arg!(--"set-orientation" <ORIENTATION> "Writes this EXIF orientation to output file(s) without rotating pixels.")
+                .long_help(indoc! {r#"Writes this EXIF orientation to output file(s) without rotating pixels.
+
+                Values are from 1 to 8, as defined by the EXIF specification, like 6 for 90 degrees clockwise.
+                Useful to fix images from cameras that tag orientation wrongly.
+                The tag is written after input file(s) are auto-oriented, combine with --no-auto-orient
+                to only replace the tag of input file(s)."#})
+                .value_parser(value_parser!(u16).range(1..=8))
+        )
+        .arg(
+            arg!(--"raw-convert" "Converts format of input file(s) only, skipping all operations.")
+                .long_help(indoc! {r#"Converts format of input file(s) only, skipping all operations.
+
+                Pixels are only converted to the depth and colorspace required by the codec, images are
+                neither rotated according to their EXIF orientation nor converted to sRGB.
+                Can't be combined with options requesting operations, like --resize or --quantization."#})
+                .conflicts_with_all([
+                    #[cfg(feature = "resize")]
+                    "resize",
+                    #[cfg(feature = "resize")]
+                    "preset-size",
+                    #[cfg(feature = "quantization")]
+                    "quantization",
+                    #[cfg(feature = "quantization")]
+                    "max-colors",
+                    #[cfg(feature = "quantization")]
+                    "dithering",
+                    "premultiply",
+                    "alpha-premultiplied",
+                    "assume-profile",
+                ])
+        )
+        .arg(
+            arg!(--"dither-depth" "Dithers high bit depth input file(s) when reducing them to 8 bits.")
+                .long_help(indoc! {r#"Dithers high bit depth input file(s) when reducing them to 8 bits.
+
+                16-bit and float images are converted to 8 bits before processing. By default values are
+                truncated, which can turn smooth gradients into visible bands. With this flag the rounding
+                error is diffused to neighbouring pixels instead."#})
+        )
+        .arg(
+            arg!(--"first-frame-only" "Keeps only the first frame of animated input file(s).")
+                .long_help(indoc! {r#"Keeps only the first frame of animated input file(s).
+
+                Other frames are dropped before any operation runs, so the output is a still image.
+                Without this flag, codecs unable to encode animations keep the first frame as well,
+                but warn about discarded frames."#})
+        )
+        .arg(
+            arg!(--"on-alpha" <POLICY> "What to do with transparent input file(s) when the codec can't store alpha.")
+                .long_help(indoc! {r#"What to do with transparent input file(s) when the codec can't store alpha.
+
+                - flatten: Composite image(s) onto a white background silently
+                - warn:    Composite image(s) onto a white background and report it with a warning
+                - error:   Fail instead of writing the output
+
+                Applies to codecs without alpha support, like JPEG and PPM."#})
+                .value_parser(["flatten", "warn", "error"])
+                .default_value("flatten"),
+        )
+        .args([
+            #[cfg(all(feature = "metadata", feature = "resize"))]
+            arg!(--"extract-thumbnail" "Writes thumbnails of input file(s) instead of full images.")
+                .long_help(indoc! {r#"Writes thumbnails of input file(s) instead of full images.
+
+                JPEG thumbnails embedded into EXIF metadata are written as is, without decoding the full image.
+                Input file(s) without one are downscaled to fit 160x160 and encoded with the selected codec."#}),
+            arg!(--"skip-blank" "Skips input file(s) that are entirely one color or fully transparent.")
+                .long_help(indoc! {r#"Skips input file(s) that are entirely one color or fully transparent.
+
+                Blank images are reported with a warning and no output is written for them.
+                Useful for asset pipelines, where such images are usually placeholders or export mistakes."#}),
+        ])
+        .arg(
+            arg!(--"keep-icc" "Keeps ICC profile of input file(s) instead of converting them to sRGB.")
+                .long_help(indoc! {r#"Keeps ICC profile of input file(s) instead of converting them to sRGB.
+
+                Wide-gamut images, like Display P3, keep their colors and profile is embedded into output file(s).
+                Only applies to codecs able to embed ICC profiles, other codecs still convert images to sRGB."#})
+                .conflicts_with("auto-codec")
+        )
+        .arg(
+            arg!(--"assume-profile" <FILE> "ICC profile to assume for input file(s) without embedded profile.")
+                .long_help(indoc! {r#"ICC profile to assume for input file(s) without embedded profile.
+
+                Untagged images are converted to sRGB from this profile, images with embedded profile are not affected.
+                By default, untagged images are assumed to be sRGB and left as is."#})
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with("keep-icc")
+        )
+        .arg(
+            arg!(--icc <FILE> "Converts image(s) to this ICC profile and embeds it.")
+                .long_help(indoc! {r#"Converts image(s) to this ICC profile and embeds it.
+
+                The file must be a valid ICC profile that 8-bit RGBA images can be converted to,
+                it's embedded into output file(s) as is.
+                Only codecs able to embed ICC profiles tag output file(s), other codecs still
+                convert colors but write untagged file(s).
+                Can't be used with '--auto-codec', as the picked codec might not embed the profile.
+                By default, images are converted to sRGB."#})
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with_all(["keep-icc", "auto-codec"])
+        )
+        .arg(
+            arg!(--extension <EXT> "Overrides the extension of output file(s).")
+                .long_help(indoc! {r#"Overrides the extension of output file(s).
+
+                The value is used as is, so casing is preserved. For example, '--extension JPG' will produce 'file.JPG'.
+                By default, the extension is chosen based on the selected codec."#})
+        )
+        .arg(
+            arg!(--"replace-extension" "Replaces the extension of input file(s) with the new one (default).")
+                .long_help(indoc! {r#"Replaces the extension of input file(s) with the new one (default).
+
+                For example, 'photo.jpg' encoded to WebP becomes 'photo.webp'."#})
+                .overrides_with("append-extension"),
+        )
+        .arg(
+            arg!(--"append-extension" "Appends the new extension to the extension of input file(s).")
+                .long_help(indoc! {r#"Appends the new extension to the extension of input file(s).
+
+                For example, 'photo.jpg' encoded to WebP becomes 'photo.jpg.webp'."#})
+                .overrides_with("replace-extension"),
+        )
+        .arg(
+            arg!(--"jpeg-ext" <EXT> "The extension to use for JPEG output file(s).")
+                .long_help(indoc! {r#"The extension to use for JPEG output file(s).
+
+                Only applies to codecs producing JPEG images and is ignored when --extension is provided."#})
+                .value_parser(["jpg", "jpeg"])
+                .default_value("jpg"),
         )
         .arg(
             arg!(-t --threads <NUM> "The number of threads for concurrent processing.")
@@ -51,6 +283,124 @@ impl CommonArgs for Command {
                 By default, the number of available threads is utilized."#})
                 .value_parser(value_parser!(u8).range(1..=threads::num_threads() as i64)),
         )
+        .arg(
+            arg!(--"auto-codec" "Chooses codec for each file based on its content.")
+                .long_help(indoc! {r#"Chooses codec for each file based on its content.
+
+                Images with few colors are encoded losslessly with oxipng, photos with a lossy codec.
+                The selected codec is kept when it suits the image, otherwise the chosen codec uses its default options."#})
+        )
+        .arg(
+            arg!(--"concurrent-io" "Overlaps decoding of next file(s) with encoding of previous ones.")
+                .long_help(indoc! {r#"Overlaps decoding of next file(s) with encoding of previous ones.
+
+                Files are decoded one by one on a separate thread, while already decoded files are processed and encoded concurrently.
+                Only a limited number of decoded files is kept in memory at once. Useful for large batches."#})
+                .alias("pipeline")
+        )
+        .arg(
+            arg!(--"size-hint" <RATIO> "Reserves memory for encoded file(s) upfront, as a ratio of the input size.")
+                .long_help(indoc! {r#"Reserves memory for encoded file(s) upfront, as a ratio of the input size.
+
+                Output is encoded into memory sized to the input file size times the ratio, like 0.5 for
+                outputs about half the size of inputs, and written out at once. Close estimates avoid
+                growing the buffer over and over, speeding up large batches. Values are from 0 to 16,
+                at most 256 MiB are reserved per file. AVIF, WebP, OxiPNG and GIF encoders buffer output
+                on their own and ignore the hint. By default, encoders write to output file(s) directly."#})
+                .value_parser(|s: &str| match s.parse::<f64>() {
+                    Ok(ratio) if (0.0..=MAX_SIZE_HINT).contains(&ratio) => Ok(ratio),
+                    _ => Err(format!("expected a ratio from 0 to {MAX_SIZE_HINT}")),
+                })
+        )
+        .arg(
+            arg!(--"temp-dir" <DIR> "The directory to write temporary file(s) to.")
+                .long_help(indoc! {r#"The directory to write temporary file(s) to.
+
+                Output files are first written to temporary files and moved into place once encoding succeeds,
+                so interrupted runs don't leave partial files behind.
+                By default, temporary files are created next to the output file(s)."#})
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"from-zip" <ARCHIVE> "Processes image(s) inside a ZIP archive.")
+                .long_help(indoc! {r#"Processes image(s) inside a ZIP archive.
+
+                Files of the archive are extracted into the temp directory and processed along with other input file(s).
+                Output files keep the folder structure of the archive and are written to the directory provided
+                with --directory, or next to the archive by default."#})
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"to-zip" <ARCHIVE> "Writes output file(s) into a new ZIP archive.")
+                .long_help(indoc! {r#"Writes output file(s) into a new ZIP archive.
+
+                Output files are written into the temp directory first and packed into the archive afterwards.
+                Images from --from-zip keep the folder structure of their archive, other file(s) are placed at the root."#})
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with("directory"),
+        )
+        .arg(
+            arg!(--deterministic "Makes runs with the same input produce identical output.")
+                .long_help(indoc! {r#"Makes runs with the same input produce identical output.
+
+                Quantization isn't random, but isn't deterministic across threads either,
+                so with this flag it runs on a single thread per image."#}),
+        )
+        .arg(
+            arg!(--"target-quality" <DSSIM> "Picks the lowest quality keeping DSSIM at or below the target.")
+                .long_help(indoc! {r#"Picks the lowest quality keeping DSSIM at or below the target.
+
+                Quality of the codec is binary-searched, comparing each encoded image with the
+                source. Lower DSSIM means closer to the source, 0 is identical.
+                Values around 0.001-0.01 are visually lossless for most images.
+                Ignored for codecs without the --quality option."#})
+                .value_parser(value_parser!(f64))
+        )
+        .arg(
+            arg!(--"adaptive-quality" <RANGE> "Picks quality within the range by complexity of each image.")
+                .long_help(indoc! {r#"Picks quality within the range by complexity of each image.
+
+                The value is a range of qualities, like '60-90'. Detail of each image is estimated by its
+                mean gradient, flat images, like screenshots or illustrations, get qualities close to the minimum,
+                while detailed photos get qualities close to the maximum.
+                Overrides the --quality option, ignored for codecs without it."#})
+                .value_parser(value_parser!(QualityRange))
+                .conflicts_with("target-quality")
+        )
+        .arg(
+            arg!(--"write-diff" <PATH> "Writes an amplified difference between each image and its encoded output.")
+                .long_help(indoc! {r#"Writes an amplified difference between each image and its encoded output.
+
+                Output file(s) are decoded back and compared with the processed image, brighter pixels
+                mark bigger differences. The diff is written as PNG. If the path is an existing directory,
+                each diff is written into it, named after its output file, like 'image.diff.png'.
+                Otherwise the path must be a file and only a single output file can be written."#})
+                .value_parser(value_parser!(PathBuf))
+        )
+        .arg(
+            arg!(--verify "Checks that output file(s) decode to exactly the encoded pixels.")
+                .long_help(indoc! {r#"Checks that output file(s) decode to exactly the encoded pixels.
+
+                Meant for lossless codecs, like oxipng, png or webp with --lossless. Each output is decoded
+                before it's written and compared with the processed image, outputs with any changed pixel
+                are reported as errors and not written. Lossy codecs always fail verification.
+                Note that webp replaces colors of transparent pixels unless --exact is used."#})
+        )
+        .arg(
+            arg!(--csv <FILE> "Writes a CSV report of processed file(s).")
+                .long_help(indoc! {r#"Writes a CSV report of processed file(s).
+
+                Each row contains input and output paths, their sizes, size ratio, output dimensions and processing time in milliseconds."#})
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"summary-json" <FILE> "Writes a JSON summary of processed file(s).")
+                .long_help(indoc! {r#"Writes a JSON summary of processed file(s).
+
+                The summary has total count, input and output sizes and average size ratio of processed file(s),
+                and the same fields for every output codec in the `byFormat` map, useful with --auto-codec."#})
+                .value_parser(value_parser!(PathBuf)),
+        )
         .arg(
             arg!(--"no-progress" "Disables progress bar.")
                 .long_help(indoc! {r#"Disables progress bar.
@@ -63,6 +413,32 @@ impl CommonArgs for Command {
 
                 By default, all output is enabled."#})
         )
+        .arg(
+            arg!(--"log-json" "Writes log messages as JSON lines.")
+                .long_help(indoc! {r#"Writes log messages as JSON lines.
+
+                Each line is an object with `level`, `message` and `file` fields, `file` is
+                null for messages not related to a file. Meant for CI and other automation
+                parsing errors of processed file(s)."#})
+        )
+        .arg(
+            arg!(--summary <MODE> "Controls the summary printed after processing.")
+                .long_help(indoc! {r#"Controls the summary printed after processing.
+
+                - auto:   Print the summary unless the --quiet flag is used
+                - never:  Never print the summary, keeping stdout clean
+                - always: Print the summary even if the --quiet flag is used"#})
+                .value_parser(["auto", "never", "always"])
+                .default_value("auto"),
+        )
+        .arg(
+            arg!(--"print-pipeline" "Prints operations applied to each image without processing it.")
+                .long_help(indoc! {r#"Prints operations applied to each image without processing it.
+
+                Operations are listed in the order they run, with the index of the
+                argument that requested them. Useful to check how --resize, --quantization
+                and --premultiply interleave."#})
+        )
         .preprocessors()
     }
 }
@@ -0,0 +1,184 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use clap::parser::ValueSource;
+use serde::Deserialize;
+
+use super::{cli, sidecar::option};
+
+/// Path of the user config, `$XDG_CONFIG_HOME/rimage/config.toml` or `~/.config/rimage/config.toml`
+pub fn config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+
+    Some(dir.join("rimage").join("config.toml"))
+}
+
+/// User defaults of the command line settings
+///
+/// Every key is optional, options given on the command line take precedence:
+///
+/// ```toml
+/// codec = "mozjpeg"
+/// quality = 80
+/// suffix = "min"
+/// threads = 4
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Codec used when the command line doesn't select one
+    pub codec: Option<String>,
+    /// Value of `--quality`, for codecs that have it
+    pub quality: Option<u8>,
+    /// Value of `--suffix`
+    pub suffix: Option<String>,
+    /// Value of `--threads`
+    pub threads: Option<u8>,
+}
+
+impl Config {
+    /// Loads the config at `path`, if there is one
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map(Some)
+                .with_context(|| format!("invalid config {}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("unable to read {}", path.display())),
+        }
+    }
+
+    /// Inserts the defaults into command line arguments, skipping options already present
+    ///
+    /// The codec is inserted only if the first argument is neither a codec nor the help
+    /// subcommand, help or version flag, so `rimage image.png` becomes `rimage <codec> image.png`.
+    /// Options the selected codec doesn't have are ignored.
+    pub fn apply(&self, mut args: Vec<OsString>) -> Vec<OsString> {
+        let cli = cli();
+
+        if let Some(codec) = &self.codec {
+            let first = args.get(1).and_then(|arg| arg.to_str());
+
+            if first.is_some_and(|arg| {
+                !["help", "-h", "--help", "-V", "--version"].contains(&arg)
+                    && cli.find_subcommand(arg).is_none()
+            }) {
+                args.insert(1, codec.into());
+            }
+        }
+
+        let Ok(matches) = cli.clone().try_get_matches_from(&args) else {
+            return args;
+        };
+        let Some((name, matches)) = matches.subcommand() else {
+            return args;
+        };
+        let command = cli.find_subcommand(name).expect("subcommand is parsed");
+
+        let defaults = [
+            ("quality", self.quality.map(|quality| quality.to_string())),
+            ("suffix", self.suffix.clone()),
+            ("threads", self.threads.map(|threads| threads.to_string())),
+        ];
+
+        for (long, value) in defaults {
+            let Some(value) = value else {
+                continue;
+            };
+
+            if command
+                .get_arguments()
+                .any(|arg| arg.get_long() == Some(long))
+                && matches.value_source(long) != Some(ValueSource::CommandLine)
+            {
+                args.insert(2, option(long, value.as_ref()));
+            }
+        }
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    fn parse(config: &Config, args: &[&str]) -> (String, Option<u8>, Option<String>) {
+        let args = config.apply(args.iter().map(OsString::from).collect());
+        let matches = cli().try_get_matches_from(args).unwrap();
+        let (codec, matches) = matches.subcommand().unwrap();
+
+        (
+            codec.to_string(),
+            matches
+                .try_get_one::<u8>("quality")
+                .unwrap_or_default()
+                .copied(),
+            matches.get_one::<String>("suffix").cloned(),
+        )
+    }
+
+    #[test]
+    fn config_defaults() {
+        let dir = TempDir::new("config_defaults");
+
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            "codec = \"mozjpeg\"\nquality = 60\nsuffix = \"min\"\nthreads = 1",
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap().unwrap();
+
+        assert_eq!(
+            parse(&config, &["rimage", "image.png"]),
+            ("mozjpeg".to_string(), Some(60), Some("min".to_string()))
+        );
+        assert_eq!(
+            parse(
+                &config,
+                &["rimage", "webp", "-q", "90", "--suffix=big", "image.png"]
+            ),
+            ("webp".to_string(), Some(90), Some("big".to_string()))
+        );
+        assert_eq!(
+            parse(&config, &["rimage", "--quiet", "image.png"]),
+            ("mozjpeg".to_string(), Some(60), Some("min".to_string()))
+        );
+        assert_eq!(
+            parse(&config, &["rimage", "png", "image.png"]),
+            ("png".to_string(), None, Some("min".to_string()))
+        );
+
+        for args in [
+            &["rimage", "help"][..],
+            &["rimage", "help", "png"],
+            &["rimage", "--help"],
+            &["rimage", "-V"],
+            &["rimage", "--version"],
+        ] {
+            let args = args.iter().map(OsString::from).collect::<Vec<_>>();
+
+            assert_eq!(config.apply(args.clone()), args);
+        }
+
+        assert!(Config::load(&dir.join("missing.toml")).unwrap().is_none());
+
+        fs::write(&path, "colour = \"blue\"").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+}
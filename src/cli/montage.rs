@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use clap::{arg, builder::PossibleValuesParser, value_parser, Command};
+use indoc::indoc;
+use rimage::operations::{
+    icc::ApplySRGB,
+    resize::{FilterType, Resize, ResizeAlg},
+    select_frame::SelectFrame,
+};
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{
+    core_filters::{colorspace::ColorspaceConv, depth::Depth},
+    errors::ImageErrors,
+    image::Image,
+    traits::OperationsTrait,
+};
+use zune_imageprocs::auto_orient::AutoOrient;
+
+use super::codecs::Codecs;
+
+pub fn montage() -> Command {
+    Command::new("montage")
+        .about("Arrange images into a contact sheet.")
+        .long_about(indoc! {r#"Arrange images into a contact sheet.
+
+        Every image is scaled down to fit into a square cell, keeping its aspect ratio,
+        and placed into a grid in the order the files are listed. Empty space is transparent,
+        or white with codecs that can't store alpha."#})
+        .arg(
+            arg!(files: <FILES> ... "Input file(s) to put on the sheet.")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(-o --output <FILE> "Write the contact sheet to the specified file.")
+                .value_parser(value_parser!(PathBuf))
+                .required(true),
+        )
+        .arg(
+            arg!(--codec <CODEC> "Codec used to encode the contact sheet.")
+                .long_help(indoc! {r#"Codec used to encode the contact sheet.
+
+                Accepts any codec subcommand name, its options are left at their defaults."#})
+                .value_parser(PossibleValuesParser::new(
+                    Command::new("codecs")
+                        .codecs()
+                        .get_subcommands()
+                        .map(|codec| codec.get_name().to_owned()),
+                ))
+                .default_value("png"),
+        )
+        .arg(
+            arg!(--columns <NUM> "Number of columns in the grid.")
+                .long_help(indoc! {r#"Number of columns in the grid.
+
+                Defaults to a roughly square grid."#})
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            arg!(--cell <SIZE> "Width and height of a grid cell in pixels.")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("160"),
+        )
+}
+
+/// Converts the first frame of `img` into an 8-bit RGBA thumbnail fitting into `cell`
+pub fn thumbnail(mut img: Image, cell: usize) -> Result<Image, ImageErrors> {
+    let operations: [Box<dyn OperationsTrait>; 6] = [
+        Box::new(SelectFrame::new(0)),
+        Box::new(Depth::new(BitDepth::Eight)),
+        Box::new(ColorspaceConv::new(ColorSpace::RGBA)),
+        Box::new(AutoOrient),
+        Box::new(ApplySRGB),
+        Box::new(Resize::downscale_only(
+            cell,
+            cell,
+            ResizeAlg::Convolution(FilterType::Lanczos3),
+        )),
+    ];
+
+    for operation in operations {
+        operation.execute(&mut img)?;
+    }
+
+    Ok(img)
+}
+
+/// Places `thumbnails` into a grid of `cell` sized squares
+///
+/// Thumbnails must be 8-bit RGBA images fitting into a cell, see [`thumbnail`].
+/// Each one is centered in its cell, filling rows from left to right.
+pub fn montage_images(
+    thumbnails: &[Image],
+    columns: usize,
+    cell: usize,
+) -> Result<Image, ImageErrors> {
+    if thumbnails.is_empty() {
+        return Err(ImageErrors::GenericStr("no images to put on the sheet"));
+    }
+
+    let columns = columns.clamp(1, thumbnails.len());
+    let rows = thumbnails.len().div_ceil(columns);
+    let (width, height) = (columns * cell, rows * cell);
+
+    let mut canvas = vec![0u8; width * height * 4];
+
+    for (idx, thumbnail) in thumbnails.iter().enumerate() {
+        let (w, h) = thumbnail.dimensions();
+
+        if w > cell || h > cell {
+            return Err(ImageErrors::GenericString(format!(
+                "image {idx} is {w}x{h}, but the cell is {cell}x{cell}"
+            )));
+        }
+
+        let x = idx % columns * cell + (cell - w) / 2;
+        let y = idx / columns * cell + (cell - h) / 2;
+
+        let pixels = &thumbnail.flatten_to_u8()[0];
+
+        pixels
+            .chunks_exact(w * 4)
+            .zip(canvas.chunks_exact_mut(width * 4).skip(y))
+            .for_each(|(src, dst)| dst[x * 4..(x + w) * 4].copy_from_slice(src));
+    }
+
+    Ok(Image::from_u8(&canvas, width, height, ColorSpace::RGBA))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_row() {
+        let thumbnails = [(40, 20, 10u8), (20, 40, 20), (8, 8, 30)]
+            .into_iter()
+            .map(|(w, h, v)| thumbnail(Image::fill(v, ColorSpace::RGB, w, h), 16).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(thumbnails[0].dimensions(), (16, 8));
+        assert_eq!(thumbnails[1].dimensions(), (8, 16));
+        assert_eq!(thumbnails[2].dimensions(), (8, 8));
+
+        let sheet = montage_images(&thumbnails, 3, 16).unwrap();
+
+        assert_eq!(sheet.dimensions(), (48, 16));
+        assert_eq!(sheet.colorspace(), ColorSpace::RGBA);
+
+        let pixels = &sheet.flatten_to_u8()[0];
+        let px = |x: usize, y: usize| &pixels[(y * 48 + x) * 4..(y * 48 + x) * 4 + 4];
+
+        assert_eq!(px(0, 0), [0, 0, 0, 0]);
+        assert_eq!(px(0, 4), [10, 10, 10, 255]);
+        assert_eq!(px(20, 0), [20, 20, 20, 255]);
+        assert_eq!(px(16, 0), [0, 0, 0, 0]);
+        assert_eq!(px(40, 8), [30, 30, 30, 255]);
+        assert_eq!(px(47, 8), [0, 0, 0, 0]);
+
+        let sheet = montage_images(&thumbnails, 2, 16).unwrap();
+
+        assert_eq!(sheet.dimensions(), (32, 32));
+    }
+
+    #[test]
+    fn codec_validated() {
+        let codec = |codec: &str| {
+            crate::cli::cli().try_get_matches_from([
+                "rimage",
+                "montage",
+                "-o",
+                "sheet",
+                "--codec",
+                codec,
+                "image.png",
+            ])
+        };
+
+        assert!(codec("mozjpeg").is_ok());
+        assert!(codec("qoi").is_ok());
+        assert!(codec("bmp").is_err());
+    }
+}
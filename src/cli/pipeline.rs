@@ -1,106 +1,235 @@
 use std::io::{Seek, SeekFrom};
-use std::{collections::BTreeMap, fs::File, io::Read, path::Path};
+use std::{fs::File, io::Read, path::Path};
 
 use clap::ArgMatches;
 #[cfg(feature = "avif")]
 use rimage::codecs::avif::AvifEncoder;
+#[cfg(feature = "gif")]
+use rimage::codecs::gif::GifEncoder;
 #[cfg(feature = "mozjpeg")]
 use rimage::codecs::mozjpeg::MozJpegEncoder;
 #[cfg(feature = "oxipng")]
 use rimage::codecs::oxipng::OxiPngEncoder;
 #[cfg(feature = "webp")]
 use rimage::codecs::webp::WebPEncoder;
-use zune_core::{bytestream::ZByteWriterTrait, options::EncoderOptions};
+use rimage::{
+    codecs::OutputFormat,
+    operations::{
+        alpha::{is_opaque, Flatten},
+        grayscale::Grayscale,
+        select_frame::SelectFrame,
+    },
+};
+use zune_core::{
+    bytestream::{ZByteWriterTrait, ZCursor},
+    colorspace::ColorSpace,
+    options::{DecoderOptions, EncoderOptions},
+};
 use zune_image::{
     codecs::{
         farbfeld::FarbFeldEncoder, jpeg::JpegEncoder, jpeg_xl::JxlEncoder, png::PngEncoder,
         ppm::PPMEncoder, qoi::QoiEncoder, ImageFormat,
     },
-    errors::ImageErrors,
+    errors::{ImageErrors, ImgEncodeErrors},
     image::Image,
     metadata::AlphaState,
     traits::{EncoderTrait, OperationsTrait},
 };
-use zune_imageprocs::premul_alpha::PremultiplyAlpha;
+use zune_imageprocs::{auto_orient::AutoOrient, premul_alpha::PremultiplyAlpha};
+
+use crate::cli::{
+    cli,
+    utils::{
+        complexity::{complexity, QualityRange},
+        dssim::dssim,
+    },
+};
 
 pub fn decode<P: AsRef<Path>>(f: P) -> Result<Image, ImageErrors> {
-    Image::open(f.as_ref()).or_else(|e| {
-        if matches!(e, ImageErrors::ImageDecoderNotImplemented(_)) {
-            #[cfg(any(feature = "avif", feature = "webp"))]
-            let mut file = File::open(f.as_ref())?;
+    decode_with_delays(f).map(|(img, _)| img)
+}
 
-            #[cfg(feature = "avif")]
-            {
-                let mut file_content = vec![];
+/// Decodes the image along with delays of its animation frames in milliseconds
+///
+/// zune-image frames don't expose their delays, so they are taken from
+/// the decoders of animated formats, other images have no delays.
+pub fn decode_with_delays<P: AsRef<Path>>(f: P) -> Result<(Image, Vec<u16>), ImageErrors> {
+    // zune decodes APNG frames without honoring their delays and disposal
+    #[cfg(feature = "apng")]
+    if f.as_ref()
+        .extension()
+        .is_some_and(|f| f.eq_ignore_ascii_case("png") | f.eq_ignore_ascii_case("apng"))
+    {
+        use rimage::codecs::apng::ApngDecoder;
+        use zune_image::traits::DecoderTrait;
 
-                file.read_to_end(&mut file_content)?;
-                file.seek(SeekFrom::Start(0))?;
+        let mut decoder = ApngDecoder::try_new(File::open(f.as_ref())?)?;
 
-                if libavif::is_avif(&file_content) {
-                    use rimage::codecs::avif::AvifDecoder;
+        if decoder.is_animated() {
+            let img = decoder.decode()?;
 
-                    let decoder = AvifDecoder::try_new(file)?;
+            return Ok((img, decoder.delays().to_vec()));
+        }
+    }
 
-                    return Image::from_decoder(decoder);
-                };
-                file.seek(SeekFrom::Start(0))?;
-            }
+    Image::open(f.as_ref())
+        .map(|img| (img, vec![]))
+        .or_else(|e| {
+            if matches!(e, ImageErrors::ImageDecoderNotImplemented(_)) {
+                #[cfg(any(feature = "avif", feature = "webp", feature = "tiff", feature = "gif"))]
+                let mut file = File::open(f.as_ref())?;
 
-            #[cfg(feature = "webp")]
-            {
-                if f.as_ref()
-                    .extension()
-                    .is_some_and(|f| f.eq_ignore_ascii_case("webp"))
+                #[cfg(feature = "avif")]
+                {
+                    let mut file_content = vec![];
+
+                    file.read_to_end(&mut file_content)?;
+                    file.seek(SeekFrom::Start(0))?;
+
+                    if libavif::is_avif(&file_content) {
+                        use rimage::codecs::avif::AvifDecoder;
+
+                        let decoder = AvifDecoder::try_new(file)?;
+
+                        return Ok((Image::from_decoder(decoder)?, vec![]));
+                    };
+                    file.seek(SeekFrom::Start(0))?;
+                }
+
+                #[cfg(feature = "webp")]
                 {
-                    use rimage::codecs::webp::WebPDecoder;
+                    if f.as_ref()
+                        .extension()
+                        .is_some_and(|f| f.eq_ignore_ascii_case("webp"))
+                    {
+                        use rimage::codecs::webp::WebPDecoder;
+                        use zune_image::traits::DecoderTrait;
 
-                    let decoder = WebPDecoder::try_new(file)?;
+                        let mut decoder = WebPDecoder::try_new(file)?;
+                        let img = decoder.decode()?;
 
-                    return Image::from_decoder(decoder);
+                        return Ok((img, decoder.delays().to_vec()));
+                    }
+
+                    file.seek(SeekFrom::Start(0))?;
                 }
 
-                file.seek(SeekFrom::Start(0))?;
-            }
+                #[cfg(feature = "tiff")]
+                {
+                    if f.as_ref().extension().is_some_and(|f| {
+                        f.eq_ignore_ascii_case("tiff") | f.eq_ignore_ascii_case("tif")
+                    }) {
+                        use rimage::codecs::tiff::TiffDecoder;
 
-            #[cfg(feature = "tiff")]
-            {
-                if f.as_ref()
-                    .extension()
-                    .is_some_and(|f| f.eq_ignore_ascii_case("tiff") | f.eq_ignore_ascii_case("tif"))
+                        let decoder = TiffDecoder::try_new(file)?;
+
+                        return Ok((Image::from_decoder(decoder)?, vec![]));
+                    }
+
+                    file.seek(SeekFrom::Start(0))?;
+                }
+
+                #[cfg(feature = "gif")]
                 {
-                    use rimage::codecs::tiff::TiffDecoder;
+                    if f.as_ref()
+                        .extension()
+                        .is_some_and(|f| f.eq_ignore_ascii_case("gif"))
+                    {
+                        use rimage::codecs::gif::GifDecoder;
+                        use zune_image::traits::DecoderTrait;
 
-                    let decoder = TiffDecoder::try_new(file)?;
+                        let mut decoder = GifDecoder::try_new(file)?;
+                        let img = decoder.decode()?;
 
-                    return Image::from_decoder(decoder);
+                        return Ok((img, decoder.delays().to_vec()));
+                    }
+
+                    file.seek(SeekFrom::Start(0))?;
                 }
 
-                file.seek(SeekFrom::Start(0))?;
+                Err(ImageErrors::ImageDecoderNotImplemented(
+                    ImageFormat::Unknown,
+                ))
+            } else {
+                Err(e)
             }
+        })
+}
 
-            Err(ImageErrors::ImageDecoderNotImplemented(
-                ImageFormat::Unknown,
-            ))
-        } else {
-            Err(e)
-        }
-    })
+/// Operation described for `--print-pipeline`
+type Step = (String, Box<dyn OperationsTrait>);
+
+/// Builds operations applied to the image, keyed by their argument index
+///
+/// Operations run in the order their arguments appear on the command line,
+/// `--premultiply` wraps the operation that follows it. See [`steps`] for details.
+pub fn operations(matches: &ArgMatches, img: &Image) -> Vec<(usize, Box<dyn OperationsTrait>)> {
+    steps(matches, img)
+        .into_iter()
+        .map(|(idx, (_, operation))| (idx, operation))
+        .collect()
+}
+
+/// Lists descriptions of operations applied to the image, keyed by their argument index
+pub fn describe_operations(matches: &ArgMatches, img: &Image) -> Vec<(usize, String)> {
+    steps(matches, img)
+        .into_iter()
+        .map(|(idx, (description, _))| (idx, description))
+        .collect()
+}
+
+/// Creates resize operation with `--filter` and `--max-output-pixels` applied
+#[cfg(feature = "resize")]
+pub fn resize_operation(
+    matches: &ArgMatches,
+    width: usize,
+    height: usize,
+) -> rimage::operations::resize::Resize {
+    use crate::cli::preprocessors::ResizeFilter;
+    use fast_image_resize::ResizeAlg;
+    use rimage::operations::resize::Resize;
+
+    let filter = matches
+        .get_one::<ResizeFilter>("filter")
+        .copied()
+        .expect("`filter` has default value");
+    let max_pixels = matches
+        .get_one::<usize>("max-output-pixels")
+        .copied()
+        .expect("`max-output-pixels` has default value");
+
+    let resize = match Option::<ResizeAlg>::from(filter) {
+        Some(algorithm) => Resize::new(width, height, algorithm),
+        None => Resize::auto(width, height),
+    };
+
+    #[cfg(feature = "metadata")]
+    let resize = resize.preserve_metadata(true);
+
+    resize.with_max_pixels(max_pixels)
 }
 
+/// Collects operations in command line order
+///
+/// Every operation is keyed by the index of its argument and the list is stably
+/// sorted by that index, so steps that share an index keep the order they were
+/// pushed in: resize, preset size, quantization and max colors.
+/// Premultiply steps are added afterwards by [`wrap_premultiplied`].
 #[allow(unused_variables)]
 #[allow(unused_mut)]
-pub fn operations(matches: &ArgMatches, img: &Image) -> BTreeMap<usize, Box<dyn OperationsTrait>> {
-    let mut map: BTreeMap<usize, Box<dyn OperationsTrait>> = BTreeMap::new();
+fn steps(matches: &ArgMatches, img: &Image) -> Vec<(usize, Step)> {
+    let mut steps: Vec<(usize, Step)> = Vec::new();
 
     #[cfg(feature = "resize")]
     {
-        use crate::cli::preprocessors::{ResizeFilter, ResizeValue};
-        use fast_image_resize::ResizeAlg;
-        use rimage::operations::resize::Resize;
+        use crate::cli::preprocessors::{ResizeFilter, ResizePreset, ResizeValue};
 
-        if let Some(values) = matches.get_many::<ResizeValue>("resize") {
-            let filter = matches.get_one::<ResizeFilter>("filter");
+        let filter = matches
+            .get_one::<ResizeFilter>("filter")
+            .copied()
+            .expect("`filter` has default value");
 
+        if let Some(values) = matches.get_many::<ResizeValue>("resize") {
             let (w, h) = img.dimensions();
 
             values
@@ -110,17 +239,31 @@ pub fn operations(matches: &ArgMatches, img: &Image) -> BTreeMap<usize, Box<dyn
                     let (w, h) = value.map_dimensions(w, h);
                     log::trace!("setup resize {value} on index {idx}");
 
-                    map.insert(
+                    steps.push((
+                        idx,
+                        (
+                            format!("resize {value} to {w}x{h} using {filter} filter"),
+                            Box::new(resize_operation(matches, w, h)),
+                        ),
+                    ));
+                })
+        }
+
+        if let Some(values) = matches.get_many::<ResizePreset>("preset-size") {
+            values
+                .into_iter()
+                .zip(matches.indices_of("preset-size").unwrap())
+                .for_each(|(preset, idx)| {
+                    let (w, h) = preset.dimensions();
+                    log::trace!("setup resize preset {preset} on index {idx}");
+
+                    steps.push((
                         idx,
-                        Box::new(Resize::new(
-                            w,
-                            h,
-                            filter
-                                .copied()
-                                .map(Into::<ResizeAlg>::into)
-                                .unwrap_or_default(),
-                        )),
-                    );
+                        (
+                            format!("resize to {preset} preset {w}x{h} using {filter} filter"),
+                            Box::new(resize_operation(matches, w, h).with_fit(preset.fit())),
+                        ),
+                    ));
                 })
         }
     }
@@ -129,59 +272,117 @@ pub fn operations(matches: &ArgMatches, img: &Image) -> BTreeMap<usize, Box<dyn
     {
         use rimage::operations::quantize::Quantize;
 
-        if let Some(values) = matches.get_many::<u8>("quantization") {
-            let dithering = matches.get_one::<u8>("dithering");
+        let dithering = matches.get_one::<u8>("dithering");
+        let deterministic = matches.get_flag("deterministic");
+
+        let quantize = |quality: u8| {
+            Quantize::new(quality, dithering.map(|q| *q as f32 / 100.)).deterministic(deterministic)
+        };
 
+        if let Some(values) = matches.get_many::<u8>("quantization") {
             values
                 .into_iter()
                 .zip(matches.indices_of("quantization").unwrap())
                 .for_each(|(value, idx)| {
                     log::trace!("setup quantization {value} on index {idx}");
 
-                    map.insert(
+                    let description = match dithering {
+                        Some(dithering) => {
+                            format!("quantize to {value}% quality with {dithering}% dithering")
+                        }
+                        None => format!("quantize to {value}% quality"),
+                    };
+
+                    steps.push((idx, (description, Box::new(quantize(*value)))));
+                })
+        }
+
+        if let Some(values) = matches.get_many::<u32>("max-colors") {
+            values
+                .into_iter()
+                .zip(matches.indices_of("max-colors").unwrap())
+                .for_each(|(value, idx)| {
+                    log::trace!("setup max colors {value} on index {idx}");
+
+                    steps.push((
                         idx,
-                        Box::new(Quantize::new(*value, dithering.map(|q| *q as f32 / 100.))),
-                    );
+                        (
+                            format!("quantize to {value} colors"),
+                            Box::new(quantize(100).with_max_colors(*value)),
+                        ),
+                    ));
                 })
         }
     }
 
-    if let Some(values) = matches.get_many::<bool>("premultiply") {
-        values
-            .into_iter()
-            .zip(matches.indices_of("premultiply").unwrap())
-            .for_each(|(value, idx)| {
-                if let Some(op) = map.get(&(idx + 2)) {
-                    log::trace!("setup alpha premultiply for {}", op.name());
+    let premultiply = matches
+        .get_many::<bool>("premultiply")
+        .into_iter()
+        .flatten()
+        .zip(matches.indices_of("premultiply").into_iter().flatten())
+        .filter_map(|(value, idx)| value.then_some(idx))
+        .collect::<Vec<_>>();
 
-                    map.insert(
-                        idx,
-                        Box::new(PremultiplyAlpha::new(AlphaState::PreMultiplied)),
-                    );
-
-                    assert!(
-                        !map.contains_key(&(idx + 3)),
-                        "There is a operation at {} aborting",
-                        idx + 3
-                    );
-
-                    map.insert(
-                        idx + 3,
-                        Box::new(PremultiplyAlpha::new(AlphaState::NonPreMultiplied)),
-                    );
-                } else {
-                    log::warn!("No operation found for premultiply at index {idx}")
-                }
-            })
+    steps.sort_by_key(|(idx, _)| *idx);
+
+    wrap_premultiplied(steps, &premultiply)
+}
+
+/// Wraps operations in alpha premultiply and unpremultiply steps
+///
+/// `operations` must be sorted by index. Each index in `premultiply` targets
+/// the first operation that comes after it, regardless of how many arguments
+/// are in between.
+fn wrap_premultiplied(operations: Vec<(usize, Step)>, premultiply: &[usize]) -> Vec<(usize, Step)> {
+    let mut steps = Vec::with_capacity(operations.len() + premultiply.len() * 2);
+    let mut pending = premultiply.iter().copied().peekable();
+
+    for (idx, (description, operation)) in operations {
+        let mut wrap = None;
+
+        while let Some(flag) = pending.next_if(|flag| *flag < idx) {
+            if let Some(previous) = wrap.replace(flag) {
+                log::warn!("No operation found for premultiply at index {previous}")
+            }
+        }
+
+        match wrap {
+            Some(flag) => {
+                log::trace!("setup alpha premultiply for {}", operation.name());
+
+                steps.push((
+                    flag,
+                    (
+                        "premultiply alpha".to_string(),
+                        Box::new(PremultiplyAlpha::new(AlphaState::PreMultiplied)) as _,
+                    ),
+                ));
+                steps.push((idx, (description, operation)));
+                steps.push((
+                    idx,
+                    (
+                        "unpremultiply alpha".to_string(),
+                        Box::new(PremultiplyAlpha::new(AlphaState::NonPreMultiplied)) as _,
+                    ),
+                ));
+            }
+            None => steps.push((idx, (description, operation))),
+        }
     }
 
-    map
+    for idx in pending {
+        log::warn!("No operation found for premultiply at index {idx}")
+    }
+
+    steps
 }
 
 pub enum AvailableEncoders {
     FarbFeld(Box<FarbFeldEncoder>),
     Jpeg(Box<JpegEncoder>),
     JpegXl(Box<JxlEncoder>),
+    #[cfg(feature = "gif")]
+    Gif(Box<GifEncoder>),
     #[cfg(feature = "mozjpeg")]
     MozJpeg(Box<MozJpegEncoder>),
     #[cfg(feature = "oxipng")]
@@ -201,29 +402,98 @@ impl AvailableEncoders {
             AvailableEncoders::FarbFeld(_) => "ff",
             AvailableEncoders::Jpeg(_) => "jpg",
             AvailableEncoders::JpegXl(_) => "jxl",
+            #[cfg(feature = "gif")]
+            AvailableEncoders::Gif(enc) => enc.extension(),
             #[cfg(feature = "mozjpeg")]
-            AvailableEncoders::MozJpeg(_) => "jpg",
+            AvailableEncoders::MozJpeg(enc) => enc.extension(),
             #[cfg(feature = "oxipng")]
-            AvailableEncoders::OxiPng(_) => "png",
+            AvailableEncoders::OxiPng(enc) => enc.extension(),
             #[cfg(feature = "avif")]
-            AvailableEncoders::Avif(_) => "avif",
+            AvailableEncoders::Avif(enc) => enc.extension(),
             #[cfg(feature = "webp")]
-            AvailableEncoders::Webp(_) => "webp",
+            AvailableEncoders::Webp(enc) => enc.extension(),
             AvailableEncoders::Png(_) => "png",
             AvailableEncoders::Ppm(_) => "ppm",
             AvailableEncoders::Qoi(_) => "qoi",
         }
     }
 
+    /// Whether the encoder writes all frames of animated images
+    pub fn supports_animation(&self) -> bool {
+        match self {
+            AvailableEncoders::FarbFeld(enc) => enc.supports_animated_images(),
+            AvailableEncoders::Jpeg(enc) => enc.supports_animated_images(),
+            AvailableEncoders::JpegXl(enc) => enc.supports_animated_images(),
+            #[cfg(feature = "gif")]
+            AvailableEncoders::Gif(enc) => enc.supports_animated_images(),
+            #[cfg(feature = "mozjpeg")]
+            AvailableEncoders::MozJpeg(enc) => enc.supports_animated_images(),
+            #[cfg(feature = "oxipng")]
+            AvailableEncoders::OxiPng(enc) => enc.supports_animated_images(),
+            #[cfg(feature = "avif")]
+            AvailableEncoders::Avif(enc) => enc.supports_animated_images(),
+            #[cfg(feature = "webp")]
+            AvailableEncoders::Webp(enc) => enc.supports_animated_images(),
+            AvailableEncoders::Png(enc) => enc.supports_animated_images(),
+            AvailableEncoders::Ppm(enc) => enc.supports_animated_images(),
+            AvailableEncoders::Qoi(enc) => enc.supports_animated_images(),
+        }
+    }
+
+    /// Sets delays of the source animation frames in milliseconds
+    ///
+    /// Only GIF writes them, other encoders are returned as is.
+    #[must_use]
+    pub fn with_frame_delays(self, delays: Vec<u16>) -> Self {
+        match self {
+            #[cfg(feature = "gif")]
+            AvailableEncoders::Gif(enc) => {
+                AvailableEncoders::Gif(Box::new(enc.frame_delays(delays)))
+            }
+            enc => enc,
+        }
+    }
+
+    /// Whether the encoder writes into the sink as it encodes
+    ///
+    /// Other encoders keep the whole output in their own buffer and copy it out at once,
+    /// so reserving memory for their output only wastes it.
+    pub fn streams(&self) -> bool {
+        match self {
+            #[cfg(feature = "gif")]
+            AvailableEncoders::Gif(_) => false,
+            #[cfg(feature = "oxipng")]
+            AvailableEncoders::OxiPng(_) => false,
+            #[cfg(feature = "avif")]
+            AvailableEncoders::Avif(_) => false,
+            #[cfg(feature = "webp")]
+            AvailableEncoders::Webp(_) => false,
+            _ => true,
+        }
+    }
+
+    /// Whether the encoder stores transparency
+    pub fn supports_alpha(&self) -> bool {
+        match self {
+            AvailableEncoders::Jpeg(_) | AvailableEncoders::Ppm(_) => false,
+            #[cfg(feature = "mozjpeg")]
+            AvailableEncoders::MozJpeg(_) => false,
+            _ => true,
+        }
+    }
+
     pub fn encode<T: ZByteWriterTrait>(
         &mut self,
         img: &Image,
         sink: T,
     ) -> Result<usize, ImageErrors> {
+        validate_channels(img)?;
+
         match self {
             AvailableEncoders::FarbFeld(enc) => enc.encode(img, sink),
             AvailableEncoders::Jpeg(enc) => enc.encode(img, sink),
             AvailableEncoders::JpegXl(enc) => enc.encode(img, sink),
+            AvailableEncoders::Gif(enc) => enc.encode(img, sink),
             AvailableEncoders::MozJpeg(enc) => enc.encode(img, sink),
             AvailableEncoders::OxiPng(enc) => enc.encode(img, sink),
             AvailableEncoders::Avif(enc) => enc.encode(img, sink),
@@ -235,98 +505,312 @@ impl AvailableEncoders {
     }
 }
 
-pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, ImageErrors> {
+/// Most bytes reserved for encoded output, larger outputs grow the buffer as usual
+pub const MAX_RESERVED_CAPACITY: usize = 256 * 1024 * 1024;
+
+/// Encodes the image into memory, reserving `capacity` bytes upfront
+///
+/// Encoders grow the buffer as they write, reserving the expected size
+/// avoids reallocating and copying it over and over for large images.
+/// Nothing is reserved for encoders buffering output on their own, see
+/// [`AvailableEncoders::streams`], and at most [`MAX_RESERVED_CAPACITY`] bytes otherwise.
+pub fn encode_to_vec(
+    encoder: &mut AvailableEncoders,
+    img: &Image,
+    capacity: usize,
+) -> Result<Vec<u8>, ImageErrors> {
+    let capacity = if encoder.streams() {
+        capacity.min(MAX_RESERVED_CAPACITY)
+    } else {
+        0
+    };
+    let mut buf = Vec::with_capacity(capacity);
+
+    encoder.encode(img, &mut buf)?;
+
+    Ok(buf)
+}
+
+/// Checks that every frame has as many channels as its colorspace requires
+///
+/// Encoders flatten frames assuming this layout, and panic on mismatched channels.
+fn validate_channels(img: &Image) -> Result<(), ImageErrors> {
+    let expected = img.colorspace().num_components();
+
+    for (idx, frame) in img.frames_ref().iter().enumerate() {
+        let found = frame.channels_vec_ref().len();
+
+        if found != expected {
+            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                format!(
+                    "frame {idx} has {found} channels, but {:?} requires {expected}",
+                    img.colorspace()
+                ),
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that encoded data decodes to exactly the pixels of `original`
+///
+/// Meant for lossless codecs, any changed sample is reported as an error.
+/// Decoded image is converted to depth and colorspace of `original` first,
+/// as encoders may store pixels in a reduced format, like opaque RGBA as RGB.
+pub fn verify_lossless(
+    original: &Image,
+    encoded: &[u8],
+    encoder: &AvailableEncoders,
+) -> Result<(), ImageErrors> {
+    let mut decoded = decode_encoded(encoded, encoder)?;
+
+    if decoded.dimensions() != original.dimensions()
+        || decoded.frames_len() != original.frames_len()
+    {
+        return Err(ImageErrors::GenericString(format!(
+            "verification failed, output has {} frame(s) of {:?} instead of {} frame(s) of {:?}",
+            decoded.frames_len(),
+            decoded.dimensions(),
+            original.frames_len(),
+            original.dimensions()
+        )));
+    }
+
+    decoded.convert_depth(original.depth())?;
+    decoded.convert_color(original.colorspace())?;
+
+    let mismatch = original
+        .frames_ref()
+        .iter()
+        .zip(decoded.frames_ref())
+        .position(|(a, b)| a.channels_vec_ref() != b.channels_vec_ref());
+
+    match mismatch {
+        Some(idx) => Err(ImageErrors::GenericString(format!(
+            "verification failed, pixels of frame {idx} changed after encoding"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Checks if encoders should embed ICC profile of the image
+///
+/// Profile is kept with `--keep-icc` and set with `--icc`, otherwise images are sRGB.
+fn embeds_icc(matches: &ArgMatches) -> bool {
+    matches.get_flag("keep-icc") || matches.contains_id("icc")
+}
+
+/// Creates operation applying EXIF orientation, unless disabled by the flag
+pub fn auto_orient(matches: &ArgMatches) -> Option<Box<dyn OperationsTrait>> {
+    (!matches.get_flag("no-auto-orient")).then(|| Box::new(AutoOrient) as Box<dyn OperationsTrait>)
+}
+
+/// Divides colors of premultiplied images by alpha before encoding
+///
+/// Encoders expect straight alpha, images marked premultiplied, like with
+/// `--alpha-premultiplied`, are converted here exactly once.
+pub fn straight_alpha(img: &mut Image) -> Result<(), ImageErrors> {
+    if img.colorspace().has_alpha() && img.metadata().alpha() == AlphaState::PreMultiplied {
+        PremultiplyAlpha::new(AlphaState::NonPreMultiplied).execute(img)?;
+    }
+
+    Ok(())
+}
+
+/// Converts image of a grayscale source back to Luma before JPEG encoding
+///
+/// Operations run on RGBA images, which JPEG encoders would write with
+/// redundant chroma, grayscale JPEG has a single component instead.
+pub fn restore_grayscale(
+    img: &mut Image,
+    source: ColorSpace,
+    encoder: &AvailableEncoders,
+) -> Result<(), ImageErrors> {
+    let grayscale = matches!(source, ColorSpace::Luma | ColorSpace::LumaA);
+    let jpeg = match encoder {
+        AvailableEncoders::Jpeg(_) => true,
+        #[cfg(feature = "mozjpeg")]
+        AvailableEncoders::MozJpeg(_) => true,
+        _ => false,
+    };
+
+    if grayscale && jpeg {
+        Grayscale::new().execute(img)?;
+    }
+
+    Ok(())
+}
+
+/// Applies `--on-alpha` policy to transparent images the encoder can't store alpha of
+///
+/// With `error` policy such images are rejected, otherwise they are flattened
+/// onto a white background. Returns `true` if the caller should warn about it.
+pub fn check_alpha(
+    img: &mut Image,
+    encoder: &AvailableEncoders,
+    matches: &ArgMatches,
+) -> Result<bool, ImageErrors> {
+    if encoder.supports_alpha() || is_opaque(img)? {
+        return Ok(false);
+    }
+
+    let warn = match matches.get_one::<String>("on-alpha").map(String::as_str) {
+        Some("error") => {
+            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                format!(
+                    "image is transparent, but {} output can't store alpha, use `--on-alpha flatten` to flatten it onto white",
+                    encoder.to_extension()
+                ),
+            )))
+        }
+        Some("warn") => true,
+        _ => false,
+    };
+
+    Flatten::new([1., 1., 1.]).execute(img)?;
+
+    Ok(warn)
+}
+
+/// Keeps only the first frame of animated image if encoder can't write animations
+///
+/// WebP and GIF write animations, other codecs, including AVIF, write still images
+/// and get only the first frame. Returns a warning about discarded frames.
+pub fn discard_frames(
+    img: &mut Image,
+    encoder: &AvailableEncoders,
+    codec: &str,
+) -> Result<Option<String>, ImageErrors> {
+    let frames = img.frames_len();
+
+    if frames <= 1 || encoder.supports_animation() {
+        return Ok(None);
+    }
+
+    SelectFrame::new(0).execute(img)?;
+
+    Ok(Some(format!(
+        "{codec} doesn't support animation, discarding {} frame(s)",
+        frames - 1
+    )))
+}
+
+/// Creates operations on EXIF metadata requested by the flags
+#[cfg(feature = "metadata")]
+pub fn metadata_operations(matches: &ArgMatches) -> Vec<Box<dyn OperationsTrait>> {
+    use rimage::operations::exif::{SetOrientation, StripGps, StripMakerNote};
+
+    let mut operations: Vec<Box<dyn OperationsTrait>> = vec![];
+
+    if matches.get_flag("strip-gps") {
+        operations.push(Box::new(StripGps));
+    }
+
+    if !matches.get_flag("keep-makernote") {
+        operations.push(Box::new(StripMakerNote));
+    }
+
+    if let Some(orientation) = matches.get_one::<u16>("set-orientation") {
+        operations.push(Box::new(SetOrientation::new(*orientation)));
+    }
+
+    operations
+}
+
+/// Qualities of `--quality-preset` values for each codec
+///
+/// Codecs scale quality differently, AVIF at 65 looks about as good as JPEG at 80.
+const QUALITY_PRESETS: &[(&str, [(&str, u8); 4])] = &[
+    (
+        "avif",
+        [("low", 45), ("medium", 65), ("high", 80), ("lossless", 100)],
+    ),
+    (
+        "jpeg",
+        [("low", 60), ("medium", 80), ("high", 92), ("lossless", 100)],
+    ),
+    (
+        "mozjpeg",
+        [("low", 55), ("medium", 75), ("high", 88), ("lossless", 100)],
+    ),
+    (
+        "webp",
+        [("low", 50), ("medium", 75), ("high", 90), ("lossless", 100)],
+    ),
+];
+
+/// Maps a `--quality-preset` value to the quality of the codec
+pub fn preset_quality(codec: &str, preset: &str) -> Option<u8> {
+    QUALITY_PRESETS
+        .iter()
+        .find(|(name, _)| *name == codec)
+        .and_then(|(_, presets)| presets.iter().find(|(name, _)| *name == preset))
+        .map(|(_, quality)| *quality)
+}
+
+/// Quality of the codec, either set directly or with `--quality-preset`
+pub fn quality(codec: &str, matches: &ArgMatches) -> Option<u8> {
+    match matches
+        .try_get_one::<String>("quality-preset")
+        .ok()
+        .flatten()
+    {
+        Some(preset) => preset_quality(codec, preset),
+        None => matches.try_get_one::<u8>("quality").ok().flatten().copied(),
+    }
+}
+
+/// Creates encoder with default options of the codec
+pub fn default_encoder(name: &str) -> Result<AvailableEncoders, ImageErrors> {
+    default_encoder_with_quality(name, None)
+}
+
+/// Creates encoder with default options of the codec and `quality`, if it has one
+///
+/// Defaults of the encoders match defaults of their subcommands.
+fn default_encoder_with_quality(
+    name: &str,
+    quality: Option<u8>,
+) -> Result<AvailableEncoders, ImageErrors> {
     match name {
         "farbfeld" => Ok(AvailableEncoders::FarbFeld(
             Box::new(FarbFeldEncoder::new()),
         )),
         "jpeg" => {
-            let options = EncoderOptions::default();
+            let mut options = EncoderOptions::default();
 
-            if let Some(quality) = matches.get_one::<u8>("quality") {
-                options.set_quality(*quality);
+            if let Some(quality) = quality {
+                options = options.set_quality(quality);
             }
 
-            options.set_jpeg_encode_progressive(matches.get_flag("progressive"));
-
             Ok(AvailableEncoders::Jpeg(Box::new(
                 JpegEncoder::new_with_options(options),
             )))
         }
         "jpeg_xl" => Ok(AvailableEncoders::JpegXl(Box::new(JxlEncoder::new()))),
+        #[cfg(feature = "gif")]
+        "gif" => {
+            use rimage::codecs::gif::GifOptions;
+
+            let defaults = GifOptions::default();
+            let options = GifOptions {
+                quality: quality.unwrap_or(defaults.quality),
+                ..defaults
+            };
+
+            Ok(AvailableEncoders::Gif(Box::new(
+                GifEncoder::new_with_options(options),
+            )))
+        }
         #[cfg(feature = "mozjpeg")]
         "mozjpeg" => {
-            use mozjpeg::qtable;
             use rimage::codecs::mozjpeg::MozJpegOptions;
 
-            let quality = *matches.get_one::<u8>("quality").unwrap() as f32;
-            let chroma_quality = matches
-                .get_one::<u8>("chroma_quality")
-                .map(|q| *q as f32)
-                .unwrap_or(quality);
-
+            let defaults = MozJpegOptions::default();
             let options = MozJpegOptions {
-                quality,
-                progressive: !matches.get_flag("baseline"),
-                optimize_coding: !matches.get_flag("no_optimize_coding"),
-                smoothing: matches
-                    .get_one::<u8>("smoothing")
-                    .copied()
-                    .unwrap_or_default(),
-                color_space: match matches.get_one::<String>("colorspace").unwrap().as_str() {
-                    "ycbcr" => mozjpeg::ColorSpace::JCS_YCbCr,
-                    "rgb" => mozjpeg::ColorSpace::JCS_EXT_RGB,
-                    "grayscale" => mozjpeg::ColorSpace::JCS_GRAYSCALE,
-                    _ => unreachable!(),
-                },
-                trellis_multipass: matches.get_flag("multipass"),
-                chroma_subsample: matches.get_one::<u8>("subsample").copied(),
-
-                luma_qtable: matches
-                    .get_one::<String>("qtable")
-                    .map(|c| match c.as_str() {
-                        "AhumadaWatsonPeterson" => {
-                            qtable::AhumadaWatsonPeterson.scaled(quality, quality)
-                        }
-                        "AnnexK" => qtable::AnnexK_Luma.scaled(quality, quality),
-                        "Flat" => qtable::Flat.scaled(quality, quality),
-                        "KleinSilversteinCarney" => {
-                            qtable::KleinSilversteinCarney.scaled(quality, quality)
-                        }
-                        "MSSSIM" => qtable::MSSSIM_Luma.scaled(quality, quality),
-                        "NRobidoux" => qtable::NRobidoux.scaled(quality, quality),
-                        "PSNRHVS" => qtable::PSNRHVS_Luma.scaled(quality, quality),
-                        "PetersonAhumadaWatson" => {
-                            qtable::PetersonAhumadaWatson.scaled(quality, quality)
-                        }
-                        "WatsonTaylorBorthwick" => {
-                            qtable::WatsonTaylorBorthwick.scaled(quality, quality)
-                        }
-                        _ => unreachable!(),
-                    }),
-
-                chroma_qtable: matches
-                    .get_one::<String>("qtable")
-                    .map(|c| match c.as_str() {
-                        "AhumadaWatsonPeterson" => {
-                            qtable::AhumadaWatsonPeterson.scaled(chroma_quality, chroma_quality)
-                        }
-                        "AnnexK" => qtable::AnnexK_Chroma.scaled(chroma_quality, chroma_quality),
-                        "Flat" => qtable::Flat.scaled(chroma_quality, chroma_quality),
-                        "KleinSilversteinCarney" => {
-                            qtable::KleinSilversteinCarney.scaled(chroma_quality, chroma_quality)
-                        }
-                        "MSSSIM" => qtable::MSSSIM_Chroma.scaled(chroma_quality, chroma_quality),
-                        "NRobidoux" => qtable::NRobidoux.scaled(chroma_quality, chroma_quality),
-                        "PSNRHVS" => qtable::PSNRHVS_Chroma.scaled(chroma_quality, chroma_quality),
-                        "PetersonAhumadaWatson" => {
-                            qtable::PetersonAhumadaWatson.scaled(chroma_quality, chroma_quality)
-                        }
-                        "WatsonTaylorBorthwick" => {
-                            qtable::WatsonTaylorBorthwick.scaled(chroma_quality, chroma_quality)
-                        }
-                        _ => unreachable!(),
-                    }),
+                quality: quality.map_or(defaults.quality, f32::from),
+                ..defaults
             };
 
             Ok(AvailableEncoders::MozJpeg(Box::new(
@@ -334,41 +818,15 @@ pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, Im
             )))
         }
         #[cfg(feature = "oxipng")]
-        "oxipng" => {
-            use rimage::codecs::oxipng::OxiPngOptions;
-
-            let mut options =
-                OxiPngOptions::from_preset(*matches.get_one::<u8>("effort").unwrap_or(&2));
-
-            options.interlace = if matches.get_flag("interlace") {
-                Some(oxipng::Interlacing::Adam7)
-            } else {
-                None
-            };
-
-            Ok(AvailableEncoders::OxiPng(Box::new(
-                OxiPngEncoder::new_with_options(options),
-            )))
-        }
+        "oxipng" => Ok(AvailableEncoders::OxiPng(Box::new(OxiPngEncoder::new()))),
         #[cfg(feature = "avif")]
         "avif" => {
             use rimage::codecs::avif::AvifOptions;
 
+            let defaults = AvifOptions::default();
             let options = AvifOptions {
-                quality: *matches.get_one::<u8>("quality").unwrap() as f32,
-                alpha_quality: matches.get_one::<u8>("alpha_quality").map(|q| *q as f32),
-                speed: *matches.get_one::<u8>("speed").unwrap(),
-                color_space: match matches.get_one::<String>("colorspace").unwrap().as_str() {
-                    "ycbcr" => ravif::ColorSpace::YCbCr,
-                    "rgb" => ravif::ColorSpace::RGB,
-                    _ => unreachable!(),
-                },
-                alpha_color_mode: match matches.get_one::<String>("alpha_mode").unwrap().as_str() {
-                    "UnassociatedDirty" => ravif::AlphaColorMode::UnassociatedDirty,
-                    "UnassociatedClean" => ravif::AlphaColorMode::UnassociatedClean,
-                    "Premultiplied" => ravif::AlphaColorMode::Premultiplied,
-                    _ => unreachable!(),
-                },
+                quality: quality.map_or(defaults.quality, f32::from),
+                ..defaults
             };
 
             Ok(AvailableEncoders::Avif(Box::new(
@@ -381,10 +839,9 @@ pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, Im
 
             let mut options = WebPOptions::new().unwrap();
 
-            options.quality = *matches.get_one::<u8>("quality").unwrap() as f32;
-            options.lossless = matches.get_flag("lossless") as i32;
-            options.near_lossless = 100 - *matches.get_one::<u8>("slight_loss").unwrap() as i32;
-            options.exact = matches.get_flag("exact") as i32;
+            if let Some(quality) = quality {
+                options.quality = f32::from(quality);
+            }
 
             Ok(AvailableEncoders::Webp(Box::new(
                 WebPEncoder::new_with_options(options),
@@ -399,3 +856,1200 @@ pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, Im
         ))),
     }
 }
+
+/// Checks if the codec has a `--quality` option
+pub fn has_quality(codec: &str) -> bool {
+    cli()
+        .find_subcommand(codec)
+        .is_some_and(|c| c.get_arguments().any(|a| a.get_long() == Some("quality")))
+}
+
+/// Creates the encoder with `quality`, overriding the quality set by `matches`
+///
+/// Without `matches`, other options of the codec have their default values.
+pub fn encoder_with_quality(
+    codec: &str,
+    matches: Option<&ArgMatches>,
+    quality: u8,
+) -> anyhow::Result<AvailableEncoders> {
+    Ok(match matches {
+        Some(matches) => codec_encoder(codec, matches, Some(quality))?,
+        None => default_encoder_with_quality(codec, Some(quality))?,
+    })
+}
+
+/// Creates the encoder with quality picked from `range` by complexity of `img`
+///
+/// Detailed images get qualities close to the maximum and flat ones to the minimum,
+/// see [`complexity`]. Returns `None` if the codec has no quality option.
+pub fn encoder_for_complexity(
+    codec: &str,
+    matches: Option<&ArgMatches>,
+    img: &Image,
+    range: QualityRange,
+) -> anyhow::Result<Option<AvailableEncoders>> {
+    if !has_quality(codec) {
+        return Ok(None);
+    }
+
+    let complexity = complexity(img);
+    let quality = range.quality(complexity);
+
+    log::info!("using quality {quality} for complexity {complexity:.3}");
+
+    encoder_with_quality(codec, matches, quality).map(Some)
+}
+
+/// Creates the encoder with the lowest quality keeping DSSIM of `img` at or below `target`
+///
+/// Quality is binary-searched by encoding and decoding the image in memory.
+/// Without `matches`, other options of the codec have their default values.
+/// Returns `None` if the codec has no quality option.
+pub fn encoder_for_target(
+    codec: &str,
+    matches: Option<&ArgMatches>,
+    img: &Image,
+    target: f64,
+) -> anyhow::Result<Option<AvailableEncoders>> {
+    if !has_quality(codec) {
+        return Ok(None);
+    }
+
+    let with_quality = |quality: u8| encoder_with_quality(codec, matches, quality);
+
+    let distance = |quality: u8| -> anyhow::Result<f64> {
+        let mut encoder = with_quality(quality)?;
+        let mut data = vec![];
+
+        encoder.encode(img, &mut data)?;
+        let distance = dssim(img, &decode_encoded(&data, &encoder)?)?;
+
+        log::trace!("quality {quality} has DSSIM {distance}");
+
+        Ok(distance)
+    };
+
+    let best = distance(100)?;
+    if best > target {
+        log::warn!("unable to reach DSSIM {target}, using the best quality with DSSIM {best}");
+
+        return with_quality(100).map(Some);
+    }
+
+    let (mut low, mut high) = (1, 100);
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+
+        if distance(mid)? <= target {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    log::info!("using quality {high} for DSSIM {target}");
+
+    with_quality(high).map(Some)
+}
+
+/// Decodes an image encoded by `encoder` from memory
+pub fn decode_encoded(data: &[u8], encoder: &AvailableEncoders) -> Result<Image, ImageErrors> {
+    match encoder {
+        #[cfg(feature = "avif")]
+        AvailableEncoders::Avif(_) => {
+            use rimage::codecs::avif::AvifDecoder;
+
+            Image::from_decoder(AvifDecoder::try_new(std::io::Cursor::new(data))?)
+        }
+        #[cfg(feature = "webp")]
+        AvailableEncoders::Webp(_) => {
+            use rimage::codecs::webp::WebPDecoder;
+
+            Image::from_decoder(WebPDecoder::try_new(std::io::Cursor::new(data))?)
+        }
+        #[cfg(feature = "gif")]
+        AvailableEncoders::Gif(_) => {
+            use rimage::codecs::gif::GifDecoder;
+
+            Image::from_decoder(GifDecoder::try_new(std::io::Cursor::new(data))?)
+        }
+        _ => Image::read(ZCursor::new(data), DecoderOptions::default()),
+    }
+}
+
+pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, ImageErrors> {
+    codec_encoder(name, matches, None)
+}
+
+/// Creates the encoder from `matches`, with `quality_override` replacing its quality
+fn codec_encoder(
+    name: &str,
+    matches: &ArgMatches,
+    quality_override: Option<u8>,
+) -> Result<AvailableEncoders, ImageErrors> {
+    let quality = |name: &str, matches: &ArgMatches| quality_override.or(quality(name, matches));
+
+    match name {
+        "farbfeld" => Ok(AvailableEncoders::FarbFeld(
+            Box::new(FarbFeldEncoder::new()),
+        )),
+        "jpeg" => {
+            let mut options = EncoderOptions::default()
+                .set_jpeg_encode_progressive(matches.get_flag("progressive"));
+
+            if let Some(quality) = quality(name, matches) {
+                options = options.set_quality(quality);
+            }
+
+            Ok(AvailableEncoders::Jpeg(Box::new(
+                JpegEncoder::new_with_options(options),
+            )))
+        }
+        "jpeg_xl" => Ok(AvailableEncoders::JpegXl(Box::new(JxlEncoder::new()))),
+        #[cfg(feature = "gif")]
+        "gif" => {
+            use rimage::codecs::gif::GifOptions;
+
+            let options = GifOptions {
+                quality: quality_override.unwrap_or(*matches.get_one::<u8>("quality").unwrap()),
+                dithering: (!matches.get_flag("no_dither")).then_some(1.0),
+                delays: matches
+                    .get_many::<u16>("delay")
+                    .map(|delays| delays.copied().collect())
+                    .unwrap_or_default(),
+                repeat: matches.get_one::<u16>("loops").copied(),
+            };
+
+            Ok(AvailableEncoders::Gif(Box::new(
+                GifEncoder::new_with_options(options),
+            )))
+        }
+        #[cfg(feature = "mozjpeg")]
+        "mozjpeg" => {
+            use mozjpeg::qtable;
+            use rimage::codecs::mozjpeg::{component_qtables, scaled_qtable, MozJpegOptions};
+
+            let quality = quality(name, matches).unwrap() as f32;
+            let luma_quality = matches
+                .get_one::<u8>("luma-quality")
+                .map(|q| *q as f32)
+                .unwrap_or(quality);
+            let chroma_quality = matches
+                .get_one::<u8>("chroma-quality")
+                .map(|q| *q as f32)
+                .unwrap_or(quality);
+
+            let qtable_scale = matches.get_one::<f32>("qtable_scale").copied();
+            let scale = |table: &qtable::QTable, quality: f32| match qtable_scale {
+                Some(scale) => scaled_qtable(table, scale),
+                None => table.scaled(quality, quality),
+            };
+
+            let qtables = match matches.get_one::<String>("qtable") {
+                Some(c) => Some(match c.as_str() {
+                    "AhumadaWatsonPeterson" => (
+                        &qtable::AhumadaWatsonPeterson,
+                        &qtable::AhumadaWatsonPeterson,
+                    ),
+                    "AnnexK" => (&qtable::AnnexK_Luma, &qtable::AnnexK_Chroma),
+                    "Flat" => (&qtable::Flat, &qtable::Flat),
+                    "KleinSilversteinCarney" => (
+                        &qtable::KleinSilversteinCarney,
+                        &qtable::KleinSilversteinCarney,
+                    ),
+                    "MSSSIM" => (&qtable::MSSSIM_Luma, &qtable::MSSSIM_Chroma),
+                    "NRobidoux" => (&qtable::NRobidoux, &qtable::NRobidoux),
+                    "PSNRHVS" => (&qtable::PSNRHVS_Luma, &qtable::PSNRHVS_Chroma),
+                    "PetersonAhumadaWatson" => (
+                        &qtable::PetersonAhumadaWatson,
+                        &qtable::PetersonAhumadaWatson,
+                    ),
+                    "WatsonTaylorBorthwick" => (
+                        &qtable::WatsonTaylorBorthwick,
+                        &qtable::WatsonTaylorBorthwick,
+                    ),
+                    _ => unreachable!(),
+                }),
+                None if qtable_scale.is_some() => Some((&qtable::NRobidoux, &qtable::NRobidoux)),
+                None => None,
+            };
+
+            let (luma_qtable, chroma_qtable) = match qtables {
+                Some((luma, chroma)) => (
+                    Some(scale(luma, luma_quality)),
+                    Some(scale(chroma, chroma_quality)),
+                ),
+                None if luma_quality != quality || chroma_quality != quality => {
+                    let (luma, chroma) = component_qtables(luma_quality, chroma_quality);
+                    (Some(luma), Some(chroma))
+                }
+                None => (None, None),
+            };
+
+            let options = MozJpegOptions {
+                quality,
+                progressive: !matches.get_flag("baseline"),
+                optimize_coding: !matches.get_flag("no_optimize_coding")
+                    && !matches.get_flag("fast"),
+                fastest: matches.get_flag("fast"),
+                smoothing: matches
+                    .get_one::<u8>("smoothing")
+                    .copied()
+                    .unwrap_or_default(),
+                color_space: match matches.get_one::<String>("colorspace").unwrap().as_str() {
+                    "ycbcr" => mozjpeg::ColorSpace::JCS_YCbCr,
+                    "rgb" => mozjpeg::ColorSpace::JCS_EXT_RGB,
+                    "grayscale" => mozjpeg::ColorSpace::JCS_GRAYSCALE,
+                    _ => unreachable!(),
+                },
+                trellis_multipass: matches.get_flag("multipass"),
+                chroma_subsample: matches.get_one::<u8>("subsample").copied(),
+
+                luma_qtable,
+                chroma_qtable,
+                preserve_icc: embeds_icc(matches),
+
+                ..Default::default()
+            };
+
+            Ok(AvailableEncoders::MozJpeg(Box::new(
+                MozJpegEncoder::new_with_options(options),
+            )))
+        }
+        #[cfg(feature = "oxipng")]
+        "oxipng" => {
+            use rimage::codecs::oxipng::{fast_options, OxiPngOptions};
+
+            let mut options = match *matches.get_one::<u8>("effort").unwrap_or(&2) {
+                0 => fast_options(),
+                effort => OxiPngOptions::from_preset(effort),
+            };
+
+            options.interlace = if matches.get_flag("interlace") {
+                Some(oxipng::Interlacing::Adam7)
+            } else {
+                None
+            };
+
+            options.strip = match matches
+                .get_one::<String>("strip-chunks")
+                .map(String::as_str)
+            {
+                Some("safe") => oxipng::StripChunks::Safe,
+                Some("all") => oxipng::StripChunks::All,
+                _ => oxipng::StripChunks::None,
+            };
+
+            Ok(AvailableEncoders::OxiPng(Box::new(
+                OxiPngEncoder::new_with_options(options)
+                    .preserve_icc(embeds_icc(matches))
+                    .bilevel(matches.get_one::<u8>("bilevel").copied()),
+            )))
+        }
+        #[cfg(feature = "avif")]
+        "avif" => {
+            use rimage::codecs::avif::AvifOptions;
+
+            let options = AvifOptions {
+                quality: quality(name, matches).unwrap() as f32,
+                alpha_quality: matches.get_one::<u8>("alpha-quality").map(|q| *q as f32),
+                speed: *matches.get_one::<u8>("speed").unwrap(),
+                passes: *matches.get_one::<u8>("passes").unwrap(),
+                depth: matches
+                    .get_one::<String>("depth")
+                    .map(|depth| depth.parse().expect("`depth` is either 8 or 10")),
+                color_space: match matches.get_one::<String>("colorspace").unwrap().as_str() {
+                    "ycbcr" => ravif::ColorSpace::YCbCr,
+                    "rgb" => ravif::ColorSpace::RGB,
+                    _ => unreachable!(),
+                },
+                alpha_color_mode: match matches
+                    .get_one::<String>("alpha-mode")
+                    .expect("`alpha-mode` has default value")
+                    .as_str()
+                {
+                    "unassociated-dirty" | "UnassociatedDirty" => {
+                        ravif::AlphaColorMode::UnassociatedDirty
+                    }
+                    "unassociated-clean" | "UnassociatedClean" => {
+                        ravif::AlphaColorMode::UnassociatedClean
+                    }
+                    "premultiplied" | "Premultiplied" => ravif::AlphaColorMode::Premultiplied,
+                    _ => unreachable!(),
+                },
+                preserve_icc: embeds_icc(matches),
+            };
+
+            Ok(AvailableEncoders::Avif(Box::new(
+                AvifEncoder::new_with_options(options),
+            )))
+        }
+        #[cfg(feature = "webp")]
+        "webp" => {
+            use rimage::codecs::webp::WebPOptions;
+
+            let mut options = WebPOptions::new().unwrap();
+
+            options.quality = quality(name, matches).unwrap() as f32;
+            options.lossless = (quality_override.is_none()
+                && (matches.get_flag("lossless")
+                    || matches
+                        .get_one::<String>("quality-preset")
+                        .is_some_and(|preset| preset == "lossless")))
+                as i32;
+            options.near_lossless = 100 - *matches.get_one::<u8>("slight_loss").unwrap() as i32;
+            options.exact = matches.get_flag("exact") as i32;
+            options.method = *matches.get_one::<i32>("method").unwrap();
+
+            Ok(AvailableEncoders::Webp(Box::new(
+                WebPEncoder::new_with_options(options).preserve_icc(embeds_icc(matches)),
+            )))
+        }
+        "png" => Ok(AvailableEncoders::Png(Box::new(PngEncoder::new()))),
+        "ppm" => Ok(AvailableEncoders::Ppm(Box::new(PPMEncoder::new()))),
+        "qoi" => Ok(AvailableEncoders::Qoi(Box::new(QoiEncoder::new()))),
+
+        name => Err(ImageErrors::GenericString(format!(
+            "Encoder \"{name}\" not found",
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::bit_depth::BitDepth;
+
+    use super::*;
+
+    /// Decodes a fixture from `tests/files/png/lowbit` into 8-bit RGBA pixels
+    fn decode_rgba(name: &str) -> Vec<u8> {
+        let mut img = decode(format!("tests/files/png/lowbit/{name}.png")).unwrap();
+
+        assert_eq!(img.dimensions(), (7, 5));
+
+        img.convert_depth(BitDepth::Eight).unwrap();
+        img.convert_color(ColorSpace::RGBA).unwrap();
+
+        img.flatten_to_u8().remove(0)
+    }
+
+    #[test]
+    #[cfg(all(feature = "resize", feature = "quantization"))]
+    fn describe_pipeline() {
+        let matches = cli()
+            .try_get_matches_from([
+                "rimage",
+                "png",
+                "--quantization",
+                "80",
+                "--premultiply",
+                "--resize",
+                "50%",
+                "-",
+            ])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        let img = Image::fill(0u8, ColorSpace::RGBA, 64, 32);
+
+        assert_eq!(
+            describe_operations(matches, &img),
+            [
+                (2, "quantize to 80% quality".to_string()),
+                (4, "premultiply alpha".to_string()),
+                (6, "resize 50% to 32x16 using lanczos3 filter".to_string()),
+                (6, "unpremultiply alpha".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "resize", feature = "quantization"))]
+    fn premultiply_wraps_next_operation() {
+        let matches = cli()
+            .try_get_matches_from([
+                "rimage",
+                "png",
+                "--premultiply",
+                "--resize",
+                "50%",
+                "--premultiply",
+                "--quantization",
+                "80",
+                "--premultiply",
+                "--filter",
+                "nearest",
+                "--resize",
+                "25%",
+                "--premultiply",
+                "-",
+            ])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        let img = Image::fill(0u8, ColorSpace::RGBA, 64, 32);
+
+        assert_eq!(
+            describe_operations(matches, &img),
+            [
+                (2, "premultiply alpha".to_string()),
+                (4, "resize 50% to 32x16 using nearest filter".to_string()),
+                (4, "unpremultiply alpha".to_string()),
+                (6, "premultiply alpha".to_string()),
+                (8, "quantize to 80% quality".to_string()),
+                (8, "unpremultiply alpha".to_string()),
+                (10, "premultiply alpha".to_string()),
+                (14, "resize 25% to 16x8 using nearest filter".to_string()),
+                (14, "unpremultiply alpha".to_string()),
+            ]
+        );
+
+        assert_eq!(operations(matches, &img).len(), 9);
+    }
+
+    #[test]
+    #[cfg(all(feature = "resize", feature = "quantization"))]
+    fn operations_follow_arguments() {
+        let matches = cli()
+            .try_get_matches_from([
+                "rimage",
+                "png",
+                "--preset-size",
+                "youtube-thumbnail",
+                "--quantization",
+                "80",
+                "--resize",
+                "50%",
+                "--premultiply",
+                "--quantization",
+                "60",
+                "--resize",
+                "10x10",
+                "-",
+            ])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        let img = Image::fill(0u8, ColorSpace::RGBA, 64, 32);
+
+        let names = operations(matches, &img)
+            .into_iter()
+            .map(|(idx, operation)| (idx, operation.name()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            [
+                (2, "fast resize"),
+                (4, "quantize"),
+                (6, "fast resize"),
+                (8, "pre-multiply alpha"),
+                (10, "quantize"),
+                (10, "pre-multiply alpha"),
+                (12, "fast resize"),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_premultiplied_keeps_equal_indices() {
+        let step = |description: &str| -> Step { (description.to_string(), Box::new(AutoOrient)) };
+
+        let steps = wrap_premultiplied(
+            vec![(3, step("first")), (3, step("second")), (5, step("third"))],
+            &[1, 4],
+        );
+
+        assert_eq!(
+            steps
+                .into_iter()
+                .map(|(idx, (description, _))| (idx, description))
+                .collect::<Vec<_>>(),
+            [
+                (1, "premultiply alpha".to_string()),
+                (3, "first".to_string()),
+                (3, "unpremultiply alpha".to_string()),
+                (3, "second".to_string()),
+                (4, "premultiply alpha".to_string()),
+                (5, "third".to_string()),
+                (5, "unpremultiply alpha".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn quality_presets() {
+        let high = ["avif", "jpeg", "mozjpeg", "webp"].map(|codec| preset_quality(codec, "high"));
+
+        assert_eq!(high, [Some(80), Some(92), Some(88), Some(90)]);
+        assert_eq!(preset_quality("png", "high"), None);
+
+        let quality = |args: &[&str]| {
+            let matches = cli().try_get_matches_from(args.iter().chain(&["-"]))?;
+            let (codec, matches) = matches.subcommand().unwrap();
+
+            Ok::<_, clap::Error>(quality(codec, matches))
+        };
+
+        assert_eq!(
+            quality(&["rimage", "moz", "--quality-preset", "low"]).unwrap(),
+            Some(55)
+        );
+        assert_eq!(quality(&["rimage", "avif", "-q", "30"]).unwrap(), Some(30));
+        assert_eq!(quality(&["rimage", "avif"]).unwrap(), Some(50));
+        assert_eq!(quality(&["rimage", "png"]).unwrap(), None);
+
+        assert!(quality(&["rimage", "webp", "-q", "30", "--quality-preset", "low"]).is_err());
+        assert!(quality(&["rimage", "webp", "--lossless", "--quality-preset", "low"]).is_err());
+    }
+
+    #[test]
+    fn target_quality() {
+        let img = Image::from_fn(64, 64, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            let noise = (x * 7919 + y * 104_729) % 61;
+            *px = [
+                (x * 3 + noise) as u8,
+                (y * 3 + noise) as u8,
+                (noise * 4) as u8,
+                255,
+            ];
+        });
+
+        let matches = cli().try_get_matches_from(["rimage", "jpeg", "-"]).unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        let sizes = [0.002, 0.02].map(|target| {
+            let mut encoder = encoder_for_target("jpeg", Some(matches), &img, target)
+                .unwrap()
+                .unwrap();
+
+            let mut data = vec![];
+            encoder.encode(&img, &mut data).unwrap();
+
+            let distance = dssim(&img, &decode_encoded(&data, &encoder).unwrap()).unwrap();
+            assert!(distance <= target, "{distance} > {target}");
+
+            data.len()
+        });
+
+        assert!(sizes[1] < sizes[0]);
+        assert!(encoder_for_target("png", None, &img, 0.01)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "quantization")]
+    fn max_colors_keeps_small_palette() {
+        let matches = cli()
+            .try_get_matches_from(["rimage", "png", "--max-colors", "8", "-"])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        let mut img = Image::from_fn(16, 16, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            *px = [((x + y) % 5 * 50) as u8, 10, 20, 255];
+        });
+        let original = img.clone();
+
+        let operations = operations(matches, &img);
+        assert_eq!(operations.len(), 1);
+
+        for (_, operation) in operations {
+            operation.execute(&mut img).unwrap();
+        }
+
+        assert!(img.frames_ref() == original.frames_ref());
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn keep_frame_delays() {
+        let (img, delays) = decode_with_delays("tests/files/gif/f1t.gif").unwrap();
+        assert_eq!(delays, [100, 200, 300]);
+
+        let mut gif = default_encoder("gif").unwrap().with_frame_delays(delays);
+        let buf = encode_to_vec(&mut gif, &img, 0).unwrap();
+
+        let mut decoder = rimage::codecs::gif::GifDecoder::try_new(buf.as_slice()).unwrap();
+        zune_image::traits::DecoderTrait::decode(&mut decoder).unwrap();
+
+        assert_eq!(decoder.delays(), [100, 200, 300]);
+    }
+
+    #[test]
+    fn on_alpha() {
+        let mut transparent = decode("tests/files/png/f1ta.png").unwrap();
+        transparent.convert_color(ColorSpace::RGBA).unwrap();
+        assert!(!is_opaque(&transparent).unwrap());
+
+        let opaque = Image::fill(255_u8, ColorSpace::RGBA, 16, 16);
+
+        let check = |codec: &str, args: &[&str], img: &Image| {
+            let matches = cli()
+                .try_get_matches_from(["rimage", codec].iter().chain(args).chain(&["-"]))
+                .unwrap();
+            let (name, matches) = matches.subcommand().unwrap();
+
+            let mut img = img.clone();
+            check_alpha(&mut img, &encoder(name, matches).unwrap(), matches).map(|warn| (warn, img))
+        };
+
+        let Err(error) = check("jpeg", &["--on-alpha", "error"], &transparent) else {
+            panic!("transparent image is accepted");
+        };
+        assert!(error.to_string().contains("transparent"), "{error}");
+
+        let (warn, flattened) = check("jpeg", &["--on-alpha", "warn"], &transparent).unwrap();
+        assert!(warn);
+        assert_eq!(flattened.colorspace(), ColorSpace::RGB);
+
+        // fully transparent pixels become white instead of their hidden color
+        let transparent_pixels = transparent.flatten_to_u8()[0]
+            .chunks_exact(4)
+            .zip(flattened.flatten_to_u8()[0].chunks_exact(3))
+            .filter(|(src, _)| src[3] == 0)
+            .map(|(_, px)| px.to_vec())
+            .collect::<Vec<_>>();
+        assert!(!transparent_pixels.is_empty());
+        assert!(transparent_pixels.iter().all(|px| px == &[255, 255, 255]));
+
+        assert!(!check("jpeg", &[], &transparent).unwrap().0);
+        assert!(!check("jpeg", &["--on-alpha", "error"], &opaque).unwrap().0);
+        assert!(
+            !check("png", &["--on-alpha", "error"], &transparent)
+                .unwrap()
+                .0
+        );
+
+        let (_, untouched) = check("png", &[], &transparent).unwrap();
+        assert!(untouched.frames_ref() == transparent.frames_ref());
+    }
+
+    #[test]
+    #[cfg(all(feature = "metadata", feature = "mozjpeg"))]
+    fn keep_makernote() {
+        use exif::{Field, In, Tag, Value};
+
+        use crate::test_utils::raw_exif;
+
+        let exif = raw_exif(&[
+            Field {
+                tag: Tag::Orientation,
+                ifd_num: In::PRIMARY,
+                value: Value::Short(vec![1]),
+            },
+            Field {
+                tag: Tag::MakerNote,
+                ifd_num: In::PRIMARY,
+                value: Value::Undefined(b"WB 2.1 1.0 1.6".to_vec(), 0),
+            },
+        ]);
+
+        let has_makernote = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(["rimage", "mozjpeg"].iter().chain(args).chain(&["-"]))
+                .unwrap();
+            let (name, matches) = matches.subcommand().unwrap();
+
+            let mut img = Image::fill(0_u8, ColorSpace::RGB, 16, 16);
+            img.metadata_mut().parse_raw_exif(&exif);
+
+            for operation in metadata_operations(matches) {
+                operation.execute(&mut img).unwrap();
+            }
+
+            let mut buf = vec![];
+            encoder(name, matches)
+                .unwrap()
+                .encode(&img, &mut buf)
+                .unwrap();
+
+            exif::Reader::new()
+                .read_from_container(&mut std::io::Cursor::new(buf))
+                .unwrap()
+                .get_field(Tag::MakerNote, In::PRIMARY)
+                .is_some()
+        };
+
+        assert!(has_makernote(&["--keep-makernote"]));
+        assert!(!has_makernote(&[]));
+    }
+
+    #[test]
+    #[cfg(all(feature = "metadata", feature = "mozjpeg"))]
+    fn set_orientation() {
+        use exif::{In, Tag};
+
+        let matches = cli()
+            .try_get_matches_from(["rimage", "mozjpeg", "--set-orientation", "6", "-"])
+            .unwrap();
+        let (name, matches) = matches.subcommand().unwrap();
+
+        let mut img = Image::fill(0_u8, ColorSpace::RGB, 64, 32);
+
+        for operation in metadata_operations(matches) {
+            operation.execute(&mut img).unwrap();
+        }
+
+        assert_eq!(img.dimensions(), (64, 32));
+
+        let mut buf = vec![];
+        encoder(name, matches)
+            .unwrap()
+            .encode(&img, &mut buf)
+            .unwrap();
+
+        let exif = exif::Reader::new()
+            .read_from_container(&mut std::io::Cursor::new(buf))
+            .unwrap();
+
+        assert_eq!(
+            exif.get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|f| f.value.get_uint(0)),
+            Some(6)
+        );
+
+        assert!(cli()
+            .try_get_matches_from(["rimage", "mozjpeg", "--set-orientation", "9", "-"])
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "oxipng", feature = "webp", feature = "mozjpeg"))]
+    fn verify_lossless_encoders() {
+        let img = Image::from_fn(32, 32, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            *px = [(x * 8) as u8, (y * 8) as u8, (x ^ y) as u8, 255 - x as u8];
+        });
+
+        let verify = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(["rimage"].iter().chain(args).chain(&["-"]))
+                .unwrap();
+            let (name, matches) = matches.subcommand().unwrap();
+
+            let mut encoder = encoder(name, matches).unwrap();
+            let mut buf = vec![];
+            encoder.encode(&img, &mut buf).unwrap();
+
+            verify_lossless(&img, &buf, &encoder)
+        };
+
+        verify(&["png"]).unwrap();
+        verify(&["oxipng"]).unwrap();
+        verify(&["webp", "--lossless", "--exact"]).unwrap();
+
+        let error = verify(&["mozjpeg"]).unwrap_err();
+        assert!(error.to_string().contains("verification failed"), "{error}");
+    }
+
+    #[test]
+    fn encoder_extensions() {
+        let expected = [
+            ("farbfeld", "ff"),
+            ("jpeg", "jpg"),
+            ("jpeg_xl", "jxl"),
+            #[cfg(feature = "gif")]
+            ("gif", "gif"),
+            #[cfg(feature = "mozjpeg")]
+            ("mozjpeg", "jpg"),
+            #[cfg(feature = "oxipng")]
+            ("oxipng", "png"),
+            #[cfg(feature = "avif")]
+            ("avif", "avif"),
+            #[cfg(feature = "webp")]
+            ("webp", "webp"),
+            ("png", "png"),
+            ("ppm", "ppm"),
+            ("qoi", "qoi"),
+        ];
+
+        for (name, extension) in expected {
+            assert_eq!(
+                default_encoder(name).unwrap().to_extension(),
+                extension,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn default_encoders() {
+        let img = Image::from_fn(16, 16, ColorSpace::RGB, |x, y, px: &mut [u8; 4]| {
+            *px = [(x * 16) as u8, (y * 16) as u8, (x ^ y) as u8, 255];
+        });
+
+        let encode = |encoder: &mut AvailableEncoders| {
+            let mut buf = vec![];
+            encoder.encode(&img, &mut buf).unwrap();
+
+            buf
+        };
+
+        let parse = |args: &[&str]| {
+            cli()
+                .try_get_matches_from(["rimage"].iter().chain(args).chain(&["-"]))
+                .unwrap()
+                .subcommand()
+                .map(|(name, matches)| (name.to_string(), matches.clone()))
+                .unwrap()
+        };
+
+        for name in [
+            "jpeg",
+            #[cfg(feature = "gif")]
+            "gif",
+            #[cfg(feature = "mozjpeg")]
+            "mozjpeg",
+            #[cfg(feature = "oxipng")]
+            "oxipng",
+            #[cfg(feature = "avif")]
+            "avif",
+            #[cfg(feature = "webp")]
+            "webp",
+            "png",
+        ] {
+            let (_, matches) = parse(&[name]);
+
+            assert_eq!(
+                encode(&mut default_encoder(name).unwrap()),
+                encode(&mut encoder(name, &matches).unwrap()),
+                "{name}"
+            );
+        }
+
+        // overridden quality replaces quality options and presets
+        for args in [
+            &["jpeg", "-q", "90"][..],
+            #[cfg(feature = "gif")]
+            &["gif", "-q", "90"],
+            #[cfg(feature = "mozjpeg")]
+            &["mozjpeg", "--quality-preset", "high"],
+            #[cfg(feature = "avif")]
+            &["avif", "-q", "90"],
+            #[cfg(feature = "webp")]
+            &["webp", "--lossless"],
+        ] {
+            let (name, matches) = parse(args);
+
+            assert_eq!(
+                encode(&mut encoder_with_quality(&name, Some(&matches), 40).unwrap()),
+                encode(&mut encoder_with_quality(&name, None, 40).unwrap()),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_with_capacity() {
+        let img = Image::from_fn(1024, 1024, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            *px = [(x * 7) as u8, (y * 3) as u8, (x ^ y) as u8, 255];
+        });
+
+        for name in [
+            "png",
+            "qoi",
+            "jpeg",
+            #[cfg(feature = "mozjpeg")]
+            "mozjpeg",
+            #[cfg(feature = "oxipng")]
+            "oxipng",
+            #[cfg(feature = "webp")]
+            "webp",
+        ] {
+            let mut encoder = default_encoder(name).unwrap();
+
+            let mut direct = vec![];
+            encoder.encode(&img, &mut direct).unwrap();
+
+            // reservations too small, close and absurdly large all give the same output
+            for capacity in [0, direct.len() / 4, direct.len(), usize::MAX] {
+                let buf = encode_to_vec(&mut encoder, &img, capacity).unwrap();
+
+                assert_eq!(buf, direct, "{name}");
+                assert!(
+                    buf.capacity() <= MAX_RESERVED_CAPACITY.max(direct.len() * 2),
+                    "{name}"
+                );
+            }
+
+            // buffering encoders get no reservation
+            if !encoder.streams() {
+                let buf = encode_to_vec(&mut encoder, &img, MAX_RESERVED_CAPACITY).unwrap();
+
+                assert!(buf.capacity() < MAX_RESERVED_CAPACITY, "{name}");
+            }
+        }
+    }
+
+    fn animated() -> Image {
+        let frames = [32_u8, 96, 160]
+            .map(|value| Image::fill(value, ColorSpace::RGBA, 16, 16).frames_ref()[0].clone());
+
+        Image::new_frames(frames.to_vec(), BitDepth::Eight, 16, 16, ColorSpace::RGBA)
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn animated_to_webp_keeps_frames() {
+        let mut img = animated();
+        let mut webp = default_encoder("webp").unwrap();
+
+        assert_eq!(discard_frames(&mut img, &webp, "webp").unwrap(), None);
+
+        let mut buf = vec![];
+        webp.encode(&img, &mut buf).unwrap();
+
+        assert_eq!(decode_encoded(&buf, &webp).unwrap().frames_len(), 3);
+    }
+
+    #[test]
+    fn animated_to_png_keeps_first_frame() {
+        let mut img = animated();
+        let mut png = default_encoder("png").unwrap();
+
+        assert_eq!(
+            discard_frames(&mut img, &png, "png").unwrap().as_deref(),
+            Some("png doesn't support animation, discarding 2 frame(s)")
+        );
+        assert_eq!(img.frames_len(), 1);
+
+        let mut buf = vec![];
+        png.encode(&img, &mut buf).unwrap();
+
+        let decoded = decode_encoded(&buf, &png).unwrap();
+        assert_eq!(decoded.frames_len(), 1);
+        assert_eq!(decoded.flatten_to_u8()[0][..4], [32, 32, 32, 32]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "webp", feature = "mozjpeg"))]
+    fn animated_webp_to_jpeg() {
+        let mut webp = default_encoder("webp").unwrap();
+        let mut buf = vec![];
+        webp.encode(&animated(), &mut buf).unwrap();
+
+        let mut img = decode_encoded(&buf, &webp).unwrap();
+        assert_eq!(img.frames_len(), 3);
+        assert_eq!(discard_frames(&mut img, &webp, "webp").unwrap(), None);
+
+        let mut mozjpeg = default_encoder("mozjpeg").unwrap();
+        assert_eq!(
+            discard_frames(&mut img, &mozjpeg, "mozjpeg")
+                .unwrap()
+                .as_deref(),
+            Some("mozjpeg doesn't support animation, discarding 2 frame(s)")
+        );
+        assert_eq!(img.frames_len(), 1);
+
+        let mut buf = vec![];
+        mozjpeg.encode(&img, &mut buf).unwrap();
+
+        let jpeg = decode_encoded(&buf, &mozjpeg).unwrap();
+        assert_eq!(jpeg.frames_len(), 1);
+        assert_eq!(jpeg.dimensions(), (16, 16));
+    }
+
+    #[test]
+    #[cfg(feature = "mozjpeg")]
+    fn grayscale_jpeg() {
+        use zune_core::{bytestream::ZCursor, options::DecoderOptions};
+
+        let decoded_colorspace = |name: &str, source: ColorSpace| {
+            let matches = cli().try_get_matches_from(["rimage", name, "-"]).unwrap();
+            let (name, matches) = matches.subcommand().unwrap();
+
+            let mut img = Image::fill(96_u8, source, 16, 16);
+            img.convert_color(ColorSpace::RGBA).unwrap();
+
+            let mut encoder = encoder(name, matches).unwrap();
+            restore_grayscale(&mut img, source, &encoder).unwrap();
+
+            let mut buf = vec![];
+            encoder.encode(&img, &mut buf).unwrap();
+
+            // single component JPEG is decoded as Luma
+            Image::read(ZCursor::new(buf), DecoderOptions::default())
+                .unwrap()
+                .colorspace()
+        };
+
+        assert_eq!(
+            decoded_colorspace("mozjpeg", ColorSpace::Luma),
+            ColorSpace::Luma
+        );
+        assert_eq!(
+            decoded_colorspace("mozjpeg", ColorSpace::LumaA),
+            ColorSpace::Luma
+        );
+        assert_eq!(
+            decoded_colorspace("jpeg", ColorSpace::Luma),
+            ColorSpace::Luma
+        );
+        assert_eq!(
+            decoded_colorspace("mozjpeg", ColorSpace::RGB),
+            ColorSpace::RGB
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "mozjpeg")]
+    fn mozjpeg_component_quality() {
+        let mut img = decode("tests/files/jpg/f1t.jpg").unwrap();
+        img.convert_color(ColorSpace::RGBA).unwrap();
+
+        let encode = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(["rimage", "mozjpeg"].iter().chain(args).chain(&["-"]))
+                .unwrap();
+            let (name, matches) = matches.subcommand().unwrap();
+
+            let mut buf = vec![];
+            encoder(name, matches)
+                .unwrap()
+                .encode(&img, &mut buf)
+                .unwrap();
+
+            buf
+        };
+
+        let default = encode(&[]);
+        let luma = encode(&["--luma-quality", "20"]);
+
+        assert!(luma.len() < default.len());
+        assert_eq!(luma, encode(&["--luma_quality", "20"]));
+        assert_eq!(
+            luma,
+            encode(&["--luma-quality", "20", "--chroma-quality", "75"])
+        );
+        assert_ne!(luma, encode(&["--chroma-quality", "20"]));
+        assert_ne!(default, encode(&["--chroma-quality", "20"]));
+    }
+
+    #[test]
+    #[cfg(feature = "resize")]
+    fn alpha_premultiplied_divided_once() {
+        let straight = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(
+                    [
+                        "rimage",
+                        "png",
+                        "--alpha-premultiplied",
+                        "--filter",
+                        "nearest",
+                    ]
+                    .iter()
+                    .chain(args)
+                    .chain(&["-"]),
+                )
+                .unwrap();
+            let (_, matches) = matches.subcommand().unwrap();
+
+            // straight color 200 with alpha 128, premultiplied to 100
+            let pixels = [100, 100, 100, 128].repeat(64 * 64);
+            let mut img = Image::from_u8(&pixels, 64, 64, ColorSpace::RGBA);
+            img.metadata_mut().set_alpha(AlphaState::PreMultiplied);
+
+            for (_, operation) in operations(matches, &img) {
+                operation.execute(&mut img).unwrap();
+            }
+            straight_alpha(&mut img).unwrap();
+
+            assert_eq!(img.metadata().alpha(), AlphaState::NonPreMultiplied);
+
+            img.flatten_to_u8()[0][..4].to_vec()
+        };
+
+        assert_eq!(straight(&[]), [199, 199, 199, 128]);
+        assert_eq!(
+            straight(&["--resize", "50%", "--resize", "50%"]),
+            [199, 199, 199, 128]
+        );
+        assert_eq!(
+            straight(&["--premultiply", "--resize", "50%"]),
+            [199, 199, 199, 128]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn no_auto_orient() {
+        use exif::{Field, In, Tag, Value};
+
+        use crate::test_utils::raw_exif;
+
+        let exif = raw_exif(&[Field {
+            tag: Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            // rotated 90 degrees clockwise
+            value: Value::Short(vec![6]),
+        }]);
+
+        let dimensions = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(["rimage", "png"].iter().chain(args).chain(&["-"]))
+                .unwrap();
+            let (_, matches) = matches.subcommand().unwrap();
+
+            let mut img = Image::fill(0_u8, ColorSpace::RGB, 64, 32);
+            img.metadata_mut().parse_raw_exif(&exif);
+
+            if let Some(operation) = auto_orient(matches) {
+                operation.execute(&mut img).unwrap();
+            }
+
+            img.dimensions()
+        };
+
+        assert_eq!(dimensions(&[]), (32, 64));
+        assert_eq!(dimensions(&["--no-auto-orient"]), (64, 32));
+    }
+
+    #[test]
+    fn mismatched_channels() {
+        let matches = cli().try_get_matches_from(["rimage", "png", "-"]).unwrap();
+        let (name, matches) = matches.subcommand().unwrap();
+
+        let mut img = Image::fill(0_u8, ColorSpace::RGBA, 8, 8);
+        // an operation dropped the alpha channel without updating the colorspace
+        img.frames_mut()[0].channels_vec().pop();
+
+        let err = encoder(name, matches)
+            .unwrap()
+            .encode(&img, vec![])
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(_))
+        ));
+        assert!(err.to_string().contains("frame 0 has 3 channels"));
+    }
+
+    #[test]
+    fn decode_low_bit_depth_png() {
+        for depth in [1, 2, 4] {
+            let max = (1 << depth) - 1;
+
+            let expected = (0..5)
+                .flat_map(|y| (0..7).map(move |x| (x + y) % (max + 1)))
+                .map(|v| v * 255 / max)
+                .collect::<Vec<_>>();
+
+            let gray = decode_rgba(&format!("gray{depth}"));
+            let palette = decode_rgba(&format!("palette{depth}"));
+
+            for (idx, v) in expected.into_iter().enumerate() {
+                let v = v as u8;
+
+                assert_eq!(gray[idx * 4..idx * 4 + 4], [v, v, v, 255], "gray{depth}");
+                assert_eq!(
+                    palette[idx * 4..idx * 4 + 4],
+                    [v, 0, 255 - v, 255],
+                    "palette{depth}"
+                );
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ use clap::{arg, value_parser, Arg, ArgAction, ArgGroup, Command};
 use indoc::indoc;
 
 #[cfg(feature = "resize")]
-pub use resize::{ResizeFilter, ResizeValue};
+pub use resize::{ResizeFilter, ResizePreset, ResizeValue};
 
 #[cfg(feature = "resize")]
 mod resize;
@@ -17,8 +17,22 @@ impl Preprocessors for Command {
                     .args([
                         #[cfg(feature = "resize")]
                         "resize",
+                        #[cfg(feature = "resize")]
+                        "preset-size",
                         #[cfg(feature = "quantization")]
                          "quantization",
+                        #[cfg(feature = "quantization")]
+                         "max-colors",
+                    ])
+                    .multiple(true)
+            )
+            .group(
+                ArgGroup::new("palette")
+                    .args([
+                        #[cfg(feature = "quantization")]
+                        "quantization",
+                        #[cfg(feature = "quantization")]
+                        "max-colors",
                     ])
                     .multiple(true)
             )
@@ -33,16 +47,44 @@ impl Preprocessors for Command {
                     - 150%:    Adjust image size by this percentage
                     - 100x100: Resize image to Width×Height
                     - 100w:    Adjust image dimensions while maintaining the aspect ratio based on the width
-                    - 100h:    Adjust image dimensions while maintaining the aspect ratio based on the height"#})
+                    - 100h:    Adjust image dimensions while maintaining the aspect ratio based on the height
+                    - 2MP:     Scale image to about this many megapixels while maintaining the aspect ratio
+                    - 2000px:  Scale image to about this many pixels while maintaining the aspect ratio
+                    - 4x6in@300dpi: Resize image to print Width×Height inches at this resolution"#})
                     .value_parser(value_parser!(ResizeValue))
                     .action(ArgAction::Append),
 
+                #[cfg(feature = "resize")]
+                arg!(--"preset-size" <PRESET> "Resize the image(s) to fill one of the common sizes.")
+                    .long_help(indoc! {r#"Resize the image(s) to fill one of the common sizes.
+
+                    The aspect ratio is preserved, parts of the image that don't fit are cropped around the center."#})
+                    .value_parser(value_parser!(ResizePreset))
+                    .action(ArgAction::Append),
+
                 #[cfg(feature = "resize")]
                 arg!(--filter <FILTER> "Filter that used when resizing an image.")
                     .value_parser(value_parser!(ResizeFilter))
-                    .default_value("lanczos3")
+                    .default_value("lanczos3"),
+
+                #[cfg(feature = "resize")]
+                arg!(--"resize-filter-compare" "Prints how close each filter gets to a high quality resize.")
+                    .long_help(indoc! {r#"Prints how close each filter gets to a high quality resize.
+
+                    Images are resized to the first --resize value with every filter, then scaled up to twice
+                    that size and compared by DSSIM against a Lanczos3 resize to twice the size.
+                    Lower scores are better. Images are not processed when this flag is set."#})
                     .requires("resize"),
 
+                #[cfg(feature = "resize")]
+                arg!(--"max-output-pixels" <NUM> "Maximum number of pixels the resized image may have.")
+                    .long_help(indoc! {r#"Maximum number of pixels the resized image may have.
+
+                    Resizing to a bigger image fails instead of allocating a huge buffer.
+                    By default, the limit is 100 megapixels."#})
+                    .value_parser(value_parser!(usize))
+                    .default_value("100000000"),
+
                 #[cfg(feature = "quantization")]
                 arg!(--quantization [QUALITY] "Enables quantization with optional quality.")
                     .long_help(indoc! {r#"Enables quantization with optional quality in percentage.
@@ -52,18 +94,33 @@ impl Preprocessors for Command {
                     .action(ArgAction::Append)
                     .default_missing_value("75"),
 
+                #[cfg(feature = "quantization")]
+                arg!(--"max-colors" <NUM> "Reduces palette of image(s) with more unique colors than specified.")
+                    .long_help(indoc! {r#"Reduces palette of image(s) with more unique colors than specified.
+
+                    Images that already fit into the limit are left untouched, keeping them lossless.
+                    Other images are quantized to a palette of at most this many colors."#})
+                    .value_parser(value_parser!(u32).range(2..=256))
+                    .action(ArgAction::Append),
+
                 #[cfg(feature = "quantization")]
                 arg!(--dithering [QUALITY] "Enables dithering with optional quality.")
                     .long_help(indoc! {r#"Enables dithering with optional quality in percentage.
 
-                    Used with --quantization flag.
+                    Used with --quantization or --max-colors flags.
                     If quality is not provided, default 75 is used."#})
                     .value_parser(value_parser!(u8).range(1..=100))
                     .default_missing_value("75")
-                    .requires("quantization"),
+                    .requires("palette"),
 
                 position_sensitive_flag(arg!(--premultiply "Premultiply alpha before operation"))
-                    .action(ArgAction::Append)
+                    .action(ArgAction::Append),
+
+                arg!(--"alpha-premultiplied" "Treat input colors as already premultiplied by alpha.")
+                    .long_help(indoc! {r#"Treat input colors as already premultiplied by alpha.
+
+                    The multiply step of --premultiply is skipped and resizing keeps colors as is.
+                    Colors are divided by alpha once before encoding, so the output has straight alpha."#}),
             ])
     }
 }
@@ -4,6 +4,7 @@ use fast_image_resize as fr;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ResizeFilter {
+    Auto,
     Nearest,
     Box,
     Bilinear,
@@ -16,6 +17,7 @@ pub enum ResizeFilter {
 impl ValueEnum for ResizeFilter {
     fn value_variants<'a>() -> &'a [Self] {
         &[
+            ResizeFilter::Auto,
             ResizeFilter::Nearest,
             ResizeFilter::Box,
             ResizeFilter::Bilinear,
@@ -28,6 +30,7 @@ impl ValueEnum for ResizeFilter {
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
         Some(match self {
+            ResizeFilter::Auto => PossibleValue::new("auto").help("Uses Lanczos3 for downscaling and Catmull-Rom for upscaling."),
             ResizeFilter::Nearest => PossibleValue::new("nearest").help("Simplest filter, for each destination pixel gets nearest source pixel."),
             ResizeFilter::Box => PossibleValue::new("box").help("Each pixel contributes equally to destination. For upscaling, like Nearest."),
             ResizeFilter::Bilinear => PossibleValue::new("bilinear").help("Uses linear interpolation among contributing pixels for output."),
@@ -61,16 +64,19 @@ impl std::str::FromStr for ResizeFilter {
     }
 }
 
-impl From<ResizeFilter> for fr::ResizeAlg {
+impl From<ResizeFilter> for Option<fr::ResizeAlg> {
     fn from(value: ResizeFilter) -> Self {
         match value {
-            ResizeFilter::Nearest => fr::ResizeAlg::Nearest,
-            ResizeFilter::Box => fr::ResizeAlg::Convolution(fr::FilterType::Box),
-            ResizeFilter::Bilinear => fr::ResizeAlg::Convolution(fr::FilterType::Bilinear),
-            ResizeFilter::Hamming => fr::ResizeAlg::Convolution(fr::FilterType::Hamming),
-            ResizeFilter::CatmullRom => fr::ResizeAlg::Convolution(fr::FilterType::CatmullRom),
-            ResizeFilter::Mitchell => fr::ResizeAlg::Convolution(fr::FilterType::Mitchell),
-            ResizeFilter::Lanczos3 => fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3),
+            ResizeFilter::Auto => None,
+            ResizeFilter::Nearest => Some(fr::ResizeAlg::Nearest),
+            ResizeFilter::Box => Some(fr::ResizeAlg::Convolution(fr::FilterType::Box)),
+            ResizeFilter::Bilinear => Some(fr::ResizeAlg::Convolution(fr::FilterType::Bilinear)),
+            ResizeFilter::Hamming => Some(fr::ResizeAlg::Convolution(fr::FilterType::Hamming)),
+            ResizeFilter::CatmullRom => {
+                Some(fr::ResizeAlg::Convolution(fr::FilterType::CatmullRom))
+            }
+            ResizeFilter::Mitchell => Some(fr::ResizeAlg::Convolution(fr::FilterType::Mitchell)),
+            ResizeFilter::Lanczos3 => Some(fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3)),
         }
     }
 }
@@ -1,5 +1,7 @@
 mod filter;
+mod preset;
 mod value;
 
 pub use filter::ResizeFilter;
+pub use preset::ResizePreset;
 pub use value::ResizeValue;
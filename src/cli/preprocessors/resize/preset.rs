@@ -0,0 +1,114 @@
+use anyhow::anyhow;
+use clap::{builder::PossibleValue, ValueEnum};
+use rimage::operations::resize::ResizeFit;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResizePreset {
+    InstagramSquare,
+    InstagramPortrait,
+    InstagramStory,
+    TwitterHeader,
+    TwitterPost,
+    FacebookCover,
+    YoutubeThumbnail,
+}
+
+impl ResizePreset {
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            ResizePreset::InstagramSquare => (1080, 1080),
+            ResizePreset::InstagramPortrait => (1080, 1350),
+            ResizePreset::InstagramStory => (1080, 1920),
+            ResizePreset::TwitterHeader => (1500, 500),
+            ResizePreset::TwitterPost => (1600, 900),
+            ResizePreset::FacebookCover => (820, 312),
+            ResizePreset::YoutubeThumbnail => (1280, 720),
+        }
+    }
+
+    pub fn fit(&self) -> ResizeFit {
+        ResizeFit::Cover
+    }
+}
+
+impl ValueEnum for ResizePreset {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            ResizePreset::InstagramSquare,
+            ResizePreset::InstagramPortrait,
+            ResizePreset::InstagramStory,
+            ResizePreset::TwitterHeader,
+            ResizePreset::TwitterPost,
+            ResizePreset::FacebookCover,
+            ResizePreset::YoutubeThumbnail,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(match self {
+            ResizePreset::InstagramSquare => {
+                PossibleValue::new("instagram-square").help("1080x1080")
+            }
+            ResizePreset::InstagramPortrait => {
+                PossibleValue::new("instagram-portrait").help("1080x1350")
+            }
+            ResizePreset::InstagramStory => PossibleValue::new("instagram-story").help("1080x1920"),
+            ResizePreset::TwitterHeader => PossibleValue::new("twitter-header").help("1500x500"),
+            ResizePreset::TwitterPost => PossibleValue::new("twitter-post").help("1600x900"),
+            ResizePreset::FacebookCover => PossibleValue::new("facebook-cover").help("820x312"),
+            ResizePreset::YoutubeThumbnail => {
+                PossibleValue::new("youtube-thumbnail").help("1280x720")
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for ResizePreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+impl std::str::FromStr for ResizePreset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(anyhow!("invalid variant: {s}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str() {
+        assert_eq!(
+            "instagram-square".parse::<ResizePreset>().unwrap(),
+            ResizePreset::InstagramSquare
+        );
+        assert_eq!(
+            "twitter-header".parse::<ResizePreset>().unwrap(),
+            ResizePreset::TwitterHeader
+        );
+
+        assert!("instagram".parse::<ResizePreset>().is_err());
+    }
+
+    #[test]
+    fn dimensions() {
+        assert_eq!(ResizePreset::InstagramSquare.dimensions(), (1080, 1080));
+        assert_eq!(ResizePreset::InstagramSquare.fit(), ResizeFit::Cover);
+
+        assert_eq!(ResizePreset::TwitterHeader.dimensions(), (1500, 500));
+        assert_eq!(ResizePreset::TwitterHeader.fit(), ResizeFit::Cover);
+    }
+}
@@ -1,24 +1,34 @@
 use anyhow::anyhow;
 use regex::Regex;
 
+/// Largest side in pixels a print size may map to
+const MAX_PRINT_SIDE: f32 = 65535.;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ResizeValue {
     Multiplier(f32),
     Percentage(f32),
     Dimensions(Option<usize>, Option<usize>),
+    Pixels(u64),
+    /// Print size in inches at the given resolution
+    Physical {
+        width: f32,
+        height: f32,
+        dpi: u32,
+    },
 }
 
 impl ResizeValue {
     pub fn map_dimensions(&self, width: usize, height: usize) -> (usize, usize) {
         match self {
             ResizeValue::Multiplier(multiplier) => (
-                (width as f32 * multiplier) as usize,
-                (height as f32 * multiplier) as usize,
+                ((width as f32 * multiplier) as usize).max(1),
+                ((height as f32 * multiplier) as usize).max(1),
             ),
 
             ResizeValue::Percentage(percentage) => (
-                (width as f32 * (percentage / 100.)) as usize,
-                (height as f32 * (percentage / 100.)) as usize,
+                ((width as f32 * (percentage / 100.)) as usize).max(1),
+                ((height as f32 * (percentage / 100.)) as usize).max(1),
             ),
 
             ResizeValue::Dimensions(new_width, new_height) => {
@@ -37,6 +47,20 @@ impl ResizeValue {
 
                 (width, height)
             }
+
+            ResizeValue::Pixels(pixels) => {
+                let scale = (*pixels as f64 / (width * height) as f64).sqrt();
+
+                (
+                    ((width as f64 * scale).round() as usize).max(1),
+                    ((height as f64 * scale).round() as usize).max(1),
+                )
+            }
+
+            ResizeValue::Physical { width, height, dpi } => (
+                ((width * *dpi as f32).round() as usize).max(1),
+                ((height * *dpi as f32).round() as usize).max(1),
+            ),
         }
     }
 }
@@ -52,6 +76,10 @@ impl std::fmt::Display for ResizeValue {
             ResizeValue::Dimensions(Some(width), None) => f.write_fmt(format_args!("{width}w")),
             ResizeValue::Dimensions(None, Some(height)) => f.write_fmt(format_args!("{height}h")),
             ResizeValue::Dimensions(None, None) => f.write_fmt(format_args!("base")),
+            ResizeValue::Pixels(pixels) => f.write_fmt(format_args!("{pixels}px")),
+            ResizeValue::Physical { width, height, dpi } => {
+                f.write_fmt(format_args!("{width}x{height}in@{dpi}dpi"))
+            }
         }
     }
 }
@@ -63,8 +91,67 @@ impl std::str::FromStr for ResizeValue {
         let s = s.trim().to_lowercase();
 
         match s {
-            s if s.starts_with('@') => Ok(Self::Multiplier(s[1..].parse()?)),
-            s if s.ends_with('%') => Ok(Self::Percentage(s[..s.len() - 1].parse()?)),
+            s if s.ends_with("dpi") => {
+                let Some((size, dpi)) = s[..s.len() - 3].split_once("in@") else {
+                    return Err(anyhow!("Invalid print size"));
+                };
+                let Some((width, height)) = size.split_once('x') else {
+                    return Err(anyhow!("Invalid print size"));
+                };
+
+                let width: f32 = width.trim().parse()?;
+                let height: f32 = height.trim().parse()?;
+                let dpi: u32 = dpi.trim().parse()?;
+
+                if !(width.is_finite()
+                    && height.is_finite()
+                    && width > 0.
+                    && height > 0.
+                    && dpi > 0)
+                {
+                    return Err(anyhow!("Print size and DPI must be greater than zero"));
+                }
+                if width * dpi as f32 > MAX_PRINT_SIDE || height * dpi as f32 > MAX_PRINT_SIDE {
+                    return Err(anyhow!(
+                        "Print size must be at most {MAX_PRINT_SIDE} pixels per side"
+                    ));
+                }
+
+                Ok(Self::Physical { width, height, dpi })
+            }
+            s if s.starts_with('@') => {
+                let multiplier: f32 = s[1..].parse()?;
+
+                if multiplier.is_nan() || multiplier <= 0. {
+                    return Err(anyhow!("Multiplier must be greater than zero"));
+                }
+
+                Ok(Self::Multiplier(multiplier))
+            }
+            s if s.ends_with('%') => {
+                let percentage: f32 = s[..s.len() - 1].parse()?;
+
+                if percentage.is_nan() || percentage <= 0. {
+                    return Err(anyhow!("Percentage must be greater than zero"));
+                }
+
+                Ok(Self::Percentage(percentage))
+            }
+            s if s.ends_with("mp") || s.ends_with("px") => {
+                let value = s[..s.len() - 2].trim();
+
+                let pixels = if s.ends_with("mp") {
+                    (value.parse::<f64>()? * 1_000_000.) as u64
+                } else {
+                    value.parse()?
+                };
+
+                if pixels == 0 {
+                    return Err(anyhow!("Pixel budget must be greater than zero"));
+                }
+
+                Ok(Self::Pixels(pixels))
+            }
             s if s.contains('w') && s.contains('h') => Err(anyhow!("Invalid resize value")),
             s if s.contains('w') => {
                 let re = Regex::new(r"(?P<width>\d+)").unwrap();
@@ -127,6 +214,18 @@ mod tests {
         assert_eq!(ResizeValue::Dimensions(Some(150), None).to_string(), "150w");
 
         assert_eq!(ResizeValue::Dimensions(None, None).to_string(), "base");
+
+        assert_eq!(ResizeValue::Pixels(2_000_000).to_string(), "2000000px");
+
+        assert_eq!(
+            ResizeValue::Physical {
+                width: 4.,
+                height: 6.,
+                dpi: 300
+            }
+            .to_string(),
+            "4x6in@300dpi"
+        );
     }
 
     #[test]
@@ -201,6 +300,53 @@ mod tests {
             ResizeValue::Dimensions(None, Some(150))
         );
 
+        assert_eq!(
+            "2MP".parse::<ResizeValue>().unwrap(),
+            ResizeValue::Pixels(2_000_000)
+        );
+
+        assert_eq!(
+            "1.5mp".parse::<ResizeValue>().unwrap(),
+            ResizeValue::Pixels(1_500_000)
+        );
+
+        assert_eq!(
+            "2000000px".parse::<ResizeValue>().unwrap(),
+            ResizeValue::Pixels(2_000_000)
+        );
+
+        assert_eq!(
+            "4x6in@300dpi".parse::<ResizeValue>().unwrap(),
+            ResizeValue::Physical {
+                width: 4.,
+                height: 6.,
+                dpi: 300
+            }
+        );
+
+        assert_eq!(
+            "8.5x11in@150DPI".parse::<ResizeValue>().unwrap(),
+            ResizeValue::Physical {
+                width: 8.5,
+                height: 11.,
+                dpi: 150
+            }
+        );
+
+        assert_eq!(
+            "0.5%".parse::<ResizeValue>().unwrap(),
+            ResizeValue::Percentage(0.5)
+        );
+
+        assert!("0%".parse::<ResizeValue>().is_err());
+        assert!("-50%".parse::<ResizeValue>().is_err());
+        assert!("@0".parse::<ResizeValue>().is_err());
+        assert!("0mp".parse::<ResizeValue>().is_err());
+        assert!("4x6in@0dpi".parse::<ResizeValue>().is_err());
+        assert!("4x6@300dpi".parse::<ResizeValue>().is_err());
+        assert!("infx6in@300dpi".parse::<ResizeValue>().is_err());
+        assert!("4xnanin@300dpi".parse::<ResizeValue>().is_err());
+        assert!("1000x6in@300dpi".parse::<ResizeValue>().is_err());
         assert!("_x_".parse::<ResizeValue>().is_err());
         assert!("150wh".parse::<ResizeValue>().is_err());
     }
@@ -217,6 +363,15 @@ mod tests {
         assert_eq!(resize_value.map_dimensions(100, 200), (50, 100));
     }
 
+    #[test]
+    fn map_dimensions_tiny_percentage() {
+        let resize_value = ResizeValue::Percentage(0.5);
+        assert_eq!(resize_value.map_dimensions(100, 20), (1, 1));
+
+        let resize_value = ResizeValue::Multiplier(0.001);
+        assert_eq!(resize_value.map_dimensions(100, 20), (1, 1));
+    }
+
     #[test]
     fn map_dimensions_dimensions() {
         let resize_value = ResizeValue::Dimensions(Some(300), Some(600));
@@ -240,4 +395,32 @@ mod tests {
         let resize_value = ResizeValue::Dimensions(None, None);
         assert_eq!(resize_value.map_dimensions(100, 200), (100, 200));
     }
+
+    #[test]
+    fn map_dimensions_pixels_landscape() {
+        let resize_value = ResizeValue::Pixels(2_000_000);
+        let (width, height) = resize_value.map_dimensions(6000, 4000);
+
+        assert_eq!((width, height), (1732, 1155));
+        assert!((width * height).abs_diff(2_000_000) < 2_000_000 / 100);
+    }
+
+    #[test]
+    fn map_dimensions_pixels_portrait() {
+        let resize_value = ResizeValue::Pixels(2_000_000);
+        let (width, height) = resize_value.map_dimensions(1080, 1920);
+
+        assert_eq!((width, height), (1061, 1886));
+        assert!((width * height).abs_diff(2_000_000) < 2_000_000 / 100);
+    }
+
+    #[test]
+    fn map_dimensions_physical() {
+        let resize_value = ResizeValue::Physical {
+            width: 4.,
+            height: 6.,
+            dpi: 300,
+        };
+        assert_eq!(resize_value.map_dimensions(6000, 4000), (1200, 1800));
+    }
 }
@@ -0,0 +1,289 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context};
+use clap::{parser::ValueSource, Arg, ArgMatches};
+use toml::{Table, Value};
+
+use super::cli;
+
+/// Extension appended to the input file name to get its sidecar config
+pub const SIDECAR_EXTENSION: &str = "rimage.toml";
+
+/// Path of the sidecar config for the input file, `image.png` has `image.png.rimage.toml`
+pub fn sidecar_path(input: &Path) -> PathBuf {
+    let mut path = input.as_os_str().to_os_string();
+    path.push(".");
+    path.push(SIDECAR_EXTENSION);
+
+    PathBuf::from(path)
+}
+
+/// Checks if the path points to a sidecar config rather than an image
+pub fn is_sidecar(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|f| f.to_str())
+        .is_some_and(|f| f.ends_with(&format!(".{SIDECAR_EXTENSION}")))
+}
+
+/// Per-image overrides of the command line settings
+///
+/// Keys are long names of the codec options, with an optional `codec` key
+/// selecting a different codec for the image:
+///
+/// ```toml
+/// codec = "webp"
+/// quality = 90
+/// resize = ["50%"]
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sidecar {
+    codec: Option<String>,
+    options: Table,
+}
+
+impl FromStr for Sidecar {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut options = s.parse::<Table>()?;
+
+        let codec = match options.remove("codec") {
+            Some(Value::String(codec)) => Some(codec),
+            Some(_) => return Err(anyhow!("`codec` must be a string")),
+            None => None,
+        };
+
+        Ok(Self { codec, options })
+    }
+}
+
+impl Sidecar {
+    /// Loads the sidecar config of the input file, if there is one
+    pub fn load(input: &Path) -> anyhow::Result<Option<Self>> {
+        let path = sidecar_path(input);
+
+        match fs::read_to_string(&path) {
+            Ok(content) => content
+                .parse()
+                .map(Some)
+                .with_context(|| format!("invalid sidecar {}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("unable to read {}", path.display())),
+        }
+    }
+
+    /// Merges the sidecar over the command line settings of `subcommand`
+    ///
+    /// Options set on the command line are kept unless the sidecar sets them or options
+    /// conflicting with them, or the codec selected by the sidecar doesn't have them.
+    /// Returns the codec for the image along with its matches.
+    pub fn apply(
+        &self,
+        subcommand: &str,
+        matches: &ArgMatches,
+    ) -> anyhow::Result<(String, ArgMatches)> {
+        let cli = cli();
+
+        let source = cli
+            .find_subcommand(subcommand)
+            .ok_or_else(|| anyhow!("unknown codec: {subcommand}"))?;
+        let codec = self.codec.as_deref().unwrap_or(subcommand);
+        let target = cli
+            .find_subcommand(codec)
+            .ok_or_else(|| anyhow!("unknown codec: {codec}"))?;
+
+        let find_arg = |long: &str| target.get_arguments().find(|a| a.get_long() == Some(long));
+
+        let conflicts_with_options = |arg: &Arg| {
+            self.options
+                .keys()
+                .filter_map(|key| find_arg(key))
+                .any(|other| {
+                    target.get_arg_conflicts_with(other).contains(&arg)
+                        || target.get_arg_conflicts_with(arg).contains(&other)
+                })
+        };
+
+        let mut args: Vec<OsString> = vec!["rimage".into(), target.get_name().into()];
+
+        for arg in source.get_arguments() {
+            let Some(long) = arg.get_long() else {
+                continue;
+            };
+
+            if self.options.contains_key(long)
+                || matches.value_source(arg.get_id().as_str()) != Some(ValueSource::CommandLine)
+            {
+                continue;
+            }
+
+            let Some(arg) = find_arg(long) else {
+                continue;
+            };
+
+            if conflicts_with_options(arg) {
+                continue;
+            }
+
+            if !arg.get_action().takes_values() {
+                args.push(format!("--{long}").into());
+                continue;
+            }
+
+            for occurrence in matches
+                .get_raw_occurrences(arg.get_id().as_str())
+                .into_iter()
+                .flatten()
+            {
+                args.push(option(long, &join(arg, occurrence.collect())));
+            }
+        }
+
+        for (key, value) in &self.options {
+            let arg = find_arg(key).ok_or_else(|| anyhow!("{codec} has no --{key} option"))?;
+
+            let values = match value {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+
+            for value in values {
+                match value {
+                    Value::Boolean(true) if !arg.get_action().takes_values() => {
+                        args.push(format!("--{key}").into())
+                    }
+                    Value::Boolean(false) if !arg.get_action().takes_values() => {}
+                    Value::String(value) => args.push(option(key, value.as_ref())),
+                    Value::Integer(value) => args.push(option(key, value.to_string().as_ref())),
+                    Value::Float(value) => args.push(option(key, value.to_string().as_ref())),
+                    Value::Boolean(value) => args.push(option(key, value.to_string().as_ref())),
+                    value => {
+                        return Err(anyhow!("unsupported {} value of `{key}`", value.type_str()))
+                    }
+                }
+            }
+        }
+
+        args.push("-".into());
+
+        let matches = cli.clone().try_get_matches_from(args)?;
+        let (_, matches) = matches.subcommand().expect("subcommand is provided");
+
+        Ok((target.get_name().to_string(), matches.clone()))
+    }
+}
+
+/// Formats `--long=value` argument
+pub fn option(long: &str, value: &OsStr) -> OsString {
+    let mut option = OsString::from(format!("--{long}="));
+    option.push(value);
+
+    option
+}
+
+/// Joins raw values of a single occurrence back with the delimiter of the argument
+fn join(arg: &Arg, values: Vec<&OsStr>) -> OsString {
+    let delimiter = arg.get_value_delimiter().unwrap_or(' ');
+
+    values
+        .iter()
+        .enumerate()
+        .fold(OsString::new(), |mut acc, (idx, value)| {
+            if idx > 0 {
+                acc.push(delimiter.to_string());
+            }
+            acc.push(value);
+
+            acc
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cli::{
+            pipeline::encoder,
+            utils::paths::{get_paths, set_extension},
+        },
+        test_utils::TempDir,
+    };
+
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn parse_sidecar() {
+        let sidecar: Sidecar = "codec = \"webp\"\nquality = 90\nresize = [\"50%\"]"
+            .parse()
+            .unwrap();
+
+        assert_eq!(sidecar.codec.as_deref(), Some("webp"));
+        assert_eq!(sidecar.options.len(), 2);
+
+        assert!("codec = 1".parse::<Sidecar>().is_err());
+        assert!(is_sidecar(&sidecar_path(Path::new("image.png"))));
+        assert!(!is_sidecar(Path::new("image.png")));
+    }
+
+    #[test]
+    fn sidecar_overrides_codec() {
+        let dir = TempDir::new("sidecar_overrides_codec");
+
+        let files = ["first.png", "second.png"].map(|name| {
+            let path = dir.join(name);
+            fs::copy("tests/files/png/f1t.png", &path).unwrap();
+            path
+        });
+
+        fs::write(
+            sidecar_path(&files[1]),
+            "codec = \"webp\"\nquality = 90\nlossless = false",
+        )
+        .unwrap();
+
+        let matches = cli()
+            .try_get_matches_from(
+                [
+                    "rimage",
+                    "mozjpeg",
+                    "--quality",
+                    "50",
+                    "--strip-gps",
+                    "--resize",
+                    "50%",
+                ]
+                .map(OsString::from)
+                .into_iter()
+                .chain(files.iter().map(OsString::from)),
+            )
+            .unwrap();
+        let (subcommand, matches) = matches.subcommand().unwrap();
+
+        let mut outputs = get_paths(files.to_vec(), None, None, false, false)
+            .map(|(input, mut output)| {
+                let (codec, matches) = match Sidecar::load(&input).unwrap() {
+                    Some(sidecar) => sidecar.apply(subcommand, matches).unwrap(),
+                    None => (subcommand.to_string(), matches.clone()),
+                };
+
+                assert!(matches.get_flag("strip-gps"));
+                assert_eq!(matches.get_raw("resize").unwrap().count(), 1);
+
+                let encoder = encoder(&codec, &matches).unwrap();
+                set_extension(&mut output, encoder.to_extension());
+
+                (output, *matches.get_one::<u8>("quality").unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        outputs.sort();
+
+        assert_eq!(outputs[0], (dir.join("first.jpg"), 50));
+        assert_eq!(outputs[1], (dir.join("second.webp"), 90));
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use super::paths::output_name;
+
+/// Temporary directory removed with its content on drop
+pub struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    /// Creates a new directory in `temp_dir`, or in the system temp directory by default
+    pub fn new(temp_dir: Option<&Path>) -> io::Result<Self> {
+        let parent = temp_dir.map_or_else(std::env::temp_dir, Path::to_path_buf);
+
+        let path = (0..)
+            .map(|idx| parent.join(format!("rimage-{}-{idx}", std::process::id())))
+            .find(|path| !path.exists())
+            .expect("unbounded range");
+
+        fs::create_dir_all(&path)?;
+
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Files of a ZIP archive extracted into a scratch directory
+pub struct Extracted {
+    dir: ScratchDir,
+    files: Vec<PathBuf>,
+}
+
+impl Extracted {
+    /// Extracts every file of the archive, keeping its folder structure
+    ///
+    /// Entries with unsafe names, like absolute paths or paths escaping with `..`, are skipped.
+    pub fn new(archive: &Path, temp_dir: Option<&Path>) -> anyhow::Result<Self> {
+        let mut archive = ZipArchive::new(File::open(archive)?)?;
+        let dir = ScratchDir::new(temp_dir)?;
+        let mut files = vec![];
+
+        for idx in 0..archive.len() {
+            let mut entry = archive.by_index(idx)?;
+
+            if entry.is_dir() {
+                continue;
+            }
+
+            let Some(name) = entry.enclosed_name() else {
+                log::warn!("{}: unsafe path in archive, skipping", entry.name());
+                continue;
+            };
+
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap())?;
+
+            io::copy(&mut entry, &mut File::create(&path)?)
+                .with_context(|| format!("unable to extract {}", entry.name()))?;
+
+            files.push(path);
+        }
+
+        Ok(Self { dir, files })
+    }
+
+    /// Input and output paths of the extracted files
+    ///
+    /// Outputs keep the folder structure of the archive inside `out_dir`.
+    pub fn paths(
+        &self,
+        out_dir: &Path,
+        suffix: Option<&str>,
+        append_extension: bool,
+    ) -> Vec<(PathBuf, PathBuf)> {
+        self.files
+            .iter()
+            .map(|input| {
+                let relative = input
+                    .parent()
+                    .and_then(|p| p.strip_prefix(self.dir.path()).ok())
+                    .unwrap_or(Path::new(""));
+
+                let output =
+                    out_dir
+                        .join(relative)
+                        .join(output_name(input, suffix, append_extension));
+
+                (input.clone(), output)
+            })
+            .collect()
+    }
+}
+
+/// Writes `files` into a new ZIP archive, named by their paths relative to `root`
+///
+/// Images are already compressed, so entries are stored as is.
+pub fn write_zip(archive: &Path, root: &Path, files: &[PathBuf]) -> anyhow::Result<()> {
+    let mut writer = ZipWriter::new(BufWriter::new(File::create(archive)?));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    for file in files {
+        let name = file.strip_prefix(root).unwrap_or(file);
+        // zip entries always use forward slashes
+        let name = name
+            .iter()
+            .map(|c| c.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        writer.start_file(name, options)?;
+        io::copy(&mut File::open(file)?, &mut writer)?;
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::{
+        cli::{
+            cli,
+            pipeline::{decode, encoder},
+        },
+        test_utils::TempDir,
+    };
+
+    use super::*;
+
+    #[test]
+    fn optimize_zip() {
+        let dir = TempDir::new("optimize_zip");
+
+        let input = dir.join("input.zip");
+        let output = dir.join("output.zip");
+
+        let png = fs::read("tests/files/png/f1t.png").unwrap();
+
+        let mut writer = ZipWriter::new(File::create(&input).unwrap());
+        for name in ["first.png", "nested/second.png"] {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(&png).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let extracted = Extracted::new(&input, Some(dir.path())).unwrap();
+        let staging = ScratchDir::new(Some(dir.path())).unwrap();
+
+        let matches = cli().get_matches_from(["rimage", "png", "-"]);
+        let (name, matches) = matches.subcommand().unwrap();
+
+        let outputs = extracted
+            .paths(staging.path(), None, false)
+            .into_iter()
+            .map(|(input, mut output)| {
+                let img = decode(&input).unwrap();
+                let mut encoder = encoder(name, matches).unwrap();
+
+                output.set_extension("png");
+                fs::create_dir_all(output.parent().unwrap()).unwrap();
+                encoder
+                    .encode(&img, File::create(&output).unwrap())
+                    .unwrap();
+
+                output
+            })
+            .collect::<Vec<_>>();
+
+        for output in &outputs {
+            assert!(output.is_file());
+        }
+
+        write_zip(&output, staging.path(), &outputs).unwrap();
+
+        let archive = ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let mut names = archive.file_names().collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(names, ["first.png", "nested/second.png"]);
+    }
+}
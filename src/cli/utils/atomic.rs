@@ -0,0 +1,199 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Temporary file removed on drop unless persisted
+struct TempFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl TempFile {
+    fn create(dir: &Path, name: &str) -> io::Result<(Self, File)> {
+        let path = dir.join(format!(
+            ".{name}.{}.{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let file = File::create(&path)?;
+
+        Ok((
+            Self {
+                path,
+                persisted: false,
+            },
+            file,
+        ))
+    }
+
+    fn persist(mut self, output: &Path) -> io::Result<()> {
+        match fs::rename(&self.path, output) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                // temp directory is on another filesystem, so the file is first copied
+                // next to the output to keep the final rename atomic
+                let dir = output.parent().filter(|dir| !dir.as_os_str().is_empty());
+                let (mut local, _) =
+                    TempFile::create(dir.unwrap_or(Path::new(".")), &file_name(output))?;
+
+                fs::copy(&self.path, &local.path)?;
+                fs::rename(&local.path, output)?;
+
+                local.persisted = true;
+
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        }
+
+        self.persisted = true;
+
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|f| f.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Creates missing parent directories of the output file
+///
+/// Paths without a parent, like bare file names or the filesystem root, need nothing to be created.
+pub fn create_parent_dirs(output: &Path) -> io::Result<()> {
+    match output.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+}
+
+/// Writes the output file atomically
+///
+/// Data is written into a temp file in `temp_dir`, or next to the output by default,
+/// which is renamed into place only when `write` succeeds. Otherwise the temp file is removed,
+/// so no partial output is left behind.
+pub fn write_atomic<T, E>(
+    output: &Path,
+    temp_dir: Option<&Path>,
+    write: impl FnOnce(&mut File) -> Result<T, E>,
+) -> Result<T, E>
+where
+    E: From<io::Error>,
+{
+    let dir = temp_dir
+        .or(output.parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let (temp, mut file) = TempFile::create(dir, &file_name(output))?;
+
+    let result = write(&mut file)?;
+
+    file.sync_all()?;
+    drop(file);
+
+    temp.persist(output)?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    #[test]
+    fn write_in_place() {
+        let dir = TempDir::new("atomic_write_in_place");
+        let output = dir.join("image.png");
+
+        write_atomic(&output, None, |file| file.write_all(b"image")).unwrap();
+
+        assert_eq!(fs::read(&output).unwrap(), b"image");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn custom_temp_dir() {
+        let dir = TempDir::new("atomic_custom_temp_dir");
+        let temp = dir.join("temp");
+        fs::create_dir(&temp).unwrap();
+
+        let output = dir.join("image.png");
+
+        write_atomic(&output, Some(&temp), |file| {
+            file.write_all(b"image")?;
+
+            assert_eq!(fs::read_dir(&temp).unwrap().count(), 1);
+            assert!(!output.exists());
+
+            Ok::<_, io::Error>(())
+        })
+        .unwrap();
+
+        assert_eq!(fs::read(&output).unwrap(), b"image");
+        assert_eq!(fs::read_dir(&temp).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn deeply_nested_output() {
+        let dir = TempDir::new("atomic_deeply_nested_output");
+        let output = dir.join("a/b/c/d/e/image.png");
+
+        create_parent_dirs(&output).unwrap();
+        write_atomic(&output, None, |file| file.write_all(b"image")).unwrap();
+
+        assert_eq!(fs::read(&output).unwrap(), b"image");
+
+        create_parent_dirs(Path::new("image.png")).unwrap();
+        create_parent_dirs(Path::new("/")).unwrap();
+    }
+
+    #[test]
+    fn no_partial_file_on_failure() {
+        let dir = TempDir::new("atomic_no_partial_file");
+        let output = dir.join("image.png");
+
+        fs::write(&output, b"previous").unwrap();
+
+        let result = write_atomic(&output, None, |file| {
+            file.write_all(b"half of the ima")?;
+
+            Err::<(), _>(io::Error::other("encoder failed mid-write"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read(&output).unwrap(), b"previous");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn failed_rename() {
+        let dir = TempDir::new("atomic_failed_rename");
+        let output = dir.join("image.png");
+        fs::create_dir(&output).unwrap();
+
+        let result = write_atomic(&output, None, |file| file.write_all(b"image"));
+
+        assert!(result.is_err());
+        assert!(output.is_dir());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
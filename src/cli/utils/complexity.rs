@@ -0,0 +1,147 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use zune_image::image::Image;
+
+/// Mean luma difference between neighbouring pixels of images considered fully detailed
+const DETAILED_GRADIENT: f64 = 24.;
+
+/// Estimates how detailed the image is, from 0 for flat images to 1 for busy ones
+///
+/// Measures mean gradient magnitude over luma of the first frame, approximated
+/// by the average of color components. Alpha is ignored.
+pub fn complexity(img: &Image) -> f64 {
+    let (width, height) = img.dimensions();
+    let colorspace = img.colorspace();
+    let components = colorspace.num_components();
+    let colors = components - usize::from(colorspace.has_alpha());
+
+    let Some(pixels) = img.flatten_to_u8().into_iter().next() else {
+        return 0.;
+    };
+
+    let luma = pixels
+        .chunks_exact(components)
+        .map(|px| px[..colors].iter().map(|v| f64::from(*v)).sum::<f64>() / colors as f64)
+        .collect::<Vec<_>>();
+
+    let (mut total, mut count) = (0., 0usize);
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = luma[y * width + x];
+
+            if x + 1 < width {
+                total += (luma[y * width + x + 1] - value).abs();
+                count += 1;
+            }
+
+            if y + 1 < height {
+                total += (luma[(y + 1) * width + x] - value).abs();
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        return 0.;
+    }
+
+    (total / count as f64 / DETAILED_GRADIENT).min(1.)
+}
+
+/// Range of qualities picked by image complexity, like `60-90`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QualityRange {
+    /// Quality of flat images
+    pub min: u8,
+    /// Quality of detailed images
+    pub max: u8,
+}
+
+impl QualityRange {
+    /// Quality for an image of given [`complexity`]
+    pub fn quality(&self, complexity: f64) -> u8 {
+        let span = f64::from(self.max - self.min);
+
+        self.min + (span * complexity.clamp(0., 1.)).round() as u8
+    }
+}
+
+impl FromStr for QualityRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |quality: &str| {
+            quality
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|quality| (1..=100).contains(quality))
+                .ok_or_else(|| anyhow!("invalid quality `{quality}` in `{s}`"))
+        };
+
+        let (min, max) = s
+            .split_once('-')
+            .ok_or_else(|| anyhow!("expected a range like `60-90`, got `{s}`"))?;
+        let (min, max) = (parse(min)?, parse(max)?);
+
+        if min > max {
+            return Err(anyhow!("minimum quality of `{s}` exceeds the maximum"));
+        }
+
+        Ok(Self { min, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::colorspace::ColorSpace;
+
+    use super::*;
+
+    #[test]
+    fn flat_below_detailed() {
+        let flat = Image::fill(128_u8, ColorSpace::RGBA, 64, 64);
+
+        let gradient = (0..64 * 64)
+            .flat_map(|idx| [(idx % 64 * 4) as u8; 3])
+            .collect::<Vec<_>>();
+        let gradient = Image::from_u8(&gradient, 64, 64, ColorSpace::RGB);
+
+        // deterministic noise
+        let noise = (0..64 * 64 * 3)
+            .map(|idx: u32| (idx.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect::<Vec<_>>();
+        let noise = Image::from_u8(&noise, 64, 64, ColorSpace::RGB);
+
+        let (flat, gradient, noise) =
+            (complexity(&flat), complexity(&gradient), complexity(&noise));
+
+        assert_eq!(flat, 0.);
+        assert!(
+            flat < gradient && gradient < noise,
+            "{flat} {gradient} {noise}"
+        );
+
+        let range = "60-90".parse::<QualityRange>().unwrap();
+
+        assert_eq!(range.quality(flat), 60);
+        assert_eq!(range.quality(noise), 90);
+        assert!(range.quality(flat) < range.quality(gradient));
+    }
+
+    #[test]
+    fn parse_range() {
+        assert_eq!(
+            " 50 - 80".parse::<QualityRange>().unwrap(),
+            QualityRange { min: 50, max: 80 }
+        );
+        assert_eq!("75-75".parse::<QualityRange>().unwrap().quality(0.5), 75);
+
+        assert!("80-50".parse::<QualityRange>().is_err());
+        assert!("0-50".parse::<QualityRange>().is_err());
+        assert!("50-101".parse::<QualityRange>().is_err());
+        assert!("80".parse::<QualityRange>().is_err());
+    }
+}
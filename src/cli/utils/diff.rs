@@ -0,0 +1,81 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, image::Image};
+
+/// Factor absolute differences are multiplied by, making subtle encoding artifacts visible
+pub const AMPLIFY: u16 = 8;
+
+/// Amplified per-channel difference between two images of the same dimensions
+///
+/// Both images are compared as 8-bit RGBA, each color channel of the result holds
+/// the absolute difference multiplied by `amplify`, saturating at 255.
+/// Differences in transparency are added to every color channel, the result is opaque.
+pub fn diff(original: &Image, distorted: &Image, amplify: u16) -> Result<Image, ImageErrors> {
+    if original.dimensions() != distorted.dimensions() {
+        return Err(ImageErrors::GenericString(format!(
+            "unable to compare {:?} image with {:?} one",
+            original.dimensions(),
+            distorted.dimensions()
+        )));
+    }
+
+    let (width, height) = original.dimensions();
+    let original = rgba(original)?;
+    let distorted = rgba(distorted)?;
+
+    let pixels = original
+        .chunks_exact(4)
+        .zip(distorted.chunks_exact(4))
+        .flat_map(|(a, b)| {
+            let delta = |c: usize| u16::from(a[c].abs_diff(b[c])) * amplify;
+            let alpha = delta(3);
+
+            [0, 1, 2]
+                .map(|c| delta(c).saturating_add(alpha).min(255) as u8)
+                .into_iter()
+                .chain([255])
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Image::from_u8(&pixels, width, height, ColorSpace::RGBA))
+}
+
+/// Pixels of the first frame as 8-bit RGBA
+fn rgba(image: &Image) -> Result<Vec<u8>, ImageErrors> {
+    let mut image = image.clone();
+
+    image.convert_depth(BitDepth::Eight)?;
+    image.convert_color(ColorSpace::RGBA)?;
+
+    Ok(image.flatten_to_u8().swap_remove(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_changed_region() {
+        let original = Image::from_fn(32, 32, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            *px = [(x * 6) as u8, (y * 8) as u8, 128, 255];
+        });
+        let altered = Image::from_fn(32, 32, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            let change = if x < 8 && y < 8 { 4 } else { 0 };
+            *px = [(x * 6) as u8, (y * 8) as u8, 128 + change, 255];
+        });
+
+        let pixels = diff(&original, &altered, AMPLIFY)
+            .unwrap()
+            .flatten_to_u8()
+            .swap_remove(0);
+
+        for (idx, px) in pixels.chunks_exact(4).enumerate() {
+            let changed = idx % 32 < 8 && idx / 32 < 8;
+
+            assert_eq!(px[2], if changed { 32 } else { 0 });
+            assert_eq!(px[3], 255);
+        }
+
+        let smaller = Image::fill(0u8, ColorSpace::RGB, 16, 16);
+        assert!(diff(&original, &smaller, AMPLIFY).is_err());
+    }
+}
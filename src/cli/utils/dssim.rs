@@ -0,0 +1,109 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, image::Image};
+
+/// Side of the square window SSIM is computed over
+const WINDOW: usize = 8;
+/// Distance between neighbouring windows, making them overlap by half
+const STEP: usize = WINDOW / 2;
+
+const C1: f64 = (0.01 * 255.) * (0.01 * 255.);
+const C2: f64 = (0.03 * 255.) * (0.03 * 255.);
+
+/// Structural dissimilarity between two images of the same dimensions
+///
+/// Computed as `1 / SSIM - 1` over the luma of the first frame, so identical images
+/// give 0 and the value grows as the images diverge. Transparent pixels are
+/// compared as composited over black.
+pub fn dssim(original: &Image, distorted: &Image) -> Result<f64, ImageErrors> {
+    if original.dimensions() != distorted.dimensions() {
+        return Err(ImageErrors::GenericString(format!(
+            "unable to compare {:?} image with {:?} one",
+            original.dimensions(),
+            distorted.dimensions()
+        )));
+    }
+
+    let (width, height) = original.dimensions();
+    let original = luma(original)?;
+    let distorted = luma(distorted)?;
+
+    let window = WINDOW.min(width).min(height);
+    let starts = |len: usize| (0..=len - window).step_by(STEP).collect::<Vec<_>>();
+
+    let (mut total, mut count) = (0., 0);
+
+    for y in starts(height) {
+        for x in starts(width) {
+            total += ssim(&original, &distorted, width, (x, y), window);
+            count += 1;
+        }
+    }
+
+    let ssim = (total / count as f64).max(f64::EPSILON);
+
+    Ok(1. / ssim - 1.)
+}
+
+/// Luma of the first frame, with colors multiplied by alpha
+fn luma(image: &Image) -> Result<Vec<f64>, ImageErrors> {
+    let mut image = image.clone();
+
+    image.convert_depth(BitDepth::Eight)?;
+    image.convert_color(ColorSpace::RGBA)?;
+
+    Ok(image.flatten_to_u8()[0]
+        .chunks_exact(4)
+        .map(|px| {
+            let [r, g, b, a] = [px[0], px[1], px[2], px[3]].map(f64::from);
+
+            (0.2126 * r + 0.7152 * g + 0.0722 * b) * a / 255.
+        })
+        .collect())
+}
+
+/// SSIM of a single `size`×`size` window at `(x, y)`
+fn ssim(a: &[f64], b: &[f64], stride: usize, (x, y): (usize, usize), size: usize) -> f64 {
+    let pixels = || {
+        (y..y + size).flat_map(move |row| {
+            let start = row * stride + x;
+
+            a[start..start + size].iter().zip(&b[start..start + size])
+        })
+    };
+
+    let n = (size * size) as f64;
+    let (mean_a, mean_b) = pixels().fold((0., 0.), |(sa, sb), (a, b)| (sa + a, sb + b));
+    let (mean_a, mean_b) = (mean_a / n, mean_b / n);
+
+    let (var_a, var_b, covar) = pixels().fold((0., 0., 0.), |(va, vb, cv), (a, b)| {
+        let (da, db) = (a - mean_a, b - mean_b);
+
+        (va + da * da, vb + db * db, cv + da * db)
+    });
+    let (var_a, var_b, covar) = (var_a / n, var_b / n, covar / n);
+
+    ((2. * mean_a * mean_b + C1) * (2. * covar + C2))
+        / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_images() {
+        let original = Image::from_fn(32, 32, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            *px = [(x * 6) as u8, (y * 8) as u8, 128, 255];
+        });
+        let noisy = Image::from_fn(32, 32, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+            let noise = if (x + y) % 2 == 0 { 20 } else { 0 };
+            *px = [(x * 6) as u8 + noise, (y * 8) as u8, 128, 255];
+        });
+
+        assert_eq!(dssim(&original, &original).unwrap(), 0.);
+        assert!(dssim(&original, &noisy).unwrap() > 0.);
+
+        let smaller = Image::fill(0u8, ColorSpace::RGB, 16, 16);
+        assert!(dssim(&original, &smaller).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use clap::ValueEnum;
+use rimage::operations::resize::{FilterType, Resize, ResizeAlg};
+use zune_image::{errors::ImageErrors, image::Image, traits::OperationsTrait};
+
+use super::dssim::dssim;
+use crate::cli::preprocessors::ResizeFilter;
+
+/// Scores every resize filter by how close it gets to a high quality reference
+///
+/// The reference is `img` resized with Lanczos3 to twice the target dimensions.
+/// Each filter resizes `img` to `width`x`height`, the result is scaled up
+/// to the reference dimensions with Lanczos3 and compared by DSSIM,
+/// so lower scores are better.
+pub fn compare_filters(
+    img: &Image,
+    width: usize,
+    height: usize,
+) -> Result<Vec<(ResizeFilter, f64)>, ImageErrors> {
+    let lanczos3 = ResizeAlg::Convolution(FilterType::Lanczos3);
+    let resized = |img: &Image, width, height, algorithm| {
+        let mut img = img.clone();
+        Resize::new(width, height, algorithm).execute(&mut img)?;
+
+        Ok::<_, ImageErrors>(img)
+    };
+
+    let reference = resized(img, width * 2, height * 2, lanczos3)?;
+
+    ResizeFilter::value_variants()
+        .iter()
+        .filter_map(|filter| Option::<ResizeAlg>::from(*filter).map(|alg| (*filter, alg)))
+        .map(|(filter, algorithm)| {
+            let candidate = resized(img, width, height, algorithm)?;
+            let candidate = resized(&candidate, width * 2, height * 2, lanczos3)?;
+
+            Ok((filter, dssim(&reference, &candidate)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::pipeline::decode;
+
+    use super::*;
+
+    #[test]
+    fn score_per_filter() {
+        let img = decode("tests/files/webp/f1t.webp").unwrap();
+
+        let scores = compare_filters(&img, 24, 40).unwrap();
+
+        assert_eq!(
+            scores
+                .iter()
+                .map(|(f, _)| f.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "nearest",
+                "box",
+                "bilinear",
+                "hamming",
+                "catmull-rom",
+                "mitchell",
+                "lanczos3"
+            ]
+        );
+        assert!(scores.iter().all(|(_, s)| s.is_finite() && *s >= 0.));
+    }
+}
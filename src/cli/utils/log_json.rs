@@ -0,0 +1,88 @@
+use std::fmt::Write;
+
+use log::{kv::Key, Record};
+
+/// Formats a log record as a single line JSON object
+///
+/// The object has `level`, `message` and `file` fields, `file` is taken from
+/// the `file` key of the record and is `null` when the record has none.
+/// A leading `"{file}: "` is stripped from the message, as the file has its own field.
+pub fn json_line(record: &Record) -> String {
+    let file = record
+        .key_values()
+        .get(Key::from_str("file"))
+        .map(|file| file.to_string());
+    let message = record.args().to_string();
+    let message = file
+        .as_deref()
+        .and_then(|file| message.strip_prefix(file)?.strip_prefix(": "))
+        .unwrap_or(&message);
+
+    let mut line = String::from("{\"level\":");
+    push_string(&mut line, record.level().as_str());
+    line.push_str(",\"message\":");
+    push_string(&mut line, message);
+    line.push_str(",\"file\":");
+    match &file {
+        Some(file) => push_string(&mut line, file),
+        None => line.push_str("null"),
+    }
+    line.push('}');
+
+    line
+}
+
+/// Appends `value` as a quoted JSON string
+pub fn push_string(line: &mut String, value: &str) {
+    line.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => write!(line, "\\u{:04x}", c as u32).unwrap(),
+            c => line.push(c),
+        }
+    }
+
+    line.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use super::*;
+
+    #[test]
+    fn error_lines() {
+        let file = "images/\"quoted\".png";
+        let line = json_line(
+            &Record::builder()
+                .level(Level::Error)
+                .args(format_args!("{file}: invalid\tdata\n\u{1}"))
+                .key_values(&[("file", file)])
+                .build(),
+        );
+
+        assert_eq!(
+            line,
+            r#"{"level":"ERROR","message":"invalid\tdata\n\u0001","file":"images/\"quoted\".png"}"#
+        );
+
+        let line = json_line(
+            &Record::builder()
+                .level(Level::Warn)
+                .args(format_args!("C:\\images: not a file"))
+                .build(),
+        );
+
+        assert_eq!(
+            line,
+            r#"{"level":"WARN","message":"C:\\images: not a file","file":null}"#
+        );
+    }
+}
@@ -1,2 +1,17 @@
+pub mod archive;
+pub mod atomic;
+pub mod complexity;
+pub mod diff;
+pub mod dssim;
+#[cfg(feature = "resize")]
+pub mod filter_compare;
+pub mod log_json;
+pub mod overwrite;
 pub mod paths;
+pub mod pipelined;
+pub mod report;
+pub mod srcset;
+pub mod template;
 pub mod threads;
+#[cfg(all(feature = "metadata", feature = "resize"))]
+pub mod thumbnail;
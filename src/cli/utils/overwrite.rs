@@ -0,0 +1,174 @@
+use std::{
+    fs::{self, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// What to do when an output file already exists
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Leaves the existing file as is, skipping the output
+    Skip,
+    /// Replaces the existing file
+    #[default]
+    Overwrite,
+    /// Writes the output next to the existing file with a numeric suffix
+    Rename,
+}
+
+impl OverwritePolicy {
+    /// Path to write the output to, `None` if it has to be skipped
+    ///
+    /// With [`OverwritePolicy::Rename`], `image.png` becomes `image-1.png`,
+    /// or the first `image-N.png` that doesn't exist yet. The name is reserved
+    /// by creating an empty file, so parallel inputs never pick the same one.
+    pub fn resolve(self, output: &Path) -> io::Result<Option<Resolved>> {
+        match self {
+            OverwritePolicy::Skip if output.exists() => Ok(None),
+            OverwritePolicy::Skip | OverwritePolicy::Overwrite => Ok(Some(Resolved {
+                path: output.to_path_buf(),
+                reserved: false,
+            })),
+            OverwritePolicy::Rename => {
+                let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+                let extension = output
+                    .extension()
+                    .map(|ext| format!(".{}", ext.to_string_lossy()))
+                    .unwrap_or_default();
+
+                let candidates = std::iter::once(output.to_path_buf())
+                    .chain((1..).map(|n| output.with_file_name(format!("{stem}-{n}{extension}"))));
+
+                for path in candidates {
+                    match OpenOptions::new().write(true).create_new(true).open(&path) {
+                        Ok(_) => {
+                            return Ok(Some(Resolved {
+                                path,
+                                reserved: true,
+                            }))
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                unreachable!("unbounded candidates")
+            }
+        }
+    }
+}
+
+/// Output path picked by [`OverwritePolicy::resolve`]
+///
+/// Reserved paths are held by an empty file, which is removed on drop
+/// unless the output is written with [`Resolved::written`].
+#[derive(Debug)]
+pub struct Resolved {
+    path: PathBuf,
+    reserved: bool,
+}
+
+impl Resolved {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Marks the output as written, keeping the file in place
+    pub fn written(mut self) -> PathBuf {
+        self.reserved = false;
+
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl Drop for Resolved {
+    fn drop(&mut self) {
+        if self.reserved {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    fn existing_output(name: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new(name);
+
+        let output = dir.join("image.png");
+        fs::write(&output, b"previous").unwrap();
+
+        (dir, output)
+    }
+
+    fn path(resolved: io::Result<Option<Resolved>>) -> Option<PathBuf> {
+        resolved
+            .unwrap()
+            .map(|resolved| resolved.path().to_path_buf())
+    }
+
+    #[test]
+    fn skip_existing() {
+        let (dir, output) = existing_output("overwrite_skip");
+
+        assert_eq!(path(OverwritePolicy::Skip.resolve(&output)), None);
+
+        let missing = dir.join("other.png");
+        assert_eq!(
+            path(OverwritePolicy::Skip.resolve(&missing)),
+            Some(missing.clone())
+        );
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn overwrite_existing() {
+        let (_dir, output) = existing_output("overwrite_overwrite");
+
+        assert_eq!(
+            path(OverwritePolicy::Overwrite.resolve(&output)),
+            Some(output.clone())
+        );
+        assert_eq!(fs::read(&output).unwrap(), b"previous");
+    }
+
+    #[test]
+    fn rename_existing() {
+        let (dir, output) = existing_output("overwrite_rename");
+
+        let first = OverwritePolicy::Rename.resolve(&output).unwrap().unwrap();
+        let second = OverwritePolicy::Rename.resolve(&output).unwrap().unwrap();
+
+        // names are reserved until the outputs are written or dropped
+        assert_eq!(first.path(), dir.join("image-1.png"));
+        assert_eq!(second.path(), dir.join("image-2.png"));
+
+        assert_eq!(first.written(), dir.join("image-1.png"));
+        drop(second);
+
+        assert!(dir.join("image-1.png").exists());
+        assert!(!dir.join("image-2.png").exists());
+        assert_eq!(
+            path(OverwritePolicy::Rename.resolve(&output)),
+            Some(dir.join("image-2.png"))
+        );
+
+        let missing = dir.join("other.png");
+        assert_eq!(
+            path(OverwritePolicy::Rename.resolve(&missing)),
+            Some(missing.clone())
+        );
+        assert!(!missing.exists());
+
+        let no_extension = dir.join("image");
+        fs::write(&no_extension, b"previous").unwrap();
+        assert_eq!(
+            path(OverwritePolicy::Rename.resolve(&no_extension)),
+            Some(dir.join("image-1"))
+        );
+    }
+}
@@ -1,12 +1,16 @@
 use std::path::{Path, PathBuf};
 
+use glob::Pattern;
+use ignore::WalkBuilder;
 use rayon::prelude::*;
+use rimage::{walk_images_with, WalkOptions};
 
 pub fn get_paths(
     files: Vec<PathBuf>,
     out_dir: Option<PathBuf>,
     suffix: Option<String>,
     recursive: bool,
+    append_extension: bool,
 ) -> impl ParallelIterator<Item = (PathBuf, PathBuf)> {
     let common_path = if recursive {
         get_common_path(&files)
@@ -22,11 +26,6 @@ pub fn get_paths(
                 return None;
             }
 
-            let file_name = path
-                .file_stem()
-                .and_then(|f| f.to_str())
-                .unwrap_or("optimized_image");
-
             let mut out_path = match &out_dir {
                 Some(dir) => {
                     if let Some(common) = &common_path {
@@ -40,16 +39,50 @@ pub fn get_paths(
                 None => path.parent().map(|p| p.to_path_buf()).unwrap_or_default(),
             };
 
-            if let Some(s) = &suffix {
-                out_path.push(format!("{file_name}{s}"));
-            } else {
-                out_path.push(file_name);
-            }
+            out_path.push(output_name(&path, suffix.as_deref(), append_extension));
 
             Some((path, out_path))
         })
 }
 
+/// File name of the output without the new extension
+///
+/// The extension of the input is dropped, so it is replaced by the extension of the output format.
+/// With `append_extension` it is kept, `photo.jpg` becomes `photo.jpg.webp` instead of `photo.webp`.
+pub fn output_name(input: &Path, suffix: Option<&str>, append_extension: bool) -> String {
+    let mut name = input
+        .file_stem()
+        .and_then(|f| f.to_str())
+        .unwrap_or("optimized_image")
+        .to_owned();
+
+    if let Some(suffix) = suffix {
+        name.push_str(suffix);
+    }
+
+    if let Some(ext) = input.extension().and_then(|e| e.to_str()) {
+        if append_extension {
+            name.push('.');
+            name.push_str(ext);
+        }
+    }
+
+    name
+}
+
+pub fn set_extension(path: &mut PathBuf, extension: &str) {
+    let extension = extension.trim_start_matches('.');
+
+    if let Some(ext) = path.extension() {
+        let mut os_str = ext.to_os_string();
+        os_str.push(".");
+        os_str.push(extension);
+        path.set_extension(os_str);
+    } else {
+        path.set_extension(extension);
+    }
+}
+
 fn get_common_path(paths: &[PathBuf]) -> Option<PathBuf> {
     if paths.is_empty() {
         return None;
@@ -69,17 +102,119 @@ fn get_common_path(paths: &[PathBuf]) -> Option<PathBuf> {
     Some(common_path)
 }
 
+/// Files configuring which files are skipped by [`Traversal::respect_ignore`]
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Options of directory traversal
+#[derive(Debug, Clone, Default)]
+pub struct Traversal {
+    /// Maximum depth of files below an input directory, 1 keeps only its direct children
+    pub max_depth: Option<usize>,
+    /// Files and directories matching any of these patterns are skipped
+    pub exclude: Vec<Pattern>,
+    /// Skip files matched by `.gitignore` and `.ignore` files inside input directories
+    pub respect_ignore: bool,
+    /// Skip files inside input directories that aren't images decodable by enabled codecs
+    ///
+    /// Files are recognized by their signature, explicitly listed inputs are always kept.
+    pub images_only: bool,
+}
+
+impl Traversal {
+    /// Checks if the path, relative to the traversed directory, is excluded
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|pattern| {
+            pattern.matches_path(path)
+                || path
+                    .file_name()
+                    .is_some_and(|name| pattern.matches_path(Path::new(name)))
+        })
+    }
+
+    /// Lists files of the input, walking it if it's a directory
+    fn expand(&self, input: PathBuf) -> Vec<PathBuf> {
+        if !input.is_dir() {
+            if self.is_excluded(&input) {
+                return vec![];
+            }
+
+            return vec![input];
+        }
+
+        if !self.respect_ignore {
+            return self.walk(&input);
+        }
+
+        let files = self.walk_ignoring(&input);
+
+        if !self.images_only {
+            return files;
+        }
+
+        files
+            .into_iter()
+            .filter(|path| {
+                let image = rimage::can_decode(path);
+
+                if !image {
+                    log::debug!("{}: not an image, skipping", path.display());
+                }
+
+                image
+            })
+            .collect()
+    }
+
+    fn walk(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut options = WalkOptions::new().with_images_only(self.images_only);
+
+        if let Some(depth) = self.max_depth {
+            options = options.with_max_depth(depth);
+        }
+
+        walk_images_with(dir, options, |relative| !self.is_excluded(relative)).collect()
+    }
+
+    /// Walks the directory skipping files matched by `.gitignore` and `.ignore` files
+    fn walk_ignoring(&self, dir: &Path) -> Vec<PathBuf> {
+        let traversal = self.clone();
+        let root = dir.to_path_buf();
+
+        WalkBuilder::new(dir)
+            .max_depth(self.max_depth)
+            .hidden(false)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+
+                entry.depth() == 0 || !traversal.is_excluded(relative)
+            })
+            .build()
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    log::warn!("{e}");
+                    None
+                }
+            })
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .filter(|entry| !IGNORE_FILES.iter().any(|name| entry.file_name() == *name))
+            .map(|entry| entry.into_path())
+            .collect()
+    }
+}
+
+/// Lists input files, walking input directories
 #[inline]
-pub fn collect_files<P: AsRef<Path>>(input: &[P]) -> Vec<PathBuf> {
+pub fn collect_files<P: AsRef<Path>>(input: &[P], traversal: &Traversal) -> Vec<PathBuf> {
     #[cfg(windows)]
-    {
-        input.iter().flat_map(apply_glob_pattern).collect()
-    }
+    let paths = input.iter().flat_map(apply_glob_pattern);
 
     #[cfg(not(windows))]
-    {
-        input.iter().map(|p| PathBuf::from(p.as_ref())).collect()
-    }
+    let paths = input.iter().map(|p| PathBuf::from(p.as_ref()));
+
+    paths.flat_map(|path| traversal.expand(path)).collect()
 }
 
 #[cfg(windows)]
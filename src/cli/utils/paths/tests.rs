@@ -1,4 +1,5 @@
 use super::*;
+use crate::test_utils::TempDir;
 
 #[test]
 fn find_common_path() {
@@ -29,3 +30,175 @@ fn find_common_path() {
     let common_path = get_common_path(&paths);
     assert_eq!(common_path, Some(PathBuf::from("/path")));
 }
+
+#[test]
+fn replace_or_append_extension() {
+    let input = Path::new("/path/to/photo.jpg");
+
+    let mut replaced = PathBuf::from(output_name(input, None, false));
+    set_extension(&mut replaced, "webp");
+    assert_eq!(replaced, PathBuf::from("photo.webp"));
+
+    let mut appended = PathBuf::from(output_name(input, None, true));
+    set_extension(&mut appended, "webp");
+    assert_eq!(appended, PathBuf::from("photo.jpg.webp"));
+
+    let mut suffixed = PathBuf::from(output_name(input, Some("@2x"), true));
+    set_extension(&mut suffixed, "webp");
+    assert_eq!(suffixed, PathBuf::from("photo@2x.jpg.webp"));
+
+    assert_eq!(output_name(Path::new("photo"), None, true), "photo");
+}
+
+#[test]
+fn apply_extension() {
+    let mut path = PathBuf::from("/path/to/image");
+    set_extension(&mut path, "jpeg");
+    assert_eq!(path, PathBuf::from("/path/to/image.jpeg"));
+
+    let mut path = PathBuf::from("/path/to/image");
+    set_extension(&mut path, ".JPG");
+    assert_eq!(path, PathBuf::from("/path/to/image.JPG"));
+
+    let mut path = PathBuf::from("/path/to/image.tar");
+    set_extension(&mut path, "png");
+    assert_eq!(path, PathBuf::from("/path/to/image.tar.png"));
+}
+
+#[test]
+fn traverse_directories() {
+    let dir = TempDir::new("traverse_directories");
+    let root = dir.path().to_path_buf();
+
+    for file in [
+        "a.png",
+        "skip.gif",
+        "one/b.png",
+        "one/two/c.png",
+        "drafts/d.png",
+    ] {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, []).unwrap();
+    }
+
+    let relative = |traversal: &Traversal| {
+        collect_files(&[&root], traversal)
+            .into_iter()
+            .map(|p| p.strip_prefix(&root).unwrap().to_path_buf())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        relative(&Traversal::default()),
+        [
+            "a.png",
+            "drafts/d.png",
+            "one/b.png",
+            "one/two/c.png",
+            "skip.gif"
+        ]
+        .map(PathBuf::from)
+    );
+
+    let shallow = Traversal {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(
+        relative(&shallow),
+        ["a.png", "drafts/d.png", "one/b.png", "skip.gif"].map(PathBuf::from)
+    );
+
+    let excluded = Traversal {
+        exclude: vec![
+            Pattern::new("*.gif").unwrap(),
+            Pattern::new("drafts").unwrap(),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(
+        relative(&excluded),
+        ["a.png", "one/b.png", "one/two/c.png"].map(PathBuf::from)
+    );
+
+    assert!(collect_files(&[root.join("skip.gif")], &excluded).is_empty());
+}
+
+#[test]
+fn respect_ignore_files() {
+    let dir = TempDir::new("respect_ignore_files");
+    let root = dir.path().to_path_buf();
+
+    for file in [
+        "kept.png",
+        "ignored.png",
+        "build/out.png",
+        "nested/kept.png",
+        "nested/secret.png",
+    ] {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, []).unwrap();
+    }
+
+    std::fs::write(root.join(".gitignore"), "ignored.png\nbuild/\n").unwrap();
+    std::fs::write(root.join("nested/.ignore"), "secret.png\n").unwrap();
+
+    let relative = |traversal: &Traversal| {
+        collect_files(&[&root], traversal)
+            .into_iter()
+            .map(|p| p.strip_prefix(&root).unwrap().to_path_buf())
+            .collect::<Vec<_>>()
+    };
+
+    let traversal = Traversal {
+        respect_ignore: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        relative(&traversal),
+        ["kept.png", "nested/kept.png"].map(PathBuf::from)
+    );
+
+    assert_eq!(relative(&Traversal::default()).len(), 7);
+}
+
+#[test]
+fn skip_non_images() {
+    let dir = TempDir::new("skip_non_images");
+    let root = dir.path().to_path_buf();
+    std::fs::create_dir_all(root.join("nested")).unwrap();
+
+    std::fs::copy("tests/files/png/f1t.png", root.join("a.png")).unwrap();
+    std::fs::copy("tests/files/jpg/f1t.jpg", root.join("nested/b.jpg")).unwrap();
+    std::fs::write(root.join("notes.txt"), "not an image").unwrap();
+    std::fs::write(root.join("fake.png"), "not an image either").unwrap();
+
+    let relative = |files: Vec<PathBuf>| {
+        files
+            .into_iter()
+            .map(|p| p.strip_prefix(&root).unwrap().to_path_buf())
+            .collect::<Vec<_>>()
+    };
+
+    let traversal = Traversal {
+        images_only: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        relative(collect_files(&[&root], &traversal)),
+        ["a.png", "nested/b.jpg"].map(PathBuf::from)
+    );
+
+    assert_eq!(
+        relative(collect_files(&[&root], &Traversal::default())).len(),
+        4
+    );
+
+    // explicitly listed files are left for the decoder to report
+    assert_eq!(
+        collect_files(&[root.join("notes.txt")], &traversal),
+        [root.join("notes.txt")]
+    );
+}
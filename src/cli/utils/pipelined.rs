@@ -0,0 +1,148 @@
+use std::{sync::mpsc, thread};
+
+use rayon::prelude::*;
+
+/// Runs `produce` over `items` on a dedicated thread, feeding its results to `consume`
+///
+/// Results are passed through a channel bounded by `capacity`, so producing
+/// next items overlaps with consuming previous ones without buffering the whole batch.
+/// Items are consumed concurrently on the rayon thread pool.
+pub fn pipelined<I, T, P, C>(items: I, capacity: usize, produce: P, consume: C)
+where
+    I: IntoIterator + Send,
+    T: Send,
+    P: Fn(I::Item) -> Option<T> + Send,
+    C: Fn(T) + Send + Sync,
+{
+    let (tx, rx) = mpsc::sync_channel(capacity);
+
+    thread::scope(|s| {
+        s.spawn(move || {
+            for value in items.into_iter().filter_map(produce) {
+                if tx.send(value).is_err() {
+                    break;
+                }
+            }
+        });
+
+        rx.into_iter().par_bridge().for_each(consume);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    use zune_core::colorspace::ColorSpace;
+    use zune_image::image::Image;
+
+    use super::*;
+    use crate::cli::pipeline::{decode_encoded, default_encoder};
+
+    #[test]
+    fn same_results() {
+        let results = Mutex::new(vec![]);
+
+        pipelined(
+            0..100,
+            4,
+            |i| (i % 3 != 0).then_some(i * 2),
+            |i| results.lock().unwrap().push(i + 1),
+        );
+
+        let mut results = results.into_inner().unwrap();
+        results.sort();
+
+        let expected = (0..100)
+            .filter(|i| i % 3 != 0)
+            .map(|i| i * 2 + 1)
+            .collect::<Vec<_>>();
+
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn overlaps_stages() {
+        let (started_tx, started_rx) = mpsc::channel();
+        let started_rx = Mutex::new(started_rx);
+        let overlapped = AtomicBool::new(false);
+
+        pipelined(
+            0..4,
+            1,
+            |i| {
+                started_tx.send(i).unwrap();
+                Some(i)
+            },
+            |i| {
+                if i != 0 {
+                    return;
+                }
+
+                // producing of the next item starts while the first one is consumed,
+                // the timeout only keeps sequential stages from hanging forever
+                let started_rx = started_rx.lock().unwrap();
+                let next_started = (0..2)
+                    .map_while(|_| started_rx.recv_timeout(Duration::from_secs(10)).ok())
+                    .any(|started| started == 1);
+
+                overlapped.store(next_started, Ordering::Relaxed);
+            },
+        );
+
+        assert!(overlapped.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn same_encoded_outputs() {
+        let mut png = default_encoder("png").unwrap();
+
+        let inputs = (0..6_u8)
+            .map(|idx| {
+                let img = Image::from_fn(24, 16, ColorSpace::RGB, |x, y, px: &mut [u8; 4]| {
+                    px[0] = (x * 10) as u8;
+                    px[1] = (y * 15) as u8;
+                    px[2] = idx * 40;
+                });
+
+                let mut buf = vec![];
+                png.encode(&img, &mut buf).unwrap();
+                (idx, buf)
+            })
+            .collect::<Vec<_>>();
+
+        let transcode = |img: &Image| {
+            let mut buf = vec![];
+            default_encoder("jpeg")
+                .unwrap()
+                .encode(img, &mut buf)
+                .unwrap();
+            buf
+        };
+
+        let expected = inputs
+            .iter()
+            .map(|(idx, buf)| (*idx, transcode(&decode_encoded(buf, &png).unwrap())))
+            .collect::<Vec<_>>();
+
+        let results = Mutex::new(vec![]);
+
+        pipelined(
+            &inputs,
+            2,
+            |(idx, buf)| Some((*idx, decode_encoded(buf, &png).unwrap())),
+            |(idx, img)| results.lock().unwrap().push((idx, transcode(&img))),
+        );
+
+        let mut results = results.into_inner().unwrap();
+        results.sort();
+
+        assert_eq!(results, expected);
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use console::style;
+use indicatif::DecimalBytes;
+
+use super::log_json::push_string;
+
+pub struct Report {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub codec: String,
+    pub input_size: u64,
+    pub output_size: u64,
+    pub dimensions: (usize, usize),
+    pub duration: Duration,
+}
+
+impl Report {
+    pub fn ratio(&self) -> f64 {
+        self.output_size as f64 / self.input_size as f64
+    }
+}
+
+pub fn write_csv<W: Write>(mut writer: W, reports: &[Report]) -> io::Result<()> {
+    writeln!(
+        writer,
+        "input,output,input_size,output_size,ratio,width,height,time_ms"
+    )?;
+
+    for report in reports {
+        writeln!(
+            writer,
+            "{},{},{},{},{:.4},{},{},{}",
+            escape(&report.input.display().to_string()),
+            escape(&report.output.display().to_string()),
+            report.input_size,
+            report.output_size,
+            report.ratio(),
+            report.dimensions.0,
+            report.dimensions.1,
+            report.duration.as_millis(),
+        )?;
+    }
+
+    writer.flush()
+}
+
+/// Aggregated sizes of a group of reports
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Aggregate {
+    count: usize,
+    input_size: u64,
+    output_size: u64,
+    ratio_sum: f64,
+}
+
+impl Aggregate {
+    fn add(&mut self, report: &Report) {
+        self.count += 1;
+        self.input_size += report.input_size;
+        self.output_size += report.output_size;
+        self.ratio_sum += report.ratio();
+    }
+
+    fn push_fields(&self, json: &mut String) {
+        json.push_str(&format!(
+            "\"count\":{},\"inputSize\":{},\"outputSize\":{},\"averageRatio\":{:.4}",
+            self.count,
+            self.input_size,
+            self.output_size,
+            self.ratio_sum / self.count.max(1) as f64,
+        ));
+    }
+}
+
+/// Writes a JSON summary of the batch, with totals and aggregates of every output codec
+///
+/// Totals and each entry of `byFormat` have `count`, `inputSize`, `outputSize`
+/// and `averageRatio` fields, the ratio is averaged over files.
+pub fn write_summary_json<W: Write>(mut writer: W, reports: &[Report]) -> io::Result<()> {
+    let mut total = Aggregate::default();
+    let mut by_format = BTreeMap::<&str, Aggregate>::new();
+
+    for report in reports {
+        total.add(report);
+        by_format.entry(&report.codec).or_default().add(report);
+    }
+
+    let mut json = String::from("{");
+    total.push_fields(&mut json);
+    json.push_str(",\"byFormat\":{");
+
+    for (idx, (codec, aggregate)) in by_format.iter().enumerate() {
+        if idx > 0 {
+            json.push(',');
+        }
+
+        push_string(&mut json, codec);
+        json.push_str(":{");
+        aggregate.push_fields(&mut json);
+        json.push('}');
+    }
+
+    json.push_str("}}");
+
+    writeln!(writer, "{json}")?;
+    writer.flush()
+}
+
+/// Writes the terminal summary of the batch, listing every output when there are several
+pub fn write_summary<W: Write>(mut writer: W, reports: &[Report]) -> io::Result<()> {
+    let percent = |ratio: f64| {
+        let difference = format!("{:.2}%", ratio * 100.0 - 100.0);
+
+        if ratio > 1.0 {
+            style(difference).red()
+        } else {
+            style(difference).green()
+        }
+    };
+
+    if reports.len() > 1 {
+        let path_width = reports
+            .iter()
+            .map(|r| r.output.display().to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        writeln!(
+            writer,
+            "{:<path_width$} {}",
+            style("File").bold(),
+            style("Size").bold(),
+        )?;
+
+        for report in reports {
+            writeln!(
+                writer,
+                "{:<path_width$} {} > {} {}",
+                report.output.display(),
+                style(DecimalBytes(report.input_size)).blue(),
+                style(DecimalBytes(report.output_size)).blue(),
+                percent(report.ratio()),
+            )?;
+        }
+    }
+
+    let total_input_size = reports.iter().map(|r| r.input_size).sum::<u64>();
+    let total_output_size = reports.iter().map(|r| r.output_size).sum::<u64>();
+
+    writeln!(
+        writer,
+        "Total: {} > {} {}",
+        style(DecimalBytes(total_input_size)).blue(),
+        style(DecimalBytes(total_output_size)).blue(),
+        percent(total_output_size as f64 / total_input_size as f64),
+    )?;
+    writer.flush()
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_rows() {
+        let reports = [
+            Report {
+                input: PathBuf::from("a.png"),
+                output: PathBuf::from("a.jpg"),
+                codec: "mozjpeg".to_string(),
+                input_size: 200,
+                output_size: 100,
+                dimensions: (10, 20),
+                duration: Duration::from_millis(15),
+            },
+            Report {
+                input: PathBuf::from("b,c.png"),
+                output: PathBuf::from("b,c.jpg"),
+                codec: "mozjpeg".to_string(),
+                input_size: 100,
+                output_size: 100,
+                dimensions: (30, 40),
+                duration: Duration::from_millis(5),
+            },
+        ];
+
+        let mut buf = vec![];
+        write_csv(&mut buf, &reports).unwrap();
+
+        let csv = String::from_utf8(buf).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "input,output,input_size,output_size,ratio,width,height,time_ms"
+        );
+        assert_eq!(lines[1], "a.png,a.jpg,200,100,0.5000,10,20,15");
+        assert_eq!(lines[2], "\"b,c.png\",\"b,c.jpg\",100,100,1.0000,30,40,5");
+    }
+
+    #[test]
+    fn summary_by_format() {
+        let report = |name: &str, codec: &str, input_size, output_size| Report {
+            input: PathBuf::from(name),
+            output: PathBuf::from(name),
+            codec: codec.to_string(),
+            input_size,
+            output_size,
+            dimensions: (10, 10),
+            duration: Duration::from_millis(1),
+        };
+        let reports = [
+            report("a.jpg", "mozjpeg", 400, 100),
+            report("b.png", "oxipng", 300, 300),
+            report("c.jpg", "mozjpeg", 100, 50),
+            report("d.webp", "webp", 1000, 200),
+        ];
+
+        let mut buf = vec![];
+        write_summary_json(&mut buf, &reports).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            concat!(
+                r#"{"count":4,"inputSize":1800,"outputSize":650,"averageRatio":0.4875,"byFormat":{"#,
+                r#""mozjpeg":{"count":2,"inputSize":500,"outputSize":150,"averageRatio":0.3750},"#,
+                r#""oxipng":{"count":1,"inputSize":300,"outputSize":300,"averageRatio":1.0000},"#,
+                r#""webp":{"count":1,"inputSize":1000,"outputSize":200,"averageRatio":0.2000}}}"#,
+                "\n"
+            )
+        );
+    }
+}
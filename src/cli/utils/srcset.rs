@@ -0,0 +1,192 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+#[cfg(feature = "resize")]
+use clap::ArgMatches;
+
+use crate::cli::pipeline::has_quality;
+
+/// Single image of a responsive set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SrcsetEntry {
+    /// Width the image is resized to, keeping the aspect ratio
+    pub width: usize,
+    /// Quality the image is encoded with, codec default if not set
+    pub quality: Option<u8>,
+}
+
+impl SrcsetEntry {
+    /// Name template of the output, `{stem}-480w.{ext}`
+    pub fn name_template(&self) -> String {
+        format!("{{stem}}-{}w.{{ext}}", self.width)
+    }
+
+    /// Resize operation of the entry, images narrower than the entry keep their width
+    #[cfg(feature = "resize")]
+    pub fn resize(&self, matches: &ArgMatches) -> rimage::operations::resize::Resize {
+        use rimage::operations::resize::ResizeFit;
+
+        crate::cli::pipeline::resize_operation(matches, self.width, usize::MAX)
+            .with_fit(ResizeFit::Shrink)
+    }
+
+    /// Quality of the entry for `codec`, codecs without a quality option have none
+    pub fn quality(&self, codec: &str) -> Option<u8> {
+        self.quality.filter(|_| has_quality(codec))
+    }
+}
+
+/// Widths and qualities of a responsive set, like `480:75,800:80,1200:85`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Srcset(pub Vec<SrcsetEntry>);
+
+impl FromStr for Srcset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let entries = s
+            .split(',')
+            .map(|entry| {
+                let (width, quality) = match entry.trim().split_once(':') {
+                    Some((width, quality)) => (width, Some(quality)),
+                    None => (entry.trim(), None),
+                };
+
+                let width = width
+                    .trim()
+                    .trim_end_matches('w')
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .ok_or_else(|| anyhow!("invalid width `{width}` in `{entry}`"))?;
+
+                let quality = quality
+                    .map(|quality| {
+                        quality
+                            .trim()
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|quality| (1..=100).contains(quality))
+                            .ok_or_else(|| anyhow!("invalid quality `{quality}` in `{entry}`"))
+                    })
+                    .transpose()?;
+
+                Ok(SrcsetEntry { width, quality })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut widths = entries.iter().map(|entry| entry.width).collect::<Vec<_>>();
+        widths.sort_unstable();
+        widths.dedup();
+
+        if widths.len() != entries.len() {
+            return Err(anyhow!("widths of `{s}` must be unique"));
+        }
+
+        Ok(Self(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_srcset() {
+        assert_eq!(
+            "480:75, 800:80,1200w:85".parse::<Srcset>().unwrap(),
+            Srcset(vec![
+                SrcsetEntry {
+                    width: 480,
+                    quality: Some(75)
+                },
+                SrcsetEntry {
+                    width: 800,
+                    quality: Some(80)
+                },
+                SrcsetEntry {
+                    width: 1200,
+                    quality: Some(85)
+                },
+            ])
+        );
+        assert_eq!(
+            "640".parse::<Srcset>().unwrap(),
+            Srcset(vec![SrcsetEntry {
+                width: 640,
+                quality: None
+            }])
+        );
+
+        assert!("".parse::<Srcset>().is_err());
+        assert!("0:75".parse::<Srcset>().is_err());
+        assert!("480:101".parse::<Srcset>().is_err());
+        assert!("480:75,480:80".parse::<Srcset>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "resize")]
+    fn srcset_outputs() {
+        use zune_core::colorspace::ColorSpace;
+        use zune_image::{image::Image, traits::OperationsTrait};
+
+        use crate::cli::{
+            cli,
+            pipeline::encoder_with_quality,
+            utils::template::{expand_template, NameValues},
+        };
+
+        let matches = cli()
+            .try_get_matches_from([
+                "rimage",
+                "mozjpeg",
+                "--srcset",
+                "480:75,800:80,1200:85",
+                "photo.png",
+            ])
+            .unwrap();
+        let (codec, matches) = matches.subcommand().unwrap();
+        let Srcset(entries) = matches.get_one::<Srcset>("srcset").unwrap();
+
+        // narrower than the widest entry, which keeps the source width
+        let img = Image::fill(128_u8, ColorSpace::RGBA, 1000, 625);
+
+        let outputs = entries
+            .iter()
+            .map(|entry| {
+                let mut img = img.clone();
+                entry.resize(matches).execute(&mut img).unwrap();
+
+                let (width, height) = img.dimensions();
+                let quality = entry.quality(codec);
+                let encoder = encoder_with_quality(codec, Some(matches), quality.unwrap()).unwrap();
+                let values = NameValues {
+                    stem: "photo",
+                    width,
+                    height,
+                    codec,
+                    extension: encoder.to_extension(),
+                    quality,
+                };
+
+                (
+                    expand_template(&entry.name_template(), &values).unwrap(),
+                    (width, height),
+                    quality,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            outputs,
+            [
+                ("photo-480w.jpg".to_string(), (480, 300), Some(75)),
+                ("photo-800w.jpg".to_string(), (800, 500), Some(80)),
+                ("photo-1200w.jpg".to_string(), (1000, 625), Some(85)),
+            ]
+        );
+
+        // codecs without a quality option ignore qualities of entries
+        assert_eq!(entries[0].quality("oxipng"), None);
+    }
+}
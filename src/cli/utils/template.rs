@@ -0,0 +1,106 @@
+use anyhow::anyhow;
+
+/// Values of the placeholders in an output name template
+#[derive(Debug, Clone, Copy)]
+pub struct NameValues<'a> {
+    /// File name of the input without extension
+    pub stem: &'a str,
+    /// Width of the output image
+    pub width: usize,
+    /// Height of the output image
+    pub height: usize,
+    /// Codec the image is encoded with
+    pub codec: &'a str,
+    /// Extension of the output file, without the leading dot
+    pub extension: &'a str,
+    /// Quality of the codec, if it has one
+    pub quality: Option<u8>,
+}
+
+/// Expands placeholders of the output name template
+///
+/// Supported placeholders are `{stem}`, `{width}`, `{height}`, `{codec}`, `{ext}`
+/// and `{quality}`, `{{` and `}}` are literal braces.
+pub fn expand_template(template: &str, values: &NameValues) -> anyhow::Result<String> {
+    let mut name = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(idx) = rest.find(['{', '}']) {
+        name.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        if let Some(escaped) = rest.strip_prefix("{{").or_else(|| rest.strip_prefix("}}")) {
+            name.push_str(&rest[..1]);
+            rest = escaped;
+            continue;
+        }
+
+        let end = rest
+            .find('}')
+            .filter(|_| rest.starts_with('{'))
+            .ok_or_else(|| anyhow!("unmatched brace in name template `{template}`"))?;
+
+        match &rest[1..end] {
+            "stem" => name.push_str(values.stem),
+            "width" => name.push_str(&values.width.to_string()),
+            "height" => name.push_str(&values.height.to_string()),
+            "codec" => name.push_str(values.codec),
+            "ext" => name.push_str(values.extension),
+            "quality" => match values.quality {
+                Some(quality) => name.push_str(&quality.to_string()),
+                None => return Err(anyhow!("{} has no quality option", values.codec)),
+            },
+            placeholder => return Err(anyhow!("unknown placeholder `{{{placeholder}}}`")),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    name.push_str(rest);
+
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: NameValues = NameValues {
+        stem: "photo",
+        width: 640,
+        height: 480,
+        codec: "mozjpeg",
+        extension: "jpg",
+        quality: Some(75),
+    };
+
+    #[test]
+    fn expand_placeholders() {
+        assert_eq!(
+            expand_template("{stem}-{width}x{height}.{codec}", &VALUES).unwrap(),
+            "photo-640x480.mozjpeg"
+        );
+        assert_eq!(
+            expand_template("{stem}_q{quality}.{ext}", &VALUES).unwrap(),
+            "photo_q75.jpg"
+        );
+        assert_eq!(
+            expand_template("{{{stem}}}.{ext}", &VALUES).unwrap(),
+            "{photo}.jpg"
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!(expand_template("{name}.{ext}", &VALUES).is_err());
+        assert!(expand_template("{stem.{ext}", &VALUES).is_err());
+        assert!(expand_template("stem}.{ext}", &VALUES).is_err());
+
+        let values = NameValues {
+            codec: "png",
+            quality: None,
+            ..VALUES
+        };
+        assert!(expand_template("{stem}_q{quality}", &values).is_err());
+    }
+}
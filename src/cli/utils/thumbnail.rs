@@ -0,0 +1,65 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
+
+use exif::{In, Tag};
+
+/// JPEG thumbnail embedded into EXIF metadata of the file, if it has one
+///
+/// Files without EXIF, in containers the EXIF reader doesn't support or with
+/// malformed EXIF are treated as having no thumbnail.
+pub fn exif_thumbnail(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let exif = match exif::Reader::new().read_from_container(&mut reader) {
+        Ok(exif) => exif,
+        Err(exif::Error::Io(e)) => return Err(e),
+        Err(_) => return Ok(None),
+    };
+
+    let field = |tag| {
+        exif.get_field(tag, In::THUMBNAIL)
+            .and_then(|field| field.value.get_uint(0))
+            .map(|value| value as usize)
+    };
+
+    let (Some(offset), Some(length)) = (
+        field(Tag::JPEGInterchangeFormat),
+        field(Tag::JPEGInterchangeFormatLength),
+    ) else {
+        return Ok(None);
+    };
+
+    Ok(exif
+        .buf()
+        .get(offset..offset.saturating_add(length))
+        .filter(|thumbnail| thumbnail.starts_with(&[0xFF, 0xD8]))
+        .map(<[u8]>::to_vec))
+}
+
+#[cfg(test)]
+mod tests {
+    use zune_core::{bytestream::ZCursor, options::DecoderOptions};
+    use zune_image::image::Image;
+
+    use super::*;
+
+    #[test]
+    fn extract_thumbnail() {
+        let thumbnail = exif_thumbnail(Path::new("tests/files/exif/thumbnail.jpg"))
+            .unwrap()
+            .unwrap();
+
+        let image = Image::read(ZCursor::new(&thumbnail), DecoderOptions::default()).unwrap();
+        assert!(image.dimensions().0 > 0 && image.dimensions().1 > 0);
+
+        assert!(exif_thumbnail(Path::new("tests/files/exif/f1t.jpg"))
+            .unwrap()
+            .is_none());
+        assert!(exif_thumbnail(Path::new("tests/files/png/f1t.png"))
+            .unwrap()
+            .is_none());
+    }
+}
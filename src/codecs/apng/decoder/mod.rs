@@ -0,0 +1,199 @@
+use std::io::Read;
+
+use png::{BlendOp, ColorType, DisposeOp, Transformations};
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
+
+/// An APNG decoder
+///
+/// Frames are composited onto the full canvas honoring their blend and disposal
+/// operations, each frame keeps its delay in milliseconds.
+pub struct ApngDecoder<R: Read> {
+    inner: png::Reader<R>,
+    delays: Vec<u16>,
+}
+
+impl<R: Read> ApngDecoder<R> {
+    /// Create a new apng decoder that reads data from `source`
+    pub fn try_new(source: R) -> Result<ApngDecoder<R>, ImageErrors> {
+        let mut decoder = png::Decoder::new(source);
+        decoder.set_transformations(Transformations::normalize_to_color8());
+
+        let inner = decoder.read_info().map_err(|e| {
+            ImageErrors::ImageDecodeErrors(format!("Unable to create APNG decoder: {e}"))
+        })?;
+
+        Ok(ApngDecoder {
+            inner,
+            delays: vec![],
+        })
+    }
+
+    /// Checks if the image has animation frames
+    pub fn is_animated(&self) -> bool {
+        self.inner.info().animation_control().is_some()
+    }
+
+    /// Delays of decoded frames in milliseconds
+    ///
+    /// Filled by [`DecoderTrait::decode`], since zune-image doesn't expose
+    /// delays stored in frames.
+    pub fn delays(&self) -> &[u16] {
+        &self.delays
+    }
+}
+
+/// Converts `fcTL` delay fraction into milliseconds
+///
+/// Zero denominator is treated as 100, so numerator is in hundredths of a second.
+fn delay_ms(num: u16, den: u16) -> usize {
+    let den = if den == 0 { 100 } else { den as usize };
+
+    (num as usize * 1000 + den / 2) / den
+}
+
+/// Expands row of `color_type` pixels into RGBA
+fn to_rgba(row: &[u8], color_type: ColorType) -> Vec<[u8; 4]> {
+    match color_type {
+        ColorType::Grayscale => row.iter().map(|&l| [l, l, l, 255]).collect(),
+        ColorType::GrayscaleAlpha => row
+            .chunks_exact(2)
+            .map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        ColorType::Rgb => row
+            .chunks_exact(3)
+            .map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        ColorType::Rgba | ColorType::Indexed => row
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect(),
+    }
+}
+
+/// Blends `src` over `dst`, both with straight alpha
+fn blend_over(dst: &mut [u8], src: [u8; 4]) {
+    let src_a = src[3] as u32;
+    let dst_a = dst[3] as u32 * (255 - src_a) / 255;
+    let out_a = src_a + dst_a;
+
+    if out_a == 0 {
+        dst.fill(0);
+        return;
+    }
+
+    for c in 0..3 {
+        dst[c] = ((src[c] as u32 * src_a + dst[c] as u32 * dst_a + out_a / 2) / out_a) as u8;
+    }
+    dst[3] = out_a as u8;
+}
+
+impl<R> DecoderTrait for ApngDecoder<R>
+where
+    R: Read,
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let (width, height) = <ApngDecoder<R> as DecoderTrait>::dimensions(self).unwrap();
+        let map_err = |e: png::DecodingError| {
+            ImageErrors::ImageDecodeErrors(format!("Unable to read frame - {e}"))
+        };
+
+        let num_frames = self
+            .inner
+            .info()
+            .animation_control()
+            .map_or(1, |actl| actl.num_frames);
+
+        // default image isn't a part of the animation without preceding fcTL
+        if self.is_animated() && self.inner.info().frame_control().is_none() {
+            self.inner.next_frame_info().map_err(map_err)?;
+        }
+
+        let mut canvas = vec![0; width * height * 4];
+        let mut buf = vec![0; self.inner.output_buffer_size()];
+        let mut frames = vec![];
+        self.delays.clear();
+
+        for idx in 0..num_frames {
+            let output = self.inner.next_frame(&mut buf).map_err(map_err)?;
+            let control = self
+                .inner
+                .info()
+                .frame_control()
+                .copied()
+                .unwrap_or_default();
+
+            let (left, top) = (control.x_offset as usize, control.y_offset as usize);
+            let frame_width = (output.width as usize).min(width.saturating_sub(left));
+            let frame_height = (output.height as usize).min(height.saturating_sub(top));
+
+            let dispose = match control.dispose_op {
+                // the first frame has nothing to revert to
+                DisposeOp::Previous if idx == 0 => DisposeOp::Background,
+                dispose => dispose,
+            };
+            let previous = (dispose == DisposeOp::Previous).then(|| canvas.clone());
+
+            buf.chunks_exact(output.line_size)
+                .take(frame_height)
+                .enumerate()
+                .for_each(|(y, row)| {
+                    let offset = ((top + y) * width + left) * 4;
+
+                    canvas[offset..offset + frame_width * 4]
+                        .chunks_exact_mut(4)
+                        .zip(to_rgba(row, output.color_type))
+                        .for_each(|(dst, src)| match control.blend_op {
+                            BlendOp::Source => dst.copy_from_slice(&src),
+                            BlendOp::Over => blend_over(dst, src),
+                        });
+                });
+
+            let delay = delay_ms(control.delay_num, control.delay_den);
+
+            frames.push(Frame::from_u8(&canvas, ColorSpace::RGBA, delay, 1000));
+            self.delays.push(u16::try_from(delay).unwrap_or(u16::MAX));
+
+            match dispose {
+                DisposeOp::Background => {
+                    canvas
+                        .chunks_exact_mut(width * 4)
+                        .skip(top)
+                        .take(frame_height)
+                        .for_each(|row| row[left * 4..(left + frame_width) * 4].fill(0));
+                }
+                DisposeOp::Previous => {
+                    if let Some(previous) = previous {
+                        canvas = previous;
+                    }
+                }
+                DisposeOp::None => {}
+            }
+        }
+
+        Ok(Image::new_frames(
+            frames,
+            BitDepth::Eight,
+            width,
+            height,
+            ColorSpace::RGBA,
+        ))
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        let info = self.inner.info();
+
+        Some((info.width as usize, info.height as usize))
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        ColorSpace::RGBA
+    }
+
+    fn name(&self) -> &'static str {
+        "apng"
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,127 @@
+use super::*;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 128];
+
+struct TestFrame {
+    position: (u32, u32),
+    size: (u32, u32),
+    pixel: [u8; 4],
+    delay: (u16, u16),
+    blend: BlendOp,
+    dispose: DisposeOp,
+}
+
+fn encode_apng(frames: &[TestFrame]) -> Vec<u8> {
+    let mut buf = vec![];
+
+    let mut encoder = png::Encoder::new(&mut buf, 4, 4);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_animated(frames.len() as u32, 0).unwrap();
+
+    let mut writer = encoder.write_header().unwrap();
+
+    for frame in frames {
+        writer
+            .set_frame_dimension(frame.size.0, frame.size.1)
+            .unwrap();
+        writer
+            .set_frame_position(frame.position.0, frame.position.1)
+            .unwrap();
+        writer
+            .set_frame_delay(frame.delay.0, frame.delay.1)
+            .unwrap();
+        writer.set_blend_op(frame.blend).unwrap();
+        writer.set_dispose_op(frame.dispose).unwrap();
+        writer
+            .write_image_data(&frame.pixel.repeat((frame.size.0 * frame.size.1) as usize))
+            .unwrap();
+    }
+
+    writer.finish().unwrap();
+
+    buf
+}
+
+#[test]
+fn decode_durations_and_disposal() {
+    let apng = encode_apng(&[
+        TestFrame {
+            position: (0, 0),
+            size: (4, 4),
+            pixel: RED,
+            delay: (1, 10),
+            blend: BlendOp::Source,
+            dispose: DisposeOp::None,
+        },
+        TestFrame {
+            position: (1, 1),
+            size: (2, 2),
+            pixel: BLUE,
+            delay: (1, 3),
+            blend: BlendOp::Over,
+            dispose: DisposeOp::Previous,
+        },
+        TestFrame {
+            position: (0, 0),
+            size: (1, 1),
+            pixel: GREEN,
+            delay: (7, 0),
+            blend: BlendOp::Source,
+            dispose: DisposeOp::None,
+        },
+    ]);
+
+    let decoder = ApngDecoder::try_new(apng.as_slice()).unwrap();
+    assert!(decoder.is_animated());
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (4, 4));
+    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+
+    let canvas = |f: &dyn Fn(usize, usize) -> [u8; 4]| {
+        (0..4)
+            .flat_map(|y| (0..4).flat_map(move |x| f(x, y)))
+            .collect::<Vec<u8>>()
+    };
+
+    let inside = |x: usize, y: usize| (1..3).contains(&x) && (1..3).contains(&y);
+
+    let expected = [
+        // 1/10 s
+        Frame::from_u8(&canvas(&|_, _| RED), ColorSpace::RGBA, 100, 1000),
+        // 1/3 s, half transparent blue over red
+        Frame::from_u8(
+            &canvas(&|x, y| {
+                if inside(x, y) {
+                    [127, 0, 128, 255]
+                } else {
+                    RED
+                }
+            }),
+            ColorSpace::RGBA,
+            333,
+            1000,
+        ),
+        // 7/0 is 7/100 s
+        Frame::from_u8(
+            &canvas(&|x, y| if (x, y) == (0, 0) { GREEN } else { RED }),
+            ColorSpace::RGBA,
+            70,
+            1000,
+        ),
+    ];
+
+    assert!(img.frames_ref() == expected);
+}
+
+#[test]
+fn static_png() {
+    let file_content = std::fs::File::open("tests/files/png/f1t.png").unwrap();
+
+    let decoder = ApngDecoder::try_new(file_content).unwrap();
+
+    assert!(!decoder.is_animated());
+}
@@ -0,0 +1,257 @@
+use zune_image::errors::{ImageErrors, ImgEncodeErrors};
+
+fn error(msg: &str) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!(
+        "unable to embed icc profile: {msg}"
+    )))
+}
+
+/// ISOBMFF box type and its payload
+type Atom<'a> = ([u8; 4], &'a [u8]);
+
+/// Splits ISOBMFF data into boxes
+fn boxes(mut data: &[u8]) -> Result<Vec<Atom<'_>>, ImageErrors> {
+    let mut boxes = vec![];
+
+    while !data.is_empty() {
+        if data.len() < 8 {
+            return Err(error("truncated box header"));
+        }
+
+        let size = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+        let typ = data[4..8].try_into().unwrap();
+
+        let size = match size {
+            0 => data.len(),
+            1 => return Err(error("64-bit box sizes are not supported")),
+            size if size < 8 || size > data.len() => return Err(error("invalid box size")),
+            size => size,
+        };
+
+        boxes.push((typ, &data[8..size]));
+        data = &data[size..];
+    }
+
+    Ok(boxes)
+}
+
+fn write_box(out: &mut Vec<u8>, typ: [u8; 4], payload: &[u8]) {
+    out.extend_from_slice(&(payload.len() as u32 + 8).to_be_bytes());
+    out.extend_from_slice(&typ);
+    out.extend_from_slice(payload);
+}
+
+fn read_uint(data: &[u8], pos: &mut usize, size: usize) -> Result<u64, ImageErrors> {
+    let bytes = data
+        .get(*pos..*pos + size)
+        .ok_or_else(|| error("truncated box"))?;
+
+    *pos += size;
+
+    Ok(bytes.iter().fold(0, |acc, b| acc << 8 | *b as u64))
+}
+
+fn write_uint(data: &mut [u8], pos: usize, size: usize, value: u64) {
+    for (idx, byte) in data[pos..pos + size].iter_mut().enumerate() {
+        *byte = (value >> (8 * (size - idx - 1))) as u8;
+    }
+}
+
+/// Shifts offsets in `iloc` payload pointing at or after `threshold` by `shift` bytes
+fn shift_iloc(iloc: &[u8], threshold: u64, shift: u64) -> Result<Vec<u8>, ImageErrors> {
+    let mut out = iloc.to_vec();
+    let mut pos = 0;
+
+    let version = read_uint(iloc, &mut pos, 4)? >> 24;
+    let sizes = read_uint(iloc, &mut pos, 2)?;
+
+    let offset_size = (sizes >> 12 & 0xf) as usize;
+    let length_size = (sizes >> 8 & 0xf) as usize;
+    let base_offset_size = (sizes >> 4 & 0xf) as usize;
+    let index_size = if version > 0 {
+        (sizes & 0xf) as usize
+    } else {
+        0
+    };
+    let id_size = if version < 2 { 2 } else { 4 };
+
+    let item_count = read_uint(iloc, &mut pos, id_size)?;
+
+    for _ in 0..item_count {
+        read_uint(iloc, &mut pos, id_size)?;
+
+        let construction_method = if version > 0 {
+            read_uint(iloc, &mut pos, 2)? & 0xf
+        } else {
+            0
+        };
+
+        read_uint(iloc, &mut pos, 2)?;
+
+        let base_pos = pos;
+        let base_offset = read_uint(iloc, &mut pos, base_offset_size)?;
+        let shift_base = construction_method == 0 && base_offset >= threshold;
+
+        if shift_base {
+            write_uint(&mut out, base_pos, base_offset_size, base_offset + shift);
+        }
+
+        let extent_count = read_uint(iloc, &mut pos, 2)?;
+
+        for _ in 0..extent_count {
+            read_uint(iloc, &mut pos, index_size)?;
+
+            let offset_pos = pos;
+            let offset = read_uint(iloc, &mut pos, offset_size)?;
+
+            if construction_method == 0 && !shift_base && base_offset + offset >= threshold {
+                if offset_size == 0 {
+                    return Err(error("unable to relocate item data"));
+                }
+
+                write_uint(&mut out, offset_pos, offset_size, offset + shift);
+            }
+
+            read_uint(iloc, &mut pos, length_size)?;
+        }
+    }
+
+    Ok(out)
+}
+
+/// Appends property `index` to associations of `item` in `ipma` payload
+fn associate(ipma: &[u8], item: u64, index: usize) -> Result<Vec<u8>, ImageErrors> {
+    let mut out = ipma
+        .get(..8)
+        .ok_or_else(|| error("truncated box"))?
+        .to_vec();
+    let mut pos = 0;
+
+    let header = read_uint(ipma, &mut pos, 4)?;
+    let (version, flags) = (header >> 24, header & 0xffffff);
+
+    let id_size = if version < 1 { 2 } else { 4 };
+    let (assoc_size, max_index) = if flags & 1 == 1 {
+        (2, 0x7fff)
+    } else {
+        (1, 0x7f)
+    };
+
+    if index > max_index {
+        return Err(error("too many item properties"));
+    }
+
+    let entry_count = read_uint(ipma, &mut pos, 4)?;
+    let mut associated = false;
+
+    for _ in 0..entry_count {
+        let start = pos;
+        let id = read_uint(ipma, &mut pos, id_size)?;
+        let count = read_uint(ipma, &mut pos, 1)? as usize;
+
+        let end = pos + count * assoc_size;
+        let associations = ipma.get(pos..end).ok_or_else(|| error("truncated box"))?;
+        pos = end;
+
+        if id == item {
+            if count == u8::MAX as usize {
+                return Err(error("too many item properties"));
+            }
+
+            out.extend_from_slice(&ipma[start..start + id_size]);
+            out.push(count as u8 + 1);
+            out.extend_from_slice(associations);
+            out.extend_from_slice(&(index as u16).to_be_bytes()[2 - assoc_size..]);
+
+            associated = true;
+        } else {
+            out.extend_from_slice(&ipma[start..end]);
+        }
+    }
+
+    if !associated {
+        return Err(error("primary item has no properties"));
+    }
+
+    Ok(out)
+}
+
+/// Embeds ICC profile into AVIF file as `colr` property of the primary item
+///
+/// Item data offsets are updated to account for the grown `meta` box.
+pub(super) fn embed_icc(avif: &[u8], icc: &[u8]) -> Result<Vec<u8>, ImageErrors> {
+    let mut colr = vec![];
+    write_box(&mut colr, *b"colr", &[b"prof".as_slice(), icc].concat());
+
+    let mut out = Vec::with_capacity(avif.len() + colr.len() + 2);
+    let mut offset = 0;
+
+    for (typ, payload) in boxes(avif)? {
+        let box_end = (offset + payload.len() + 8) as u64;
+        offset += payload.len() + 8;
+
+        if &typ != b"meta" {
+            write_box(&mut out, typ, payload);
+            continue;
+        }
+
+        let children = boxes(payload.get(4..).ok_or_else(|| error("truncated box"))?)?;
+
+        let primary = children
+            .iter()
+            .find(|(typ, _)| typ == b"pitm")
+            .map(|(_, pitm)| {
+                let mut pos = 0;
+                let version = read_uint(pitm, &mut pos, 4)? >> 24;
+
+                read_uint(pitm, &mut pos, if version == 0 { 2 } else { 4 })
+            })
+            .ok_or_else(|| error("missing primary item"))??;
+
+        let mut iprp = vec![];
+        let mut ipma_grow = 0;
+
+        let (_, iprp_payload) = children
+            .iter()
+            .find(|(typ, _)| typ == b"iprp")
+            .ok_or_else(|| error("missing item properties"))?;
+
+        let iprp_children = boxes(iprp_payload)?;
+
+        let (_, ipco) = iprp_children
+            .iter()
+            .find(|(typ, _)| typ == b"ipco")
+            .ok_or_else(|| error("missing item properties"))?;
+
+        let index = boxes(ipco)?.len() + 1;
+
+        for (typ, child) in iprp_children.iter() {
+            match typ {
+                b"ipco" => write_box(&mut iprp, *typ, &[child, colr.as_slice()].concat()),
+                b"ipma" => {
+                    let ipma = associate(child, primary, index)?;
+                    ipma_grow = ipma.len() - child.len();
+
+                    write_box(&mut iprp, *typ, &ipma);
+                }
+                _ => write_box(&mut iprp, *typ, child),
+            }
+        }
+
+        let shift = (colr.len() + ipma_grow) as u64;
+
+        let mut meta = payload[..4].to_vec();
+
+        for (typ, child) in children {
+            match &typ {
+                b"iprp" => write_box(&mut meta, typ, &iprp),
+                b"iloc" => write_box(&mut meta, typ, &shift_iloc(child, box_end, shift)?),
+                _ => write_box(&mut meta, typ, child),
+            }
+        }
+
+        write_box(&mut out, typ, &meta);
+    }
+
+    Ok(out)
+}
@@ -1,5 +1,6 @@
-use ravif::Img;
-use rgb::FromSlice;
+use rav1e::prelude::PixelRange;
+use ravif::{AlphaColorMode, EncodedImage, Img, MatrixCoefficients, RGB8};
+use rgb::{ComponentMap, FromSlice};
 use zune_core::{
     bit_depth::BitDepth,
     bytestream::{ZByteWriterTrait, ZWriter},
@@ -9,10 +10,18 @@ use zune_image::{
     codecs::ImageFormat,
     errors::{ImageErrors, ImgEncodeErrors},
     image::Image,
+    metadata::AlphaState,
     traits::EncoderTrait,
 };
 
+use crate::codecs::{check_dimensions, OutputFormat};
+
+mod icc;
+
 /// Advanced options for AVIF encoding
+///
+/// These are the knobs ravif exposes. Tools like chroma from luma prediction
+/// can't be toggled, rav1e picks them on its own during mode search.
 pub struct AvifOptions {
     /// Quality `1..=100`
     pub quality: f32,
@@ -23,12 +32,34 @@ pub struct AvifOptions {
     /// 1 = very very slow, but max compression
     /// 10 = quick, but larger file sizes and lower quality.
     pub speed: u8,
+    /// Number of encodes at decreasing speeds, starting from `speed` `1..=10`
+    ///
+    /// This is a best of N search rather than multi-pass encoding: every pass is
+    /// an independent encode one speed step slower than the previous one, stopping
+    /// at speed 1, and the smallest result is kept. 1 encodes the image once.
+    pub passes: u8,
+    /// Bit depth of the AV1 stream, 8 or 10
+    ///
+    /// 10 bits reduce banding in smooth gradients at a small size cost.
+    /// `None` uses 10 bits for opaque images and 8 bits for transparent ones.
+    /// Images with [`AlphaColorMode::Premultiplied`] colors are always stored in 8 bits.
+    pub depth: Option<u8>,
     /// Changes how color channels are stored in the image.
     ///
     /// Note that this is only internal detail for the AVIF file, and doesn't change color space of inputs to encode functions.
     pub color_space: ravif::ColorSpace,
     /// Configure handling of color channels in transparent images
+    ///
+    /// - `UnassociatedClean` (default) replaces colors of fully transparent pixels
+    ///   with colors of their neighbours, which compresses better without halos.
+    /// - `UnassociatedDirty` keeps colors of transparent pixels as is, it's the fastest,
+    ///   but hidden colors cost bits and bleed into visible edges as halos.
+    /// - `Premultiplied` stores colors multiplied by alpha, which compresses soft edges best,
+    ///   but requires decoders supporting premultiplied AVIF. Images marked as premultiplied
+    ///   are stored as is, others are premultiplied before encoding.
     pub alpha_color_mode: ravif::AlphaColorMode,
+    /// Copy ICC profile of the image into `colr` box
+    pub preserve_icc: bool,
 }
 
 /// A AVIF encoder
@@ -43,8 +74,11 @@ impl Default for AvifOptions {
             quality: 50.,
             alpha_quality: None,
             speed: 6,
+            passes: 1,
+            depth: None,
             color_space: ravif::ColorSpace::YCbCr,
             alpha_color_mode: ravif::AlphaColorMode::UnassociatedClean,
+            preserve_icc: false,
         }
     }
 }
@@ -61,6 +95,14 @@ impl AvifEncoder {
     }
 }
 
+/// Speeds of every encode for `passes`, from `speed` down to the slowest one
+fn pass_speeds(speed: u8, passes: u8) -> impl Iterator<Item = u8> {
+    let speed = speed.clamp(1, 10);
+    let slowest = speed.saturating_sub(passes.max(1) - 1).max(1);
+
+    (slowest..=speed).rev()
+}
+
 impl EncoderTrait for AvifEncoder {
     fn name(&self) -> &'static str {
         "avif"
@@ -71,51 +113,98 @@ impl EncoderTrait for AvifEncoder {
         image: &Image,
         sink: T,
     ) -> Result<usize, ImageErrors> {
+        check_dimensions(self.name(), image)?;
+
+        if image.colorspace() == ColorSpace::LumaA {
+            // ravif has no grayscale input, expand to RGBA to keep the alpha channel
+            let mut image = image.clone();
+            image.convert_color(ColorSpace::RGBA)?;
+
+            return self.encode_inner(&image, sink);
+        }
+
+        if let Some(depth) = self.options.depth.filter(|d| ![8, 10].contains(d)) {
+            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                format!("{depth}-bit depth is not supported, expected 8 or 10"),
+            )));
+        }
+
         let (width, height) = image.dimensions();
         let data = &image.flatten_to_u8()[0];
 
         let mut writer = ZWriter::new(sink);
 
-        let encoder = ravif::Encoder::new()
-            .with_quality(self.options.quality)
-            .with_alpha_quality(self.options.alpha_quality.unwrap_or(self.options.quality))
-            .with_speed(self.options.speed)
-            .with_internal_color_space(self.options.color_space)
-            .with_alpha_color_mode(self.options.alpha_color_mode);
-
-        match image.colorspace() {
-            ColorSpace::RGB => {
-                let img = Img::new(data.as_slice().as_rgb(), width, height);
-                let result = encoder
-                    .encode_rgb(img)
-                    .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
-
-                writer.write(&result.avif_file).map_err(|e| {
-                    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
-                })?;
-
-                Ok(writer.bytes_written())
+        let encode = |speed: u8| {
+            let encoder = ravif::Encoder::new()
+                .with_quality(self.options.quality)
+                .with_alpha_quality(self.options.alpha_quality.unwrap_or(self.options.quality))
+                .with_speed(speed)
+                .with_depth(self.options.depth)
+                .with_internal_color_space(self.options.color_space)
+                .with_alpha_color_mode(self.options.alpha_color_mode);
+
+            match image.colorspace() {
+                ColorSpace::RGB => {
+                    let img = Img::new(data.as_slice().as_rgb(), width, height);
+                    encoder.encode_rgb(img)
+                }
+                ColorSpace::RGBA
+                    if self.options.alpha_color_mode == AlphaColorMode::Premultiplied =>
+                {
+                    let premultiplied = image.metadata().alpha() == AlphaState::PreMultiplied;
+
+                    encode_premultiplied(
+                        &encoder,
+                        data,
+                        width,
+                        height,
+                        self.options.color_space,
+                        premultiplied,
+                    )
+                }
+                ColorSpace::RGBA => {
+                    let img = Img::new(data.as_slice().as_rgba(), width, height);
+                    encoder.encode_rgba(img)
+                }
+                cs => {
+                    return Err(ImageErrors::EncodeErrors(
+                        ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
+                    ))
+                }
             }
-            ColorSpace::RGBA => {
-                let img = Img::new(data.as_slice().as_rgba(), width, height);
-                let result = encoder
-                    .encode_rgba(img)
-                    .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+            .map_err(|e| ImageErrors::from(ImgEncodeErrors::ImageEncodeErrors(e.to_string())))
+        };
+
+        let mut speeds = pass_speeds(self.options.speed, self.options.passes);
+        let mut result = encode(speeds.next().unwrap_or(self.options.speed))?;
 
-                writer.write(&result.avif_file).map_err(|e| {
-                    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
-                })?;
+        for speed in speeds {
+            let pass = encode(speed)?;
 
-                Ok(writer.bytes_written())
+            if pass.avif_file.len() < result.avif_file.len() {
+                result = pass;
             }
-            cs => Err(ImageErrors::EncodeErrors(
-                ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
-            )),
         }
+
+        let icc = image
+            .metadata()
+            .icc_chunk()
+            .filter(|_| self.options.preserve_icc);
+
+        let avif_file = match icc {
+            Some(icc) => icc::embed_icc(&result.avif_file, icc)?,
+            None => result.avif_file,
+        };
+
+        writer.write(&avif_file).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
     }
 
     fn supported_colorspaces(&self) -> &'static [ColorSpace] {
-        &[ColorSpace::RGB, ColorSpace::RGBA]
+        &[ColorSpace::RGB, ColorSpace::RGBA, ColorSpace::LumaA]
     }
 
     fn format(&self) -> ImageFormat {
@@ -131,5 +220,78 @@ impl EncoderTrait for AvifEncoder {
     }
 }
 
+impl OutputFormat for AvifEncoder {
+    fn extension(&self) -> &'static str {
+        "avif"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "image/avif"
+    }
+}
+
+/// Encodes RGBA pixels with colors premultiplied by alpha
+///
+/// ravif premultiplication drops opaque pixels and divides colors instead of
+/// multiplying them, so colors are premultiplied here, unless they already are,
+/// and passed to the encoder as raw planes. The encoder still has to be configured
+/// with [`AlphaColorMode::Premultiplied`] to mark the file as premultiplied.
+fn encode_premultiplied(
+    encoder: &ravif::Encoder,
+    data: &[u8],
+    width: usize,
+    height: usize,
+    color_space: ravif::ColorSpace,
+    premultiplied: bool,
+) -> Result<EncodedImage, ravif::Error> {
+    let pixels = data.as_rgba();
+
+    if pixels.iter().all(|px| px.a == u8::MAX) {
+        let rgb = pixels.iter().map(|px| px.rgb()).collect::<Vec<_>>();
+
+        return encoder.encode_rgb(Img::new(&rgb, width, height));
+    }
+
+    let colors = pixels.iter().map(|px| match premultiplied {
+        true => px.rgb(),
+        false => px
+            .rgb()
+            .map(|c| ((u16::from(c) * u16::from(px.a) + 127) / 255) as u8),
+    });
+    let alpha = pixels.iter().map(|px| px.a);
+
+    match color_space {
+        ravif::ColorSpace::YCbCr => encoder.encode_raw_planes_8_bit(
+            width,
+            height,
+            colors.map(rgb_to_ycbcr),
+            Some(alpha),
+            PixelRange::Full,
+            MatrixCoefficients::BT601,
+        ),
+        ravif::ColorSpace::RGB => encoder.encode_raw_planes_8_bit(
+            width,
+            height,
+            colors.map(|px| [px.g, px.b, px.r]),
+            Some(alpha),
+            PixelRange::Full,
+            MatrixCoefficients::Identity,
+        ),
+    }
+}
+
+/// Full range BT.601 conversion, the same ravif uses for 8-bit images
+fn rgb_to_ycbcr(px: RGB8) -> [u8; 3] {
+    const BT601: [f32; 3] = [0.299, 0.587, 0.114];
+
+    let [r, g, b] = [px.r, px.g, px.b].map(f32::from);
+
+    let y = BT601[0] * r + BT601[1] * g + BT601[2] * b;
+    let cb = (b - y).mul_add(0.5 / (1. - BT601[2]), 128.);
+    let cr = (r - y).mul_add(0.5 / (1. - BT601[0]), 128.);
+
+    [y, cb, cr].map(|v| v.round() as u8)
+}
+
 #[cfg(test)]
 mod tests;
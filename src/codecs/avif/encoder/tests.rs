@@ -156,3 +156,191 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_luma_alpha() {
+    let image = create_test_image_u8(200, 200, ColorSpace::LumaA);
+    let mut encoder = AvifEncoder::new();
+
+    let buf = Cursor::new(vec![]);
+
+    let result = encoder.encode_inner(&image, buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+}
+
+/// Returns data of every item extent, assuming `iloc` layout of avif-serialize
+fn item_data(avif: &[u8]) -> Vec<&[u8]> {
+    let meta = avif.windows(4).position(|w| w == b"iloc").unwrap() + 4;
+
+    let items = u16::from_be_bytes([avif[meta + 6], avif[meta + 7]]);
+    let mut pos = meta + 8;
+    let mut data = vec![];
+
+    for _ in 0..items {
+        let extents = u16::from_be_bytes([avif[pos + 4], avif[pos + 5]]);
+        pos += 6;
+
+        for _ in 0..extents {
+            let offset = u32::from_be_bytes(avif[pos..pos + 4].try_into().unwrap()) as usize;
+            let len = u32::from_be_bytes(avif[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            pos += 8;
+
+            data.push(&avif[offset..offset + len]);
+        }
+    }
+
+    data
+}
+
+#[test]
+fn encode_preserve_icc() {
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGBA);
+
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    let mut plain = vec![];
+    AvifEncoder::new().encode(&image, &mut plain).unwrap();
+
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+        preserve_icc: true,
+        ..Default::default()
+    });
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let colr = [b"colr".as_slice(), b"prof", &icc].concat();
+
+    assert!(buf.windows(colr.len()).any(|w| w == colr));
+    assert_eq!(item_data(&plain), item_data(&buf));
+}
+
+#[test]
+fn encode_lossy_alpha() {
+    // smooth color with alpha gradient and noise, so alpha takes a noticeable part of the file
+    let pixels = (0..128 * 128)
+        .flat_map(|idx| {
+            let (x, y) = (idx % 128, idx / 128);
+            let noise = idx * 7919 % 31;
+
+            [120, 80, 200, ((x + y + noise) / 2) as u8]
+        })
+        .collect::<Vec<u8>>();
+    let image = Image::from_u8(&pixels, 128, 128, ColorSpace::RGBA);
+
+    let encode = |alpha_quality| {
+        let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+            quality: 80.,
+            alpha_quality,
+            speed: 10,
+            ..Default::default()
+        });
+
+        let mut buf = vec![];
+        encoder.encode(&image, &mut buf).unwrap();
+
+        buf.len()
+    };
+
+    assert!(encode(Some(10.)) < encode(None));
+}
+
+#[test]
+fn encode_passes() {
+    assert_eq!(pass_speeds(6, 1).collect::<Vec<_>>(), [6]);
+    assert_eq!(pass_speeds(6, 0).collect::<Vec<_>>(), [6]);
+    assert_eq!(pass_speeds(6, 3).collect::<Vec<_>>(), [6, 5, 4]);
+    assert_eq!(pass_speeds(2, 5).collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(pass_speeds(0, 2).collect::<Vec<_>>(), [1]);
+
+    let image = Image::open("tests/files/png/f1t.png").unwrap();
+
+    let encode = |speed: u8, passes: u8| {
+        let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+            speed,
+            passes,
+            ..Default::default()
+        });
+        let mut buf = vec![];
+
+        encoder.encode(&image, &mut buf).unwrap();
+
+        buf
+    };
+
+    // the first of the smallest single encodes is kept
+    let smallest = (8..=10)
+        .rev()
+        .map(|speed| encode(speed, 1))
+        .min_by_key(Vec::len)
+        .unwrap();
+
+    assert_eq!(encode(10, 3), smallest);
+}
+
+#[test]
+fn encode_alpha_modes() {
+    // soft edge with colors hidden under transparent pixels
+    let pixels = (0..64 * 64)
+        .flat_map(|idx| {
+            let (x, y) = (idx % 64, idx / 64);
+
+            [(x * 4) as u8, (y * 4) as u8, 160, (x * 8).min(255) as u8]
+        })
+        .collect::<Vec<u8>>();
+    let image = Image::from_u8(&pixels, 64, 64, ColorSpace::RGBA);
+
+    let encode = |alpha_color_mode| {
+        let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+            speed: 10,
+            alpha_color_mode,
+            ..Default::default()
+        });
+
+        let mut buf = vec![];
+        encoder.encode(&image, &mut buf).unwrap();
+
+        buf
+    };
+
+    let dirty = encode(ravif::AlphaColorMode::UnassociatedDirty);
+    let clean = encode(ravif::AlphaColorMode::UnassociatedClean);
+    let premultiplied = encode(ravif::AlphaColorMode::Premultiplied);
+
+    assert_ne!(dirty, clean);
+    assert_ne!(dirty, premultiplied);
+    assert_ne!(clean, premultiplied);
+}
+
+#[test]
+fn encode_depth() {
+    let image = Image::open("tests/files/png/f1t.png").unwrap();
+
+    let encode = |depth| {
+        let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+            speed: 10,
+            depth,
+            ..Default::default()
+        });
+        let mut buf = vec![];
+
+        encoder.encode(&image, &mut buf).map(|_| buf)
+    };
+
+    let eight = encode(Some(8)).unwrap();
+    let ten = encode(Some(10)).unwrap();
+
+    assert_ne!(eight, ten);
+    // opaque images are encoded in 10 bits by default
+    assert_eq!(encode(None).unwrap(), ten);
+
+    assert!(encode(Some(12)).is_err());
+}
@@ -0,0 +1,95 @@
+use std::io::Read;
+
+use zune_core::colorspace::ColorSpace;
+use zune_image::{errors::ImageErrors, image::Image, traits::DecoderTrait};
+
+/// Magic bytes every farbfeld file starts with
+const MAGIC: &[u8; 8] = b"farbfeld";
+
+/// A farbfeld decoder
+///
+/// Farbfeld stores 16-bit big-endian RGBA pixels after a 16 bytes header.
+pub struct FarbfeldDecoder<R: Read> {
+    inner: R,
+    dimensions: (usize, usize),
+}
+
+impl<R: Read> FarbfeldDecoder<R> {
+    /// Create a new farbfeld decoder that reads data from `source`
+    pub fn try_new(mut source: R) -> Result<FarbfeldDecoder<R>, ImageErrors> {
+        let mut header = [0; 16];
+
+        source.read_exact(&mut header).map_err(|e| {
+            ImageErrors::ImageDecodeErrors(format!("Unable to read farbfeld header - {e}"))
+        })?;
+
+        if &header[..8] != MAGIC {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "Not a farbfeld file".to_string(),
+            ));
+        }
+
+        let width = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(header[12..16].try_into().unwrap()) as usize;
+
+        Ok(FarbfeldDecoder {
+            inner: source,
+            dimensions: (width, height),
+        })
+    }
+}
+
+impl<R> DecoderTrait for FarbfeldDecoder<R>
+where
+    R: Read,
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let (width, height) = self.dimensions;
+
+        let size = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(8))
+            .ok_or_else(|| {
+                ImageErrors::ImageDecodeErrors(format!(
+                    "Farbfeld dimensions {width}x{height} are too large"
+                ))
+            })?;
+
+        let mut data = vec![];
+
+        self.inner
+            .by_ref()
+            .take(size as u64)
+            .read_to_end(&mut data)
+            .map_err(|e| ImageErrors::ImageDecodeErrors(format!("Unable to read pixels - {e}")))?;
+
+        if data.len() != size {
+            return Err(ImageErrors::ImageDecodeErrors(format!(
+                "Farbfeld pixel data is truncated, expected {size} bytes, got {}",
+                data.len()
+            )));
+        }
+
+        let pixels = data
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+
+        Ok(Image::from_u16(&pixels, width, height, ColorSpace::RGBA))
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some(self.dimensions)
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        ColorSpace::RGBA
+    }
+
+    fn name(&self) -> &'static str {
+        "farbfeld-decoder"
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,40 @@
+use std::io::Cursor;
+
+use zune_core::bit_depth::BitDepth;
+use zune_image::{codecs::farbfeld::FarbFeldEncoder, traits::EncoderTrait};
+
+use crate::test_utils::*;
+
+use super::*;
+
+#[test]
+fn roundtrip() {
+    let image = create_test_image_u16(32, 32, ColorSpace::RGBA);
+
+    let mut buf = vec![];
+    FarbFeldEncoder::new().encode(&image, &mut buf).unwrap();
+
+    let decoder = FarbfeldDecoder::try_new(Cursor::new(buf)).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (32, 32));
+    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+    assert_eq!(img.depth(), BitDepth::Sixteen);
+    assert!(img.frames_ref() == image.frames_ref());
+}
+
+#[test]
+fn invalid_magic() {
+    assert!(FarbfeldDecoder::try_new(Cursor::new(b"farbfelt\0\0\0\x01\0\0\0\x01")).is_err());
+}
+
+#[test]
+fn truncated() {
+    let mut buf = b"farbfeld\0\0\0\x02\0\0\0\x02".to_vec();
+    buf.extend_from_slice(&[0; 8]);
+
+    let decoder = FarbfeldDecoder::try_new(Cursor::new(buf)).unwrap();
+
+    assert!(Image::from_decoder(decoder).is_err());
+}
@@ -0,0 +1,144 @@
+use std::io::Read;
+
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
+
+/// A GIF decoder
+///
+/// Frames are composited onto the full canvas honoring their disposal method,
+/// each frame keeps its delay in hundredths of a second.
+pub struct GifDecoder<R: Read> {
+    inner: gif::Decoder<R>,
+    delays: Vec<u16>,
+}
+
+impl<R: Read> GifDecoder<R> {
+    /// Create a new gif decoder that reads data from `source`
+    pub fn try_new(source: R) -> Result<GifDecoder<R>, ImageErrors> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+
+        let inner = options.read_info(source).map_err(|e| {
+            ImageErrors::ImageDecodeErrors(format!("Unable to create GIF decoder: {e}"))
+        })?;
+
+        Ok(GifDecoder {
+            inner,
+            delays: vec![],
+        })
+    }
+
+    /// Delays of decoded frames in milliseconds
+    ///
+    /// Filled by [`DecoderTrait::decode`], since zune-image doesn't expose
+    /// delays stored in frames.
+    pub fn delays(&self) -> &[u16] {
+        &self.delays
+    }
+}
+
+impl<R> DecoderTrait for GifDecoder<R>
+where
+    R: Read,
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let (width, height) = <GifDecoder<R> as DecoderTrait>::dimensions(self).unwrap();
+
+        let mut canvas = vec![0; width * height * 4];
+        let mut frames = vec![];
+        self.delays.clear();
+
+        while let Some(frame) = self
+            .inner
+            .next_frame_info()
+            .map_err(|e| ImageErrors::ImageDecodeErrors(format!("Unable to read frame - {e}")))?
+        {
+            let (left, top) = (frame.left as usize, frame.top as usize);
+            let (delay, dispose) = (frame.delay, frame.dispose);
+            let (full_width, full_height) = (frame.width as usize, frame.height as usize);
+
+            // empty frames have no pixels to draw, their data is skipped with the next frame
+            if full_width == 0 || full_height == 0 {
+                continue;
+            }
+
+            let mut buffer = vec![0; self.inner.buffer_size()];
+            self.inner.read_into_buffer(&mut buffer).map_err(|e| {
+                ImageErrors::ImageDecodeErrors(format!("Unable to read frame - {e}"))
+            })?;
+
+            // frames may lie partially or entirely outside of the canvas,
+            // only their visible part is drawn and disposed
+            let frame_width = full_width.min(width.saturating_sub(left));
+            let frame_height = full_height.min(height.saturating_sub(top));
+            let visible = frame_width > 0 && frame_height > 0;
+
+            let previous = (dispose == gif::DisposalMethod::Previous).then(|| canvas.clone());
+
+            if visible {
+                buffer
+                    .chunks_exact(full_width * 4)
+                    .take(frame_height)
+                    .enumerate()
+                    .for_each(|(y, row)| {
+                        let offset = ((top + y) * width + left) * 4;
+
+                        canvas[offset..offset + frame_width * 4]
+                            .chunks_exact_mut(4)
+                            .zip(row.chunks_exact(4))
+                            .filter(|(_, px)| px[3] != 0)
+                            .for_each(|(dst, src)| dst.copy_from_slice(src));
+                    });
+            }
+
+            frames.push(Frame::from_u8(
+                &canvas,
+                ColorSpace::RGBA,
+                delay as usize,
+                100,
+            ));
+            self.delays.push(delay.saturating_mul(10));
+
+            match dispose {
+                gif::DisposalMethod::Background if visible => {
+                    canvas
+                        .chunks_exact_mut(width * 4)
+                        .skip(top)
+                        .take(frame_height)
+                        .for_each(|row| row[left * 4..(left + frame_width) * 4].fill(0));
+                }
+                gif::DisposalMethod::Previous => {
+                    if let Some(previous) = previous {
+                        canvas = previous;
+                    }
+                }
+                gif::DisposalMethod::Background
+                | gif::DisposalMethod::Any
+                | gif::DisposalMethod::Keep => {}
+            }
+        }
+
+        Ok(Image::new_frames(
+            frames,
+            BitDepth::Eight,
+            width,
+            height,
+            ColorSpace::RGBA,
+        ))
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some((self.inner.width() as usize, self.inner.height() as usize))
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        ColorSpace::RGBA
+    }
+
+    fn name(&self) -> &'static str {
+        "gif"
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,114 @@
+use std::fs::File;
+
+use super::*;
+
+#[test]
+fn decode() {
+    let file_content = File::open("tests/files/gif/f1t.gif").unwrap();
+
+    let decoder = GifDecoder::try_new(file_content).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (8, 8));
+    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+    assert_eq!(img.frames_len(), 3);
+}
+
+#[test]
+fn decode_durations_and_disposal() {
+    let file_content = File::open("tests/files/gif/f1t.gif").unwrap();
+
+    let mut decoder = GifDecoder::try_new(file_content).unwrap();
+
+    let img = decoder.decode().unwrap();
+
+    assert_eq!(decoder.delays(), [100, 200, 300]);
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    let canvas = |f: &dyn Fn(usize, usize) -> [u8; 4]| {
+        (0..8)
+            .flat_map(|y| (0..8).flat_map(move |x| f(x, y)))
+            .collect::<Vec<u8>>()
+    };
+
+    let inside = |x: usize, y: usize| (2..6).contains(&x) && (2..6).contains(&y);
+
+    let expected = [
+        Frame::from_u8(&canvas(&|_, _| RED), ColorSpace::RGBA, 10, 100),
+        Frame::from_u8(
+            &canvas(&|x, y| if inside(x, y) { BLUE } else { RED }),
+            ColorSpace::RGBA,
+            20,
+            100,
+        ),
+        Frame::from_u8(
+            &canvas(&|x, y| match (x, y) {
+                (0..2, 0..2) => GREEN,
+                _ if inside(x, y) => CLEAR,
+                _ => RED,
+            }),
+            ColorSpace::RGBA,
+            30,
+            100,
+        ),
+    ];
+
+    assert!(img.frames_ref() == expected);
+}
+
+#[test]
+fn decode_out_of_bounds_frames() {
+    let mut buf = vec![];
+
+    {
+        let mut encoder = gif::Encoder::new(&mut buf, 4, 4, &[]).unwrap();
+
+        for (left, top, width, height) in [(0, 0, 4, 4), (2, 2, 4, 4), (8, 0, 2, 2), (0, 0, 0, 2)] {
+            let mut frame = gif::Frame::from_palette_pixels(
+                width,
+                height,
+                vec![0; width as usize * height as usize],
+                [255, 0, 0],
+                None,
+            );
+            frame.left = left;
+            frame.top = top;
+            frame.dispose = gif::DisposalMethod::Background;
+
+            encoder.write_frame(&frame).unwrap();
+        }
+    }
+
+    let decoder = GifDecoder::try_new(buf.as_slice()).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (4, 4));
+    // empty frame is skipped
+    assert_eq!(img.frames_len(), 3);
+
+    let pixels = img.flatten_to_u8();
+    let red = |frame: &[u8]| {
+        frame
+            .chunks_exact(4)
+            .map(|px| px == [255, 0, 0, 255])
+            .collect::<Vec<_>>()
+    };
+
+    // clipped frame is drawn over the cleared canvas
+    assert!(red(&pixels[0]).iter().all(|v| *v));
+    assert_eq!(
+        red(&pixels[1]),
+        (0..16)
+            .map(|i| i % 4 >= 2 && i / 4 >= 2)
+            .collect::<Vec<_>>()
+    );
+    assert!(pixels[2..]
+        .iter()
+        .all(|frame| frame.iter().all(|v| *v == 0)));
+}
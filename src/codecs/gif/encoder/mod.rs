@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    image::Image,
+    traits::{EncoderTrait, OperationsTrait},
+};
+
+use crate::{
+    codecs::{check_dimensions, OutputFormat},
+    operations::quantize::Quantize,
+};
+
+/// Default delay between animation frames in milliseconds
+pub const DEFAULT_FRAME_DELAY: u16 = 100;
+
+/// Options for the GIF encoder
+#[derive(Debug, Clone)]
+pub struct GifOptions {
+    /// Quality of the generated palette, values from 0 to 100
+    pub quality: u8,
+    /// Dithering level applied when reducing colors, values from 0.0 to 1.0
+    pub dithering: Option<f32>,
+    /// Delay of every animation frame in milliseconds
+    ///
+    /// Used for frames without a source delay set with
+    /// [`GifEncoder::frame_delays`], frames without either use [`DEFAULT_FRAME_DELAY`].
+    pub delays: Vec<u16>,
+    /// Number of times the animation is repeated, `None` loops forever
+    pub repeat: Option<u16>,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            quality: 75,
+            dithering: Some(1.0),
+            delays: vec![],
+            repeat: None,
+        }
+    }
+}
+
+/// A GIF encoder
+///
+/// Every frame is reduced to a 256 color palette with the [`Quantize`] operation,
+/// pixels with alpha below 128 are written as fully transparent.
+#[derive(Default)]
+pub struct GifEncoder {
+    options: GifOptions,
+    frame_delays: Vec<u16>,
+}
+
+impl GifEncoder {
+    /// Create a new encoder
+    pub fn new() -> GifEncoder {
+        GifEncoder::default()
+    }
+
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: GifOptions) -> GifEncoder {
+        GifEncoder {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Delays of the source animation frames in milliseconds
+    ///
+    /// Takes precedence over [`GifOptions::delays`], zune-image frames don't
+    /// expose their delays, so decoders like [`GifDecoder`](crate::codecs::gif::GifDecoder)
+    /// report them separately.
+    #[must_use]
+    pub fn frame_delays(mut self, delays: Vec<u16>) -> GifEncoder {
+        self.frame_delays = delays;
+        self
+    }
+}
+
+/// Quantized frame ready to be written into GIF
+struct IndexedPixels {
+    indices: Vec<u8>,
+    palette: Vec<u8>,
+    transparent: Option<u8>,
+}
+
+/// Maps quantized RGBA pixels to palette indices
+fn index_pixels(pixels: &[u8]) -> Result<IndexedPixels, ImageErrors> {
+    let mut colors = HashMap::new();
+    let mut palette = vec![];
+    let mut transparent = None;
+
+    let indices = pixels
+        .chunks_exact(4)
+        .map(|px| {
+            let color = if px[3] < 128 {
+                [0; 3]
+            } else {
+                [px[0], px[1], px[2]]
+            };
+            let key = (color, px[3] < 128);
+
+            if let Some(&idx) = colors.get(&key) {
+                return Ok(idx);
+            }
+
+            let idx = u8::try_from(colors.len()).map_err(|_| {
+                ImgEncodeErrors::ImageEncodeErrors("gif palette exceeds 256 colors".to_string())
+            })?;
+
+            colors.insert(key, idx);
+            palette.extend_from_slice(&color);
+
+            if key.1 {
+                transparent = Some(idx);
+            }
+
+            Ok(idx)
+        })
+        .collect::<Result<Vec<u8>, ImageErrors>>()?;
+
+    Ok(IndexedPixels {
+        indices,
+        palette,
+        transparent,
+    })
+}
+
+impl EncoderTrait for GifEncoder {
+    fn name(&self) -> &'static str {
+        "gif"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        check_dimensions(self.name(), image)?;
+
+        let (width, height) = image.dimensions();
+
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(ImageErrors::EncodeErrors(
+                ImgEncodeErrors::ImageEncodeErrors(format!(
+                    "gif dimensions cannot exceed {0}x{0}",
+                    u16::MAX
+                )),
+            ));
+        };
+
+        let mut image = image.clone();
+
+        if image.colorspace() != ColorSpace::RGBA {
+            image.convert_color(ColorSpace::RGBA)?;
+        }
+
+        Quantize::new(self.options.quality, self.options.dithering).execute(&mut image)?;
+
+        let map_err = |e: gif::EncodingError| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!(
+                "gif encoding failed: {e}"
+            )))
+        };
+
+        let mut buf = vec![];
+
+        {
+            let mut encoder = gif::Encoder::new(&mut buf, width, height, &[]).map_err(map_err)?;
+
+            if image.is_animated() {
+                encoder
+                    .set_repeat(match self.options.repeat {
+                        Some(count) => gif::Repeat::Finite(count),
+                        None => gif::Repeat::Infinite,
+                    })
+                    .map_err(map_err)?;
+            }
+
+            for (idx, pixels) in image.flatten_to_u8().iter().enumerate() {
+                let pixels = index_pixels(pixels)?;
+
+                let mut frame = gif::Frame::from_palette_pixels(
+                    width,
+                    height,
+                    pixels.indices,
+                    pixels.palette,
+                    pixels.transparent,
+                );
+
+                if image.is_animated() {
+                    let delay = self
+                        .frame_delays
+                        .get(idx)
+                        .or_else(|| self.options.delays.get(idx))
+                        .copied()
+                        .unwrap_or(DEFAULT_FRAME_DELAY);
+
+                    // gif stores delays in hundredths of a second
+                    frame.delay = delay.div_ceil(10);
+                    frame.dispose = gif::DisposalMethod::Background;
+                }
+
+                encoder.write_frame(&frame).map_err(map_err)?;
+            }
+        }
+
+        let mut writer = ZWriter::new(sink);
+
+        writer.write(&buf).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGBA]
+    }
+
+    // TODO: update when new version with custom image format is released.
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Unknown
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _depth: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+
+    fn supports_animated_images(&self) -> bool {
+        true
+    }
+}
+
+impl OutputFormat for GifEncoder {
+    fn extension(&self) -> &'static str {
+        "gif"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "image/gif"
+    }
+}
+
+#[cfg(test)]
+mod tests;
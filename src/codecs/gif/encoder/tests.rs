@@ -0,0 +1,126 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::frame::Frame;
+
+use crate::test_utils::*;
+
+use super::*;
+
+fn decode_frames(buf: &[u8]) -> Vec<gif::Frame<'static>> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+
+    let mut decoder = options.read_info(buf).unwrap();
+    let mut frames = vec![];
+
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        frames.push(frame.clone());
+    }
+
+    frames
+}
+
+#[test]
+fn encode_u8() {
+    let image = create_test_image_u8(200, 200, ColorSpace::RGB);
+    let mut encoder = GifEncoder::new();
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let frames = decode_frames(&buf);
+
+    assert_eq!(frames.len(), 1);
+    assert_eq!((frames[0].width, frames[0].height), (200, 200));
+}
+
+#[test]
+fn encode_u16() {
+    let image = create_test_image_u16(200, 200, ColorSpace::RGB);
+    let mut encoder = GifEncoder::new();
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn encode_animated() {
+    let red = Frame::from_u8(&[255, 0, 0].repeat(64 * 64), ColorSpace::RGB, 0, 0);
+    let blue = Frame::from_u8(&[0, 0, 255].repeat(64 * 64), ColorSpace::RGB, 0, 0);
+
+    let image = Image::new_frames(vec![red, blue], BitDepth::Eight, 64, 64, ColorSpace::RGB);
+
+    let mut encoder = GifEncoder::new_with_options(GifOptions {
+        delays: vec![200, 500],
+        ..Default::default()
+    });
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let frames = decode_frames(&buf);
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].delay, 20);
+    assert_eq!(frames[1].delay, 50);
+    assert_eq!(&frames[0].buffer[..4], &[255, 0, 0, 255]);
+    assert_eq!(&frames[1].buffer[..4], &[0, 0, 255, 255]);
+}
+
+#[test]
+fn encode_frame_delays() {
+    let frames = (0..3)
+        .map(|_| Frame::from_u8(&[0; 8 * 8 * 3], ColorSpace::RGB, 0, 0))
+        .collect();
+
+    let image = Image::new_frames(frames, BitDepth::Eight, 8, 8, ColorSpace::RGB);
+
+    let mut encoder = GifEncoder::new_with_options(GifOptions {
+        delays: vec![500, 500],
+        ..Default::default()
+    })
+    .frame_delays(vec![70]);
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let delays = decode_frames(&buf)
+        .iter()
+        .map(|frame| frame.delay)
+        .collect::<Vec<_>>();
+
+    // source delays first, then options, then the default
+    assert_eq!(delays, [7, 50, DEFAULT_FRAME_DELAY / 10]);
+}
+
+#[test]
+fn encode_transparent() {
+    let image = create_test_image_u8(64, 64, ColorSpace::RGBA);
+    let mut encoder = GifEncoder::new();
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let frames = decode_frames(&buf);
+
+    assert!(frames[0].buffer.chunks_exact(4).all(|px| px[3] == 0));
+}
@@ -0,0 +1,5 @@
+mod decoder;
+mod encoder;
+
+pub use decoder::*;
+pub use encoder::*;
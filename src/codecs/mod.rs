@@ -1,7 +1,19 @@
+/// APNG decoding support
+#[cfg(feature = "apng")]
+pub mod apng;
+
 /// AVIF encoding support
 #[cfg(feature = "avif")]
 pub mod avif;
 
+/// Farbfeld decoding support
+#[cfg(feature = "farbfeld")]
+pub mod farbfeld;
+
+/// GIF encoding and decoding support
+#[cfg(feature = "gif")]
+pub mod gif;
+
 /// MozJpeg encoding support
 #[cfg(feature = "mozjpeg")]
 pub mod mozjpeg;
@@ -10,6 +22,11 @@ pub mod mozjpeg;
 #[cfg(feature = "oxipng")]
 pub mod oxipng;
 
+/// Probing support of formats without decoding or encoding
+mod probe;
+
+pub use probe::{can_decode, can_decode_bytes, can_encode};
+
 /// Tiff encoding support
 #[cfg(feature = "tiff")]
 pub mod tiff;
@@ -17,3 +34,45 @@ pub mod tiff;
 /// WebP encoding support
 #[cfg(feature = "webp")]
 pub mod webp;
+
+/// File type of images written by an encoder
+///
+/// Useful to name output files or to fill `Content-Type` of HTTP responses.
+pub trait OutputFormat {
+    /// Conventional file extension, without the leading dot
+    fn extension(&self) -> &'static str;
+
+    /// MIME type of encoded images, like `image/webp`
+    fn mime_type(&self) -> &'static str;
+}
+
+/// Rejects images without pixels, which encoders either panic on or write as invalid files
+#[cfg_attr(
+    not(any(
+        feature = "avif",
+        feature = "gif",
+        feature = "mozjpeg",
+        feature = "oxipng",
+        feature = "webp"
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn check_dimensions(
+    encoder: &str,
+    image: &zune_image::image::Image,
+) -> Result<(), zune_image::errors::ImageErrors> {
+    let (width, height) = image.dimensions();
+
+    if width == 0 || height == 0 {
+        return Err(zune_image::errors::ImageErrors::EncodeErrors(
+            zune_image::errors::ImgEncodeErrors::ImageEncodeErrors(format!(
+                "{encoder} can't encode {width}x{height} image, both dimensions must be non-zero"
+            )),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -4,6 +4,8 @@ use mozjpeg::qtable::QTable;
 use zune_core::{bit_depth::BitDepth, bytestream::ZByteWriterTrait, colorspace::ColorSpace};
 use zune_image::{codecs::ImageFormat, errors::ImageErrors, image::Image, traits::EncoderTrait};
 
+use crate::codecs::{check_dimensions, OutputFormat};
+
 /// Advanced options for MozJpeg encoding
 pub struct MozJpegOptions {
     /// Quality, values 60-80 are recommended. `1..=100`
@@ -12,6 +14,12 @@ pub struct MozJpegOptions {
     pub progressive: bool,
     /// Set to false to make files larger for no reason
     pub optimize_coding: bool,
+    /// Start from libjpeg-turbo defaults, disabling trellis quantization and scan optimization
+    ///
+    /// Encodes much faster at the cost of larger files, combine with
+    /// `optimize_coding: false` to also skip Huffman table optimization.
+    /// Other options like `progressive` still apply on top of these defaults.
+    pub fastest: bool,
     /// If `1..=100` (non-zero), it will use MozJPEG's smoothing.
     pub smoothing: u8,
     /// Set color space of JPEG being written, different from input color space
@@ -24,6 +32,41 @@ pub struct MozJpegOptions {
     pub luma_qtable: Option<QTable>,
     /// Instead of quality setting, use a specific quantization table for color.
     pub chroma_qtable: Option<QTable>,
+    /// Copy EXIF metadata of the image into APP1 marker
+    pub preserve_exif: bool,
+    /// Copy ICC profile of the image into APP2 marker(s)
+    pub preserve_icc: bool,
+    /// Text written into COM marker
+    pub comment: Option<String>,
+}
+
+/// Scales every coefficient of a quantization table by `scale`
+///
+/// Unlike [`QTable::scaled`], the factor is applied directly instead of
+/// being derived from quality, `1.0` keeps the table as is. Resulting
+/// coefficients are clamped to `1..=255`.
+pub fn scaled_qtable(table: &QTable, scale: f32) -> QTable {
+    // invert the quality to scale mapping used by mozjpeg
+    let quality = if scale < 1. {
+        100. - 50. * scale.max(0.)
+    } else {
+        (50. / scale).max(0.01)
+    };
+
+    table.scaled(quality, quality)
+}
+
+/// Default MozJpeg quantization tables scaled for luma and chroma qualities separately
+///
+/// Returns `(luma, chroma)` tables to use as [`MozJpegOptions::luma_qtable`] and
+/// [`MozJpegOptions::chroma_qtable`], which take precedence over [`MozJpegOptions::quality`].
+pub fn component_qtables(luma_quality: f32, chroma_quality: f32) -> (QTable, QTable) {
+    use mozjpeg::qtable::NRobidoux;
+
+    (
+        NRobidoux.scaled(luma_quality, luma_quality),
+        NRobidoux.scaled(chroma_quality, chroma_quality),
+    )
 }
 
 /// A MozJpeg encoder
@@ -69,12 +112,16 @@ impl Default for MozJpegOptions {
             quality: 75.,
             progressive: true,
             optimize_coding: true,
+            fastest: false,
             smoothing: 0,
             color_space: mozjpeg::ColorSpace::JCS_YCbCr,
             trellis_multipass: false,
             chroma_subsample: None,
             luma_qtable: None,
             chroma_qtable: None,
+            preserve_exif: true,
+            preserve_icc: false,
+            comment: None,
         }
     }
 }
@@ -91,6 +138,35 @@ impl MozJpegEncoder {
     }
 }
 
+/// Writes ICC profile split into APP2 markers
+///
+/// `mozjpeg` has own `write_icc_profile`, but it numbers markers from 0,
+/// while ICC specification (and decoders) expects numbering to start from 1.
+fn write_icc_profile<W: io::Write>(comp: &mut mozjpeg::compress::CompressStarted<W>, icc: &[u8]) {
+    const ICC_MARKER: &[u8; 12] = b"ICC_PROFILE\0";
+    const MAX_DATA_BYTES_IN_MARKER: usize = 65533 - ICC_MARKER.len() - 2;
+
+    let chunks = icc.chunks(MAX_DATA_BYTES_IN_MARKER);
+    let num_chunks = chunks.len();
+
+    if num_chunks == 0 || num_chunks > u8::MAX as usize {
+        log::warn!(
+            "ICC profile of {} bytes can't be embedded, skipping",
+            icc.len()
+        );
+        return;
+    }
+
+    for (idx, chunk) in chunks.enumerate() {
+        let mut buf = Vec::with_capacity(ICC_MARKER.len() + 2 + chunk.len());
+        buf.extend_from_slice(ICC_MARKER);
+        buf.extend([idx as u8 + 1, num_chunks as u8]);
+        buf.extend_from_slice(chunk);
+
+        comp.write_marker(mozjpeg::Marker::APP(2), &buf);
+    }
+}
+
 impl EncoderTrait for MozJpegEncoder {
     fn name(&self) -> &'static str {
         "mozjpeg-encoder"
@@ -101,6 +177,8 @@ impl EncoderTrait for MozJpegEncoder {
         image: &Image,
         sink: T,
     ) -> Result<usize, ImageErrors> {
+        check_dimensions(self.name(), image)?;
+
         let (width, height) = image.dimensions();
         let data = &image.flatten_to_u8()[0];
 
@@ -124,6 +202,11 @@ impl EncoderTrait for MozJpegEncoder {
 
             let mut comp = mozjpeg::Compress::new(format);
 
+            // resets every parameter, so it has to come first
+            if self.options.fastest {
+                comp.set_fastest_defaults();
+            }
+
             comp.set_size(width, height);
             comp.set_quality(self.options.quality);
 
@@ -134,11 +217,7 @@ impl EncoderTrait for MozJpegEncoder {
             comp.set_optimize_coding(self.options.optimize_coding);
             comp.set_smoothing_factor(self.options.smoothing);
             comp.set_color_space(match format {
-                mozjpeg::ColorSpace::JCS_GRAYSCALE => {
-                    log::warn!("Input colorspace is GRAYSCALE, using GRAYSCALE as output");
-
-                    mozjpeg::ColorSpace::JCS_GRAYSCALE
-                }
+                mozjpeg::ColorSpace::JCS_GRAYSCALE => mozjpeg::ColorSpace::JCS_GRAYSCALE,
                 mozjpeg::ColorSpace::JCS_CMYK => {
                     log::warn!("Input colorspace is CMYK, using CMYK as output");
 
@@ -174,15 +253,14 @@ impl EncoderTrait for MozJpegEncoder {
             let mut comp = comp.start_compress(writer)?;
 
             #[cfg(feature = "metadata")]
-            {
+            if self.options.preserve_exif {
                 use exif::experimental::Writer;
 
                 if let Some(metadata) = &image.metadata().exif() {
                     let mut writer = Writer::new();
-                    // write first tags for exif
-                    let mut buf = std::io::Cursor::new(b"Exif\x00\x00".to_vec());
-                    // set buffer position to be bytes written, to ensure we don't overwrite anything
-                    buf.set_position(6);
+                    // writer computes IFD offsets from the start of the stream,
+                    // so TIFF data is written separately and prefixed afterwards
+                    let mut buf = std::io::Cursor::new(vec![]);
 
                     for metadatum in *metadata {
                         writer.push_field(metadatum);
@@ -190,13 +268,24 @@ impl EncoderTrait for MozJpegEncoder {
                     let result = writer.write(&mut buf, false);
                     if result.is_ok() {
                         // add the exif tag to APP1 segment
-                        comp.write_marker(mozjpeg::Marker::APP(1), buf.get_ref());
+                        let app1 = [b"Exif\x00\x00".as_slice(), buf.get_ref()].concat();
+                        comp.write_marker(mozjpeg::Marker::APP(1), &app1);
                     } else {
                         log::warn!("Writing exif failed {:?}", result);
                     }
                 }
             }
 
+            if self.options.preserve_icc {
+                if let Some(icc) = image.metadata().icc_chunk() {
+                    write_icc_profile(&mut comp, icc);
+                }
+            }
+
+            if let Some(comment) = &self.options.comment {
+                comp.write_marker(mozjpeg::Marker::COM, comment.as_bytes());
+            }
+
             comp.write_scanlines(data)?;
 
             Ok(comp.finish()?.bytes_written)
@@ -244,5 +333,15 @@ impl EncoderTrait for MozJpegEncoder {
     }
 }
 
+impl OutputFormat for MozJpegEncoder {
+    fn extension(&self) -> &'static str {
+        "jpg"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "image/jpeg"
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,6 +1,6 @@
 use std::io::Cursor;
 
-use zune_core::colorspace::ColorSpace;
+use zune_core::{bytestream::ZCursor, colorspace::ColorSpace, options::DecoderOptions};
 
 use crate::test_utils::*;
 
@@ -156,3 +156,122 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_preserve_icc() {
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    let mut encoder = MozJpegEncoder::new_with_options(MozJpegOptions {
+        preserve_icc: true,
+        comment: Some("rimage".to_string()),
+        ..Default::default()
+    });
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert!(buf.windows(6).any(|w| w == b"rimage"));
+
+    let decoded = Image::read(ZCursor::new(&buf), DecoderOptions::default()).unwrap();
+
+    assert_eq!(decoded.metadata().icc_chunk(), Some(&icc));
+}
+
+#[test]
+fn scale_qtable() {
+    use mozjpeg::qtable;
+
+    assert_eq!(
+        scaled_qtable(&qtable::Flat, 1.),
+        qtable::Flat.scaled(50., 50.)
+    );
+    assert_eq!(
+        scaled_qtable(&qtable::Flat, 0.5),
+        qtable::Flat.scaled(75., 75.)
+    );
+    assert_eq!(
+        scaled_qtable(&qtable::Flat, 2.),
+        qtable::Flat.scaled(25., 25.)
+    );
+}
+
+#[test]
+fn encode_scaled_qtable() {
+    use mozjpeg::qtable;
+
+    let image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let encode = |options| {
+        let mut encoder = MozJpegEncoder::new_with_options(options);
+        let mut buf = vec![];
+
+        encoder.encode(&image, &mut buf).unwrap();
+
+        buf
+    };
+
+    let quality_only = encode(MozJpegOptions::default());
+    let scaled = encode(MozJpegOptions {
+        luma_qtable: Some(scaled_qtable(&qtable::NRobidoux, 3.)),
+        chroma_qtable: Some(scaled_qtable(&qtable::NRobidoux, 3.)),
+        ..Default::default()
+    });
+
+    assert_ne!(quality_only, scaled);
+}
+
+#[test]
+fn encode_component_qtables() {
+    let image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let encode = |(luma, chroma)| {
+        let mut encoder = MozJpegEncoder::new_with_options(MozJpegOptions {
+            luma_qtable: Some(luma),
+            chroma_qtable: Some(chroma),
+            ..Default::default()
+        });
+        let mut buf = vec![];
+
+        encoder.encode(&image, &mut buf).unwrap();
+
+        buf
+    };
+
+    let uniform = encode(component_qtables(75., 75.));
+
+    assert_eq!(uniform, encode(component_qtables(75., 75.)));
+    assert_ne!(uniform, encode(component_qtables(75., 30.)));
+    assert_ne!(uniform, encode(component_qtables(30., 75.)));
+}
+
+#[test]
+fn encode_fastest() {
+    let image = create_test_image_u8(512, 512, ColorSpace::RGB);
+
+    let encode = |fastest: bool| {
+        let mut encoder = MozJpegEncoder::new_with_options(MozJpegOptions {
+            optimize_coding: !fastest,
+            fastest,
+            ..Default::default()
+        });
+
+        let mut buf = vec![];
+        encoder.encode(&image, &mut buf).unwrap();
+
+        buf
+    };
+
+    let default = encode(false);
+    let fastest = encode(true);
+
+    assert_ne!(default, fastest);
+
+    let decoded = Image::read(ZCursor::new(fastest), DecoderOptions::default()).unwrap();
+    assert_eq!(decoded.dimensions(), (512, 512));
+}
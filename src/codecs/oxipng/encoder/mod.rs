@@ -10,13 +10,38 @@ use zune_image::{
     traits::EncoderTrait,
 };
 
+use crate::codecs::{check_dimensions, OutputFormat};
+
 /// Alias to [`oxipng::Options`]
 pub type OxiPngOptions = oxipng::Options;
 
+/// Options encoding pixels as is, as fast as possible
+///
+/// Color type and bit depth are kept, rows are not filtered and the fastest
+/// compression level is used. Meant for rewriting metadata of PNG images,
+/// where spending time on optimization is not wanted.
+pub fn fast_options() -> OxiPngOptions {
+    let mut options = OxiPngOptions::from_preset(0);
+
+    options.bit_depth_reduction = false;
+    options.color_type_reduction = false;
+    options.palette_reduction = false;
+    options.grayscale_reduction = false;
+    options.filter = oxipng::indexset! {oxipng::RowFilter::None};
+    options.deflate = oxipng::Deflaters::Libdeflater { compression: 1 };
+
+    options
+}
+
 /// A OxiPNG encoder
+///
+/// Note that oxipng is buffer-based, so the whole optimized image
+/// is kept in memory before being written to the sink.
 #[derive(Default)]
 pub struct OxiPngEncoder {
     options: OxiPngOptions,
+    preserve_icc: bool,
+    bilevel: Option<u8>,
 }
 
 impl OxiPngEncoder {
@@ -26,8 +51,89 @@ impl OxiPngEncoder {
     }
     /// Create a new encoder with specified options
     pub fn new_with_options(options: OxiPngOptions) -> OxiPngEncoder {
-        OxiPngEncoder { options }
+        OxiPngEncoder {
+            options,
+            preserve_icc: false,
+            bilevel: None,
+        }
     }
+
+    /// Copy ICC profile of the image into iCCP chunk
+    #[must_use]
+    pub fn preserve_icc(mut self, preserve: bool) -> OxiPngEncoder {
+        self.preserve_icc = preserve;
+        self
+    }
+
+    /// Encode 1-bit grayscale image, pixels with luma of at least `threshold` become white
+    ///
+    /// Transparent pixels are composited over white, ICC profile is not embedded.
+    #[must_use]
+    pub fn bilevel(mut self, threshold: Option<u8>) -> OxiPngEncoder {
+        self.bilevel = threshold;
+        self
+    }
+}
+
+/// Variant of [`oxipng::RawImage::create_optimized_png`] writing the optimized file into `sink`
+///
+/// oxipng returns the whole file in a buffer, which is written to the sink as is
+/// instead of being copied into another buffer first. Returns the number of bytes written.
+fn create_optimized_png_into<T: ZByteWriterTrait>(
+    img: &oxipng::RawImage,
+    options: &OxiPngOptions,
+    sink: T,
+) -> Result<usize, ImageErrors> {
+    let result = img
+        .create_optimized_png(options)
+        .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+
+    let mut writer = ZWriter::new(sink);
+
+    writer.write_all(&result).map_err(|e| {
+        ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+    })?;
+
+    Ok(writer.bytes_written())
+}
+
+/// Thresholds the first frame of the image into packed 1-bit rows
+fn bilevel_pixels(image: &Image, threshold: u8) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let colorspace = image.colorspace();
+    let frame = &image.frames_ref()[0];
+
+    let data = match image.depth() {
+        BitDepth::Sixteen => frame
+            .flatten::<u16>(colorspace)
+            .into_iter()
+            .map(|v| (v >> 8) as u8)
+            .collect(),
+        _ => frame.flatten::<u8>(colorspace),
+    };
+
+    let components = colorspace.num_components();
+    let row_bytes = width.div_ceil(8);
+    let mut packed = vec![0; row_bytes * height];
+
+    for (idx, px) in data.chunks_exact(components).enumerate() {
+        let mut luma = match components {
+            1 | 2 => px[0] as u32,
+            _ => (299 * px[0] as u32 + 587 * px[1] as u32 + 114 * px[2] as u32) / 1000,
+        };
+
+        if colorspace.has_alpha() {
+            let alpha = px[components - 1] as u32;
+            luma = (luma * alpha + 255 * (255 - alpha)) / 255;
+        }
+
+        if luma >= threshold as u32 {
+            let (x, y) = (idx % width, idx / width);
+            packed[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+        }
+    }
+
+    packed
 }
 
 impl EncoderTrait for OxiPngEncoder {
@@ -40,59 +146,76 @@ impl EncoderTrait for OxiPngEncoder {
         image: &Image,
         sink: T,
     ) -> Result<usize, ImageErrors> {
-        let (width, height) = image.dimensions();
+        check_dimensions(self.name(), image)?;
 
-        // inlined `to_u8` method because its private
-        let colorspace = image.colorspace();
-        let data = if image.depth() == BitDepth::Eight {
-            image.flatten_frames::<u8>()
-        } else if image.depth() == BitDepth::Sixteen {
-            image
-                .frames_ref()
-                .iter()
-                .map(|z| z.u16_to_native_endian(colorspace))
-                .collect()
-        } else {
-            unreachable!()
-        }
-        .into_iter()
-        .next()
-        .unwrap();
+        let (width, height) = image.dimensions();
 
         #[allow(unused_mut)]
-        let mut img = oxipng::RawImage::new(
-            width as u32,
-            height as u32,
-            match image.colorspace() {
-                ColorSpace::Luma => oxipng::ColorType::Grayscale {
+        let mut img = match self.bilevel {
+            Some(threshold) => oxipng::RawImage::new(
+                width as u32,
+                height as u32,
+                oxipng::ColorType::Grayscale {
                     transparent_shade: None,
                 },
-                ColorSpace::RGB => oxipng::ColorType::RGB {
-                    transparent_color: None,
-                },
+                oxipng::BitDepth::One,
+                bilevel_pixels(image, threshold),
+            ),
+            None => {
+                // inlined `to_u8` method because its private,
+                // only the first frame is flattened as oxipng doesn't support animations
+                let colorspace = image.colorspace();
+                let frame = &image.frames_ref()[0];
+                let data = match image.depth() {
+                    BitDepth::Eight => frame.flatten::<u8>(colorspace),
+                    BitDepth::Sixteen => frame.u16_to_native_endian(colorspace),
+                    _ => unreachable!(),
+                };
 
-                ColorSpace::LumaA => oxipng::ColorType::GrayscaleAlpha,
-                ColorSpace::RGBA => oxipng::ColorType::RGBA,
+                oxipng::RawImage::new(
+                    width as u32,
+                    height as u32,
+                    match image.colorspace() {
+                        ColorSpace::Luma => oxipng::ColorType::Grayscale {
+                            transparent_shade: None,
+                        },
+                        ColorSpace::RGB => oxipng::ColorType::RGB {
+                            transparent_color: None,
+                        },
 
-                cs => {
-                    return Err(ImageErrors::EncodeErrors(
-                        ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
-                    ))
-                }
-            },
-            match image.depth() {
-                BitDepth::Eight => oxipng::BitDepth::Eight,
-                BitDepth::Sixteen => oxipng::BitDepth::Sixteen,
-                d => {
-                    return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
-                        format!("{d:?} is not supported"),
-                    )))
-                }
-            },
-            data,
-        )
+                        ColorSpace::LumaA => oxipng::ColorType::GrayscaleAlpha,
+                        ColorSpace::RGBA => oxipng::ColorType::RGBA,
+
+                        cs => {
+                            return Err(ImageErrors::EncodeErrors(
+                                ImgEncodeErrors::UnsupportedColorspace(
+                                    cs,
+                                    self.supported_colorspaces(),
+                                ),
+                            ))
+                        }
+                    },
+                    match image.depth() {
+                        BitDepth::Eight => oxipng::BitDepth::Eight,
+                        BitDepth::Sixteen => oxipng::BitDepth::Sixteen,
+                        d => {
+                            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                                format!("{d:?} is not supported"),
+                            )))
+                        }
+                    },
+                    data,
+                )
+            }
+        }
         .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
 
+        if self.preserve_icc && self.bilevel.is_none() {
+            if let Some(icc) = image.metadata().icc_chunk() {
+                img.add_icc_profile(icc);
+            }
+        }
+
         #[cfg(feature = "metadata")]
         {
             use exif::experimental::Writer;
@@ -114,17 +237,7 @@ impl EncoderTrait for OxiPngEncoder {
             }
         }
 
-        let mut writer = ZWriter::new(sink);
-
-        let result = img
-            .create_optimized_png(&self.options)
-            .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
-
-        writer.write(&result).map_err(|e| {
-            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
-        })?;
-
-        Ok(writer.bytes_written())
+        create_optimized_png_into(&img, &self.options, sink)
     }
 
     fn supported_colorspaces(&self) -> &'static [ColorSpace] {
@@ -152,5 +265,15 @@ impl EncoderTrait for OxiPngEncoder {
     }
 }
 
+impl OutputFormat for OxiPngEncoder {
+    fn extension(&self) -> &'static str {
+        "png"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "image/png"
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,6 +1,6 @@
 use std::io::Cursor;
 
-use zune_core::colorspace::ColorSpace;
+use zune_core::{bytestream::ZCursor, colorspace::ColorSpace, options::DecoderOptions};
 
 use crate::test_utils::*;
 
@@ -156,3 +156,192 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_matches_full_buffer() {
+    let image = create_test_image_u8(200, 200, ColorSpace::RGBA);
+    let mut encoder = OxiPngEncoder::new();
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), buf.len());
+
+    let expected = oxipng::RawImage::new(
+        200,
+        200,
+        oxipng::ColorType::RGBA,
+        oxipng::BitDepth::Eight,
+        image.flatten_frames::<u8>().into_iter().next().unwrap(),
+    )
+    .unwrap()
+    .create_optimized_png(&OxiPngOptions::default())
+    .unwrap();
+
+    assert_eq!(buf, expected);
+}
+
+#[test]
+fn create_optimized_png_into_matches() {
+    let image = create_test_image_u8(200, 200, ColorSpace::RGB);
+    let raw = oxipng::RawImage::new(
+        200,
+        200,
+        oxipng::ColorType::RGB {
+            transparent_color: None,
+        },
+        oxipng::BitDepth::Eight,
+        image.flatten_frames::<u8>().into_iter().next().unwrap(),
+    )
+    .unwrap();
+
+    for options in [OxiPngOptions::default(), fast_options()] {
+        let mut buf = vec![];
+
+        let result = create_optimized_png_into(&raw, &options, &mut buf);
+        dbg!(&result);
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), buf.len());
+        assert_eq!(buf, raw.create_optimized_png(&options).unwrap());
+    }
+}
+
+#[test]
+fn encode_preserve_icc() {
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    let mut encoder = OxiPngEncoder::new().preserve_icc(true);
+
+    let mut buf = vec![];
+
+    let result = encoder.encode(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let decoded = Image::read(ZCursor::new(&buf), DecoderOptions::default()).unwrap();
+
+    assert_eq!(decoded.metadata().icc_chunk(), Some(&icc));
+}
+
+#[test]
+fn encode_bilevel() {
+    // dark strokes on a slightly uneven paper
+    let pixels = (0..64 * 16)
+        .map(|idx| match idx % 64 {
+            8..=11 | 40..=43 => 20 + (idx % 7) as u8,
+            _ => 220 + (idx % 5) as u8,
+        })
+        .collect::<Vec<_>>();
+    let image = Image::from_u8(&pixels, 64, 16, ColorSpace::Luma);
+
+    let mut encoder = OxiPngEncoder::new().bilevel(Some(128));
+
+    let mut buf = vec![];
+    encoder.encode(&image, &mut buf).unwrap();
+
+    // bit depth field of IHDR chunk
+    assert_eq!(buf[24], 1);
+
+    let mut decoded = Image::read(ZCursor::new(&buf), DecoderOptions::default()).unwrap();
+    decoded.convert_color(ColorSpace::Luma).unwrap();
+
+    let expected = pixels
+        .iter()
+        .map(|&v| if v >= 128 { 255 } else { 0 })
+        .collect::<Vec<u8>>();
+
+    assert_eq!(decoded.flatten_to_u8()[0], expected);
+}
+
+#[test]
+fn encode_strip_chunks() {
+    let mut image = create_test_image_u8(64, 64, ColorSpace::RGB);
+    image
+        .metadata_mut()
+        .set_icc_chunk(std::fs::read("tests/files/icc/tinysrgb.icc").unwrap());
+    #[cfg(feature = "metadata")]
+    image
+        .metadata_mut()
+        .parse_raw_exif(&raw_exif(&[exif::Field {
+            tag: exif::Tag::Orientation,
+            ifd_num: exif::In::PRIMARY,
+            value: exif::Value::Short(vec![1]),
+        }]));
+
+    let encode = |strip| {
+        let mut encoder = OxiPngEncoder::new_with_options(OxiPngOptions {
+            strip,
+            ..Default::default()
+        })
+        .preserve_icc(true);
+
+        let mut buf = vec![];
+        encoder.encode(&image, &mut buf).unwrap();
+
+        [b"iCCP", b"eXIf"].map(|name| buf.windows(4).any(|chunk| chunk == name))
+    };
+
+    let exif = cfg!(feature = "metadata");
+
+    assert_eq!(encode(oxipng::StripChunks::None), [true, exif]);
+    assert_eq!(encode(oxipng::StripChunks::Safe), [true, false]);
+    assert_eq!(encode(oxipng::StripChunks::All), [false, false]);
+
+    // decoders don't keep text chunks, so they are added to the raw image directly
+    let text = |strip| {
+        let mut raw = oxipng::RawImage::new(
+            1,
+            1,
+            oxipng::ColorType::RGB {
+                transparent_color: None,
+            },
+            oxipng::BitDepth::Eight,
+            vec![0; 3],
+        )
+        .unwrap();
+        raw.add_png_chunk(*b"tEXt", b"Comment\0rimage".to_vec());
+        raw.add_png_chunk(*b"iTXt", b"Comment\0\0\0\0\0rimage".to_vec());
+
+        let buf = raw
+            .create_optimized_png(&OxiPngOptions {
+                strip,
+                ..Default::default()
+            })
+            .unwrap();
+
+        [b"tEXt", b"iTXt"].map(|name| buf.windows(4).any(|chunk| chunk == name))
+    };
+
+    assert_eq!(text(oxipng::StripChunks::None), [true, true]);
+    assert_eq!(text(oxipng::StripChunks::Safe), [false, false]);
+    assert_eq!(text(oxipng::StripChunks::All), [false, false]);
+}
+
+#[test]
+fn encode_fast() {
+    // noise with every channel in use, so no reduction applies
+    let pixels = (0..512 * 512 * 4)
+        .map(|idx: u32| (idx.wrapping_mul(2_654_435_761) >> 13) as u8 & 0xF0 | (idx % 16) as u8)
+        .collect::<Vec<_>>();
+    let image = Image::from_u8(&pixels, 512, 512, ColorSpace::RGBA);
+
+    let mut encoder = OxiPngEncoder::new_with_options(fast_options());
+    let mut fast = vec![];
+
+    encoder.encode(&image, &mut fast).unwrap();
+
+    // color type field of IHDR chunk stays RGBA
+    assert_eq!(fast[25], 6);
+
+    let decoded = Image::read(ZCursor::new(&fast), DecoderOptions::default()).unwrap();
+
+    assert_eq!(decoded.flatten_to_u8()[0], pixels);
+}
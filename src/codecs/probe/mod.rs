@@ -0,0 +1,84 @@
+use std::{fs::File, io::Read, path::Path};
+
+use zune_core::bytestream::ZCursor;
+use zune_image::codecs::{guess_format, ImageFormat};
+
+/// Number of leading bytes read by [`can_decode`] to recognize the format
+///
+/// AVIF lists compatible brands in its first box, which may take a few hundred bytes.
+const HEADER_SIZE: usize = 512;
+
+/// Checks if the file at `path` is in a format that can be decoded
+///
+/// Only the header of the file is read, so it still may fail to decode if it's corrupted.
+/// Files that can't be read are reported as not decodable.
+pub fn can_decode<P: AsRef<Path>>(path: P) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+
+    file.take(HEADER_SIZE as u64)
+        .read_to_end(&mut header)
+        .is_ok_and(|_| can_decode_bytes(&header))
+}
+
+/// Checks if `data` is in a format that can be decoded with enabled features
+///
+/// The format is recognized by its signature, so `data` may hold just the beginning of an image.
+pub fn can_decode_bytes(data: &[u8]) -> bool {
+    let riff = |fourcc: &[u8]| data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == fourcc;
+
+    #[cfg(feature = "avif")]
+    if libavif::is_avif(data) {
+        return true;
+    }
+
+    if cfg!(feature = "webp") && riff(b"WEBP") {
+        return true;
+    }
+
+    if cfg!(feature = "gif") && (data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")) {
+        return true;
+    }
+
+    if cfg!(feature = "tiff") && (data.starts_with(b"II*\0") || data.starts_with(b"MM\0*")) {
+        return true;
+    }
+
+    match guess_format(ZCursor::new(data)) {
+        Some((ImageFormat::PNG, _)) if cfg!(feature = "apng") => true,
+        Some((ImageFormat::Farbfeld, _)) if cfg!(feature = "farbfeld") => true,
+        Some((format, _)) => format.has_decoder(),
+        None => false,
+    }
+}
+
+/// Checks if an encoder with the given name is available with enabled features
+///
+/// Names are lowercase, like `mozjpeg`, `oxipng` or `webp` for rimage encoders
+/// and `jpeg`, `png` or `jpeg_xl` for `zune_image` ones.
+pub fn can_encode(codec: &str) -> bool {
+    let format = match codec {
+        "avif" => return cfg!(feature = "avif"),
+        "gif" => return cfg!(feature = "gif"),
+        "mozjpeg" => return cfg!(feature = "mozjpeg"),
+        "oxipng" => return cfg!(feature = "oxipng"),
+        "webp" => return cfg!(feature = "webp"),
+        "jpeg" => ImageFormat::JPEG,
+        "png" => ImageFormat::PNG,
+        "ppm" => ImageFormat::PPM,
+        "farbfeld" => ImageFormat::Farbfeld,
+        "qoi" => ImageFormat::QOI,
+        "jpeg_xl" => ImageFormat::JPEG_XL,
+        "hdr" => ImageFormat::HDR,
+        "bmp" => ImageFormat::BMP,
+        _ => return false,
+    };
+
+    format.has_encoder()
+}
+
+#[cfg(test)]
+mod tests;
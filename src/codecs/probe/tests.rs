@@ -0,0 +1,38 @@
+use super::*;
+
+#[test]
+fn decode_png() {
+    assert!(can_decode("tests/files/png/f1t.png"));
+    assert!(can_decode_bytes(
+        &std::fs::read("tests/files/png/f1t.png").unwrap()[..16]
+    ));
+}
+
+#[test]
+#[cfg(all(feature = "avif", feature = "webp", feature = "gif", feature = "tiff"))]
+fn decode_rimage_formats() {
+    for path in [
+        "tests/files/avif/f1t.avif",
+        "tests/files/webp/f1t.webp",
+        "tests/files/gif/f1t.gif",
+        "tests/files/tiff/f1t.tif",
+    ] {
+        assert!(can_decode(path), "{path}");
+    }
+}
+
+#[test]
+fn decode_unknown() {
+    assert!(!can_decode_bytes(b"NOTANIMAGEFORMAT"));
+    assert!(!can_decode_bytes(&[]));
+    assert!(!can_decode("tests/files/missing.png"));
+    assert!(!can_decode("Cargo.toml"));
+}
+
+#[test]
+fn encode() {
+    assert_eq!(can_encode("mozjpeg"), cfg!(feature = "mozjpeg"));
+    assert_eq!(can_encode("webp"), cfg!(feature = "webp"));
+    assert!(!can_encode("heic"));
+    assert!(!can_encode(""));
+}
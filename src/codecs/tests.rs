@@ -0,0 +1,66 @@
+use zune_core::colorspace::ColorSpace;
+use zune_image::{errors::ImageErrors, image::Image, traits::EncoderTrait};
+
+use crate::test_utils::create_test_image_u8;
+
+use super::*;
+
+#[test]
+fn output_formats() {
+    let formats: Vec<(Box<dyn OutputFormat>, &str, &str)> = vec![
+        #[cfg(feature = "avif")]
+        (Box::new(avif::AvifEncoder::new()), "avif", "image/avif"),
+        #[cfg(feature = "gif")]
+        (Box::new(gif::GifEncoder::new()), "gif", "image/gif"),
+        #[cfg(feature = "mozjpeg")]
+        (
+            Box::new(mozjpeg::MozJpegEncoder::new()),
+            "jpg",
+            "image/jpeg",
+        ),
+        #[cfg(feature = "oxipng")]
+        (Box::new(oxipng::OxiPngEncoder::new()), "png", "image/png"),
+        #[cfg(feature = "webp")]
+        (Box::new(webp::WebPEncoder::new()), "webp", "image/webp"),
+    ];
+
+    for (format, extension, mime_type) in formats {
+        assert_eq!(format.extension(), extension);
+        assert_eq!(format.mime_type(), mime_type);
+    }
+}
+
+#[test]
+fn encode_degenerate_dimensions() {
+    type Encode = Box<dyn Fn(&Image) -> Result<Vec<u8>, ImageErrors>>;
+
+    fn encoder<E: EncoderTrait + 'static>(new: fn() -> E) -> Encode {
+        Box::new(move |image| {
+            let mut buf = vec![];
+            new().encode(image, &mut buf)?;
+
+            Ok(buf)
+        })
+    }
+
+    let encoders: Vec<(&str, Encode)> = vec![
+        #[cfg(feature = "avif")]
+        ("avif", encoder(avif::AvifEncoder::new)),
+        #[cfg(feature = "gif")]
+        ("gif", encoder(gif::GifEncoder::new)),
+        #[cfg(feature = "mozjpeg")]
+        ("mozjpeg", encoder(mozjpeg::MozJpegEncoder::new)),
+        #[cfg(feature = "oxipng")]
+        ("oxipng", encoder(oxipng::OxiPngEncoder::new)),
+        #[cfg(feature = "webp")]
+        ("webp", encoder(webp::WebPEncoder::new)),
+    ];
+
+    for (name, encode) in encoders {
+        let buf = encode(&create_test_image_u8(1, 1, ColorSpace::RGBA)).unwrap();
+        assert!(!buf.is_empty(), "{name}");
+
+        let error = encode(&create_test_image_u8(0, 1, ColorSpace::RGBA)).unwrap_err();
+        assert!(format!("{error:?}").contains("0x1"), "{name}: {error:?}");
+    }
+}
@@ -1,7 +1,38 @@
 use std::io::{Read, Seek};
 
-use zune_core::colorspace::ColorSpace;
-use zune_image::{errors::ImageErrors, image::Image, traits::DecoderTrait};
+use tiff::decoder::{ChunkType, DecodingResult};
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{
+    channel::{Channel, ChannelErrors},
+    errors::ImageErrors,
+    frame::Frame,
+    image::Image,
+    traits::DecoderTrait,
+};
+
+/// Interleaved samples of consecutive full-width rows
+#[derive(Clone, Copy)]
+pub enum Rows<'a> {
+    /// Samples of up to 8 bits
+    U8(&'a [u8]),
+    /// Samples of up to 16 bits
+    U16(&'a [u16]),
+    /// Floating point samples
+    F32(&'a [f32]),
+}
+
+impl<'a> Rows<'a> {
+    fn from_result(result: &'a DecodingResult) -> Result<Self, ImageErrors> {
+        match result {
+            DecodingResult::U8(data) => Ok(Rows::U8(data)),
+            DecodingResult::U16(data) => Ok(Rows::U16(data)),
+            DecodingResult::F32(data) => Ok(Rows::F32(data)),
+            _ => Err(ImageErrors::ImageDecodeErrors(
+                "Tiff Data format not supported".to_string(),
+            )),
+        }
+    }
+}
 
 /// A Tiff decoder
 pub struct TiffDecoder<R: Read + Seek> {
@@ -23,13 +54,86 @@ impl<R: Read + Seek> TiffDecoder<R> {
             colorspace: ColorSpace::Unknown,
         })
     }
-}
 
-impl<R> DecoderTrait for TiffDecoder<R>
-where
-    R: Read + Seek,
-{
-    fn decode(&mut self) -> Result<Image, ImageErrors> {
+    /// Decodes the image one band of rows at a time
+    ///
+    /// `on_rows` receives the index of the first row in the band and its samples.
+    /// A band is a single strip, or a single row of tiles, so only that much
+    /// of the image is held in memory at once.
+    pub fn decode_rows(
+        &mut self,
+        mut on_rows: impl FnMut(usize, Rows) -> Result<(), ImageErrors>,
+    ) -> Result<(), ImageErrors> {
+        let (width, height) = self.read_headers()?;
+
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let (chunk_width, chunk_height) = self.inner.chunk_dimensions();
+        let (chunk_width, chunk_height) = (
+            (chunk_width as usize).min(width),
+            (chunk_height as usize).min(height),
+        );
+
+        if chunk_width == 0 || chunk_height == 0 {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "Invalid TIFF chunk dimensions".to_string(),
+            ));
+        }
+
+        let chunks_across = width.div_ceil(chunk_width);
+        let chunks_down = height.div_ceil(chunk_height);
+
+        for band in 0..chunks_down {
+            let first_row = band * chunk_height;
+
+            if self.inner.get_chunk_type() == ChunkType::Strip {
+                let strip = self.read_chunk(band as u32)?;
+                on_rows(first_row, Rows::from_result(&strip)?)?;
+                continue;
+            }
+
+            let mut rows: Option<DecodingResult> = None;
+
+            for across in 0..chunks_across {
+                let idx = (band * chunks_across + across) as u32;
+                let tile = self.read_chunk(idx)?;
+                let (tile_width, tile_height) = self.inner.chunk_data_dimensions(idx);
+                let (tile_width, tile_height) = (tile_width as usize, tile_height as usize);
+                let samples = tile_samples(&tile) / (tile_width * tile_height).max(1);
+
+                let rows =
+                    rows.get_or_insert_with(|| empty_like(&tile, width * tile_height * samples));
+
+                let x = across * chunk_width * samples;
+                match (rows, &tile) {
+                    (DecodingResult::U8(rows), DecodingResult::U8(tile)) => {
+                        copy_tile(rows, tile, width * samples, x, tile_width * samples)
+                    }
+                    (DecodingResult::U16(rows), DecodingResult::U16(tile)) => {
+                        copy_tile(rows, tile, width * samples, x, tile_width * samples)
+                    }
+                    (DecodingResult::F32(rows), DecodingResult::F32(tile)) => {
+                        copy_tile(rows, tile, width * samples, x, tile_width * samples)
+                    }
+                    _ => {
+                        return Err(ImageErrors::ImageDecodeErrors(
+                            "Tiff Data format not supported".to_string(),
+                        ))
+                    }
+                }
+            }
+
+            if let Some(rows) = rows {
+                on_rows(first_row, Rows::from_result(&rows)?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_headers(&mut self) -> Result<(usize, usize), ImageErrors> {
         let (width, height) = self.inner.dimensions().map_err(|e| {
             ImageErrors::ImageDecodeErrors(format!("Unable to read dimensions - {}", e))
         })?;
@@ -55,26 +159,125 @@ where
 
         self.colorspace = colorspace;
 
-        let result = self.inner.read_image().map_err(|e| {
+        Ok((width, height))
+    }
+
+    fn read_chunk(&mut self, idx: u32) -> Result<DecodingResult, ImageErrors> {
+        self.inner.read_chunk(idx).map_err(|e| {
             ImageErrors::ImageDecodeErrors(format!("Unable to decode TIFF file - {}", e))
-        })?;
+        })
+    }
+}
 
-        match result {
-            tiff::decoder::DecodingResult::U8(data) => {
-                Ok(Image::from_u8(&data, width, height, colorspace))
-            }
-            tiff::decoder::DecodingResult::U16(data) => {
-                Ok(Image::from_u16(&data, width, height, colorspace))
+fn tile_samples(result: &DecodingResult) -> usize {
+    match result {
+        DecodingResult::U8(data) => data.len(),
+        DecodingResult::U16(data) => data.len(),
+        DecodingResult::F32(data) => data.len(),
+        _ => 0,
+    }
+}
+
+fn empty_like(result: &DecodingResult, len: usize) -> DecodingResult {
+    match result {
+        DecodingResult::U16(_) => DecodingResult::U16(vec![0; len]),
+        DecodingResult::F32(_) => DecodingResult::F32(vec![0.0; len]),
+        _ => DecodingResult::U8(vec![0; len]),
+    }
+}
+
+/// Copies the rows of a tile into a band of full-width rows, starting at sample `x`
+fn copy_tile<T: Copy>(rows: &mut [T], tile: &[T], stride: usize, x: usize, tile_stride: usize) {
+    for (dst, src) in rows
+        .chunks_exact_mut(stride)
+        .zip(tile.chunks_exact(tile_stride))
+    {
+        dst[x..x + tile_stride].copy_from_slice(src);
+    }
+}
+
+/// Splits interleaved samples of a band into the planar `channels`, starting at pixel `offset`
+fn deinterleave(channels: &mut [Channel], rows: Rows, offset: usize) -> Result<(), ChannelErrors> {
+    let components = channels.len();
+
+    for (idx, channel) in channels.iter_mut().enumerate() {
+        match rows {
+            Rows::U8(data) => scatter(channel.reinterpret_as_mut()?, data, offset, idx, components),
+            Rows::U16(data) => {
+                scatter(channel.reinterpret_as_mut()?, data, offset, idx, components)
             }
-            tiff::decoder::DecodingResult::F32(data) => {
-                Ok(Image::from_f32(&data, width, height, colorspace))
+            Rows::F32(data) => {
+                scatter(channel.reinterpret_as_mut()?, data, offset, idx, components)
             }
-            _ => Err(ImageErrors::ImageDecodeErrors(
-                "Tiff Data format not supported".to_string(),
-            )),
         }
     }
 
+    Ok(())
+}
+
+fn scatter<T: Copy>(channel: &mut [T], data: &[T], offset: usize, idx: usize, components: usize) {
+    let pixels = data.len() / components;
+
+    for (dst, src) in channel[offset..offset + pixels]
+        .iter_mut()
+        .zip(data.iter().skip(idx).step_by(components))
+    {
+        *dst = *src;
+    }
+}
+
+impl<R> DecoderTrait for TiffDecoder<R>
+where
+    R: Read + Seek,
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let (width, height) = self.read_headers()?;
+        let components = self.colorspace.num_components();
+
+        if components == 0 {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "Tiff colorspace not supported".to_string(),
+            ));
+        }
+
+        let mut planes: Option<(BitDepth, Vec<Channel>)> = None;
+
+        self.decode_rows(|first_row, rows| {
+            let offset = first_row * width;
+            let len = width * height;
+            let depth = match rows {
+                Rows::U8(_) => BitDepth::Eight,
+                Rows::U16(_) => BitDepth::Sixteen,
+                Rows::F32(_) => BitDepth::Float32,
+            };
+
+            let (_, channels) = planes.get_or_insert_with(|| {
+                let channels = (0..components)
+                    .map(|_| Channel::new_with_bit_type(len * depth.size_of(), depth.bit_type()))
+                    .collect();
+
+                (depth, channels)
+            });
+
+            deinterleave(channels, rows, offset).map_err(ImageErrors::from)
+        })?;
+
+        let (depth, channels) = planes.unwrap_or_else(|| {
+            (
+                BitDepth::Eight,
+                (0..components).map(|_| Channel::new::<u8>()).collect(),
+            )
+        });
+
+        Ok(Image::new_frames(
+            vec![Frame::new(channels)],
+            depth,
+            width,
+            height,
+            self.colorspace,
+        ))
+    }
+
     fn dimensions(&self) -> Option<(usize, usize)> {
         self.dimensions
     }
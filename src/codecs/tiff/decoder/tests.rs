@@ -1,4 +1,4 @@
-use std::fs::File;
+use std::{fs::File, io::Cursor};
 
 use super::*;
 
@@ -13,3 +13,51 @@ fn decode() {
     assert_eq!(img.dimensions(), (48, 80));
     assert_eq!(img.colorspace(), ColorSpace::RGB);
 }
+
+#[test]
+fn decode_strips() {
+    let (width, height) = (1024, 1024);
+    let pixels = (0..width * height)
+        .map(|i| ((i % width) ^ (i / width)) as u16)
+        .collect::<Vec<_>>();
+
+    let mut buf = Cursor::new(vec![]);
+    let mut encoder = tiff::encoder::TiffEncoder::new(&mut buf).unwrap();
+    let mut image = encoder
+        .new_image::<tiff::encoder::colortype::Gray16>(width as u32, height as u32)
+        .unwrap();
+    image.rows_per_strip(16).unwrap();
+    image.write_data(&pixels).unwrap();
+
+    let mut decoder = TiffDecoder::try_new(Cursor::new(buf.get_ref())).unwrap();
+
+    let mut next_row = 0;
+    decoder
+        .decode_rows(|first_row, rows| {
+            let Rows::U16(data) = rows else {
+                panic!("expected 16-bit samples");
+            };
+
+            assert_eq!(first_row, next_row);
+            assert!(data.len() <= 16 * width);
+            assert_eq!(data, &pixels[first_row * width..][..data.len()]);
+
+            next_row += data.len() / width;
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(next_row, height);
+
+    let decoder = TiffDecoder::try_new(Cursor::new(buf.get_ref())).unwrap();
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (width, height));
+    assert_eq!(img.colorspace(), ColorSpace::Luma);
+    assert_eq!(img.depth(), BitDepth::Sixteen);
+    assert_eq!(
+        img.frames_ref()[0].channels_ref(ColorSpace::Luma, false)[0]
+            .reinterpret_as::<u16>()
+            .unwrap(),
+        pixels
+    );
+}
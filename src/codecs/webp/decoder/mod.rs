@@ -5,8 +5,13 @@ use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
 use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
 
 /// A WebP decoder
+///
+/// WebP stores 8 bits per channel in both lossy and lossless bitstreams,
+/// so decoded images are always [`BitDepth::Eight`]. Frames are decoded as
+/// straight (non premultiplied) RGBA, alpha of lossless images is kept exactly.
 pub struct WebPDecoder<R: Read> {
     inner: DecodeAnimImage,
+    delays: Vec<u16>,
     phantom: PhantomData<R>,
 }
 
@@ -21,9 +26,18 @@ impl<R: Read> WebPDecoder<R> {
 
         Ok(WebPDecoder {
             inner: img,
+            delays: vec![],
             phantom: PhantomData,
         })
     }
+
+    /// Delays of decoded frames in milliseconds
+    ///
+    /// Filled by [`DecoderTrait::decode`], since zune-image doesn't expose
+    /// delays stored in frames.
+    pub fn delays(&self) -> &[u16] {
+        &self.delays
+    }
 }
 
 impl<R> DecoderTrait for WebPDecoder<R>
@@ -43,6 +57,19 @@ where
             })
             .collect::<Vec<_>>();
 
+        // frames are timestamped with the time they end at
+        let mut end = 0;
+        self.delays = self
+            .inner
+            .into_iter()
+            .map(|frame| {
+                let delay = frame.get_time_ms().saturating_sub(end);
+                end = frame.get_time_ms();
+
+                u16::try_from(delay.max(0)).unwrap_or(u16::MAX)
+            })
+            .collect();
+
         Ok(Image::new_frames(
             frames,
             BitDepth::Eight,
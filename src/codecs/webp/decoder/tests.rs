@@ -1,5 +1,9 @@
 use std::fs::File;
 
+use zune_image::traits::EncoderTrait;
+
+use crate::codecs::webp::{WebPEncoder, WebPOptions};
+
 use super::*;
 
 #[test]
@@ -13,3 +17,36 @@ fn decode() {
     assert_eq!(img.dimensions(), (48, 80));
     assert_eq!(img.colorspace(), ColorSpace::RGBA);
 }
+
+#[test]
+fn decode_lossless_alpha() {
+    let pixels = (0..64 * 32)
+        .flat_map(|idx| {
+            let (x, y) = (idx % 64, idx / 64);
+
+            [(x * 4) as u8, (y * 8) as u8, 200, (x * 4 + 1) as u8]
+        })
+        .collect::<Vec<u8>>();
+    let image = Image::from_u8(&pixels, 64, 32, ColorSpace::RGBA);
+
+    let mut options = WebPOptions::new().unwrap();
+    options.lossless = 1;
+    options.exact = 1;
+
+    let mut buf = vec![];
+    WebPEncoder::new_with_options(options)
+        .encode(&image, &mut buf)
+        .unwrap();
+
+    let decoder = WebPDecoder::try_new(buf.as_slice()).unwrap();
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (64, 32));
+    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+    assert_eq!(img.depth(), BitDepth::Eight);
+
+    let decoded = &img.flatten_to_u8()[0];
+
+    assert!(decoded.chunks_exact(4).all(|px| px[3] != 0));
+    assert_eq!(decoded, &pixels);
+}
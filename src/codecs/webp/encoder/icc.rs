@@ -0,0 +1,107 @@
+use zune_image::errors::{ImageErrors, ImgEncodeErrors};
+
+/// ICC profile flag of `VP8X` chunk
+const ICC_FLAG: u8 = 0x20;
+/// Alpha flag of `VP8X` chunk
+const ALPHA_FLAG: u8 = 0x10;
+
+fn error(msg: &str) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!(
+        "unable to embed icc profile: {msg}"
+    )))
+}
+
+/// RIFF chunk fourcc and its payload
+type Chunk<'a> = ([u8; 4], &'a [u8]);
+
+/// Splits RIFF payload into chunks
+fn chunks(mut data: &[u8]) -> Result<Vec<Chunk<'_>>, ImageErrors> {
+    let mut chunks = vec![];
+
+    while !data.is_empty() {
+        if data.len() < 8 {
+            return Err(error("truncated chunk header"));
+        }
+
+        let fourcc = data[..4].try_into().unwrap();
+        let size = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+
+        let payload = data
+            .get(8..8 + size)
+            .ok_or_else(|| error("invalid chunk size"))?;
+
+        chunks.push((fourcc, payload));
+        data = &data[(8 + size + size % 2).min(data.len())..];
+    }
+
+    Ok(chunks)
+}
+
+fn write_chunk(out: &mut Vec<u8>, fourcc: [u8; 4], payload: &[u8]) {
+    out.extend_from_slice(&fourcc);
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(payload);
+
+    if payload.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+/// Builds `VP8X` chunk payload for a simple (`VP8 `/`VP8L`) WebP file
+fn vp8x(fourcc: [u8; 4], payload: &[u8], (width, height): (usize, usize)) -> Vec<u8> {
+    // lossless bitstream stores `alpha_is_used` bit right after 14-bit dimensions
+    let alpha = &fourcc == b"VP8L"
+        && payload
+            .get(1..5)
+            .is_some_and(|header| u32::from_le_bytes(header.try_into().unwrap()) >> 28 & 1 == 1);
+
+    let mut vp8x = vec![ICC_FLAG | if alpha { ALPHA_FLAG } else { 0 }, 0, 0, 0];
+    vp8x.extend_from_slice(&(width as u32 - 1).to_le_bytes()[..3]);
+    vp8x.extend_from_slice(&(height as u32 - 1).to_le_bytes()[..3]);
+
+    vp8x
+}
+
+/// Embeds ICC profile into WebP file as `ICCP` chunk
+///
+/// Simple files are converted into the extended format, as only it can carry the profile.
+pub(super) fn embed_icc(
+    webp: &[u8],
+    icc: &[u8],
+    dimensions: (usize, usize),
+) -> Result<Vec<u8>, ImageErrors> {
+    if webp.len() < 12 || &webp[..4] != b"RIFF" || &webp[8..12] != b"WEBP" {
+        return Err(error("not a webp file"));
+    }
+
+    let chunks = chunks(&webp[12..])?;
+
+    let mut out = Vec::with_capacity(webp.len() + icc.len() + 32);
+    out.extend_from_slice(b"RIFF\0\0\0\0WEBP");
+
+    match chunks.first() {
+        Some((fourcc, payload)) if fourcc == b"VP8X" => {
+            let mut vp8x = payload.to_vec();
+            vp8x[0] |= ICC_FLAG;
+
+            write_chunk(&mut out, *fourcc, &vp8x);
+        }
+        Some((fourcc, payload)) => {
+            write_chunk(&mut out, *b"VP8X", &vp8x(*fourcc, payload, dimensions))
+        }
+        None => return Err(error("empty webp file")),
+    }
+
+    write_chunk(&mut out, *b"ICCP", icc);
+
+    chunks
+        .iter()
+        .skip(usize::from(chunks[0].0 == *b"VP8X"))
+        .filter(|(fourcc, _)| fourcc != b"ICCP")
+        .for_each(|(fourcc, payload)| write_chunk(&mut out, *fourcc, payload));
+
+    let riff_size = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+    Ok(out)
+}
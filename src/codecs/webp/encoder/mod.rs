@@ -10,18 +10,24 @@ use zune_image::{
     traits::EncoderTrait,
 };
 
+use crate::codecs::{check_dimensions, OutputFormat};
+
+mod icc;
+
 /// Alias to [`webp::WebPConfig`]
 pub type WebPOptions = webp::WebPConfig;
 
 /// A WebP encoder
 pub struct WebPEncoder {
     options: WebPOptions,
+    preserve_icc: bool,
 }
 
 impl Default for WebPEncoder {
     fn default() -> Self {
         Self {
             options: WebPOptions::new().unwrap(),
+            preserve_icc: false,
         }
     }
 }
@@ -34,10 +40,79 @@ impl WebPEncoder {
 
     /// Create a new encoder with specified options
     pub fn new_with_options(options: WebPOptions) -> WebPEncoder {
-        WebPEncoder { options }
+        WebPEncoder {
+            options,
+            preserve_icc: false,
+        }
+    }
+
+    /// Copy ICC profile of the image into ICCP chunk
+    #[must_use]
+    pub fn preserve_icc(mut self, preserve: bool) -> WebPEncoder {
+        self.preserve_icc = preserve;
+        self
+    }
+
+    /// Encodes already flattened animation frames
+    ///
+    /// libwebp's animation encoder optimizes each frame against the previous one,
+    /// so frames are always fed to it sequentially.
+    fn encode_animated(&self, image: &Image, frames: &[Vec<u8>]) -> Result<Vec<u8>, ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        let mut encoder = webp::AnimEncoder::new(width as u32, height as u32, &self.options);
+
+        encoder.set_bgcolor([0, 0, 0, 0]);
+        encoder.set_loop_count(frames.len() as i32);
+
+        frames.iter().try_for_each(|frame| {
+            // TODO: add frame timestamp
+
+            let frame = match image.colorspace() {
+                ColorSpace::RGB => {
+                    webp::AnimFrame::from_rgb(frame, width as u32, height as u32, 500)
+                }
+                ColorSpace::RGBA => {
+                    webp::AnimFrame::from_rgba(frame, width as u32, height as u32, 500)
+                }
+                cs => {
+                    return Err(ImageErrors::EncodeErrors(
+                        ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
+                    ))
+                }
+            };
+
+            encoder.add_frame(frame);
+
+            Ok(())
+        })?;
+
+        Ok(encoder.encode().to_vec())
     }
 }
 
+/// Flattens every frame of the image into interleaved `u8` pixels
+///
+/// When `parallel` is set and the `threads` feature is enabled, frames are
+/// flattened concurrently, otherwise this falls back to [`Image::flatten_to_u8`].
+#[cfg_attr(not(feature = "threads"), allow(unused_variables))]
+fn flatten_frames(image: &Image, parallel: bool) -> Vec<Vec<u8>> {
+    #[cfg(feature = "threads")]
+    if parallel && image.depth() == BitDepth::Eight {
+        use rayon::prelude::*;
+
+        let colorspace = image.colorspace();
+
+        return image
+            .frames_ref()
+            .par_iter()
+            .map(|frame| frame.flatten::<u8>(colorspace))
+            .collect();
+    }
+
+    image.flatten_to_u8()
+}
+
 impl EncoderTrait for WebPEncoder {
     fn name(&self) -> &'static str {
         "webp"
@@ -48,50 +123,24 @@ impl EncoderTrait for WebPEncoder {
         image: &Image,
         sink: T,
     ) -> Result<usize, ImageErrors> {
-        let (width, height) = image.dimensions();
-
-        let mut writer = ZWriter::new(sink);
-
-        if image.is_animated() {
-            let frames = image.flatten_to_u8();
-
-            let mut encoder = webp::AnimEncoder::new(width as u32, height as u32, &self.options);
-
-            encoder.set_bgcolor([0, 0, 0, 0]);
-            encoder.set_loop_count(frames.len() as i32);
-
-            frames.iter().try_for_each(|frame| {
-                // TODO: add frame timestamp
+        check_dimensions(self.name(), image)?;
 
-                let frame = match image.colorspace() {
-                    ColorSpace::RGB => {
-                        webp::AnimFrame::from_rgb(frame, width as u32, height as u32, 500)
-                    }
-                    ColorSpace::RGBA => {
-                        webp::AnimFrame::from_rgba(frame, width as u32, height as u32, 500)
-                    }
-                    cs => {
-                        return Err(ImageErrors::EncodeErrors(
-                            ImgEncodeErrors::UnsupportedColorspace(
-                                cs,
-                                self.supported_colorspaces(),
-                            ),
-                        ))
-                    }
-                };
+        if image.colorspace() == ColorSpace::LumaA {
+            // libwebp has no grayscale input, expand to RGBA to keep the alpha channel
+            let mut image = image.clone();
+            image.convert_color(ColorSpace::RGBA)?;
 
-                encoder.add_frame(frame);
+            return self.encode_inner(&image, sink);
+        }
 
-                Ok(())
-            })?;
+        let (width, height) = image.dimensions();
 
-            let res = encoder.encode();
+        let mut writer = ZWriter::new(sink);
 
-            writer.write(&res).map_err(|e| {
-                ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
-            })?;
+        let res = if image.is_animated() {
+            let frames = flatten_frames(image, cfg!(feature = "threads"));
 
-            Ok(writer.bytes_written())
+            self.encode_animated(image, &frames)?
         } else {
             let data = &image.flatten_to_u8()[0];
 
@@ -105,20 +154,28 @@ impl EncoderTrait for WebPEncoder {
                 }
             };
 
-            let res = encoder.encode_advanced(&self.options).map_err(|e| {
-                ImgEncodeErrors::ImageEncodeErrors(format!("webp encoding failed: {e:?}"))
-            })?;
+            encoder
+                .encode_advanced(&self.options)
+                .map_err(|e| {
+                    ImgEncodeErrors::ImageEncodeErrors(format!("webp encoding failed: {e:?}"))
+                })?
+                .to_vec()
+        };
 
-            writer.write(&res).map_err(|e| {
-                ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
-            })?;
+        let res = match image.metadata().icc_chunk().filter(|_| self.preserve_icc) {
+            Some(icc) => icc::embed_icc(&res, icc, (width, height))?,
+            None => res,
+        };
 
-            Ok(writer.bytes_written())
-        }
+        writer.write(&res).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
     }
 
     fn supported_colorspaces(&self) -> &'static [ColorSpace] {
-        &[ColorSpace::RGB, ColorSpace::RGBA]
+        &[ColorSpace::RGB, ColorSpace::RGBA, ColorSpace::LumaA]
     }
 
     // TODO: update when new version with custom image format is released.
@@ -139,5 +196,15 @@ impl EncoderTrait for WebPEncoder {
     }
 }
 
+impl OutputFormat for WebPEncoder {
+    fn extension(&self) -> &'static str {
+        "webp"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "image/webp"
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,6 +1,7 @@
 use std::io::Cursor;
 
 use zune_core::colorspace::ColorSpace;
+use zune_image::frame::Frame;
 
 use crate::test_utils::*;
 
@@ -156,3 +157,178 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_luma_alpha() {
+    let image = create_test_image_u8(200, 200, ColorSpace::LumaA);
+    let mut encoder = WebPEncoder::new();
+
+    let mut buf = vec![];
+
+    let result = encoder.encode_inner(&image, &mut buf);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let decoder = crate::codecs::webp::WebPDecoder::try_new(buf.as_slice()).unwrap();
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+}
+
+#[test]
+fn encode_animated_parallel() {
+    let (width, height) = (64, 64);
+
+    let frames = (0..20)
+        .map(|idx| {
+            let pixels = (0..width * height * 3)
+                .map(|i| ((i + idx * 7) % 256) as u8)
+                .collect::<Vec<_>>();
+
+            Frame::from_u8(&pixels, ColorSpace::RGB, idx, 1)
+        })
+        .collect::<Vec<_>>();
+
+    let image = Image::new_frames(frames, BitDepth::Eight, width, height, ColorSpace::RGB);
+    let encoder = WebPEncoder::new();
+
+    let sequential = flatten_frames(&image, false);
+    let parallel = flatten_frames(&image, true);
+
+    assert_eq!(sequential, parallel);
+
+    assert_eq!(
+        encoder.encode_animated(&image, &sequential).unwrap(),
+        encoder.encode_animated(&image, &parallel).unwrap()
+    );
+}
+
+#[test]
+fn encode_preserve_icc() {
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    for (colorspace, lossless) in [
+        (ColorSpace::RGB, false),
+        (ColorSpace::RGBA, false),
+        (ColorSpace::RGBA, true),
+    ] {
+        let mut image = create_test_image_u8(200, 200, colorspace);
+        image.metadata_mut().set_icc_chunk(icc.clone());
+
+        let mut options = WebPOptions::new().unwrap();
+        options.lossless = lossless as i32;
+
+        let mut encoder = WebPEncoder::new_with_options(options).preserve_icc(true);
+
+        let mut buf = vec![];
+
+        let result = encoder.encode(&image, &mut buf);
+        dbg!(&result);
+
+        assert!(result.is_ok());
+
+        let iccp = [b"ICCP".as_slice(), &(icc.len() as u32).to_le_bytes(), &icc].concat();
+
+        assert!(buf.windows(iccp.len()).any(|w| w == iccp));
+        assert_eq!(&buf[12..16], b"VP8X");
+        assert_eq!(buf[20] & 0x20, 0x20);
+
+        let decoded = webp::Decoder::new(&buf).decode().unwrap();
+
+        assert_eq!((decoded.width(), decoded.height()), (200, 200));
+        assert_eq!(decoded.is_alpha(), colorspace == ColorSpace::RGBA);
+    }
+}
+
+#[cfg(feature = "icc")]
+#[test]
+fn encode_display_p3() {
+    use lcms2::{CIExyY, CIExyYTRIPLE, Profile, ToneCurve};
+
+    use zune_image::traits::OperationsTrait;
+
+    use crate::operations::icc::ApplySRGB;
+
+    let xy = |x, y| CIExyY { x, y, Y: 1.0 };
+    let srgb_curve =
+        ToneCurve::new_parametric(4, &[2.4, 1. / 1.055, 0.055 / 1.055, 1. / 12.92, 0.04045])
+            .unwrap();
+
+    let p3 = Profile::new_rgb(
+        &xy(0.3127, 0.3290),
+        &CIExyYTRIPLE {
+            Red: xy(0.680, 0.320),
+            Green: xy(0.265, 0.690),
+            Blue: xy(0.150, 0.060),
+        },
+        &[&srgb_curve, &srgb_curve, &srgb_curve],
+    )
+    .unwrap()
+    .icc()
+    .unwrap();
+
+    let pixels = [200, 100, 50].repeat(16 * 16);
+    let mut source = Image::from_u8(&pixels, 16, 16, ColorSpace::RGB);
+    source.metadata_mut().set_icc_chunk(p3.clone());
+
+    let encode = |image: &Image, preserve_icc| {
+        let mut options = WebPOptions::new().unwrap();
+        options.lossless = 1;
+
+        let mut buf = vec![];
+        WebPEncoder::new_with_options(options)
+            .preserve_icc(preserve_icc)
+            .encode(image, &mut buf)
+            .unwrap();
+
+        let decoded = webp::Decoder::new(&buf).decode().unwrap().to_vec();
+
+        (buf, decoded)
+    };
+
+    let iccp = [b"ICCP".as_slice(), &(p3.len() as u32).to_le_bytes(), &p3].concat();
+
+    // kept profile is embedded along with untouched pixels
+    let (buf, decoded) = encode(&source, true);
+
+    assert!(buf.windows(iccp.len()).any(|w| w == iccp));
+    assert_eq!(decoded, pixels);
+
+    // otherwise pixels are converted to sRGB and the file is untagged
+    let mut converted = source.clone();
+    ApplySRGB.execute(&mut converted).unwrap();
+
+    let (buf, decoded) = encode(&converted, false);
+
+    assert!(!buf.windows(4).any(|w| w == b"ICCP"));
+    assert_eq!(decoded, converted.flatten_to_u8()[0]);
+    assert_ne!(decoded, pixels);
+}
+
+#[test]
+fn encode_method() {
+    let pixels = (0..128 * 128)
+        .flat_map(|idx| {
+            let (x, y) = (idx % 128, idx / 128);
+            let noise = (idx * 7919 % 23) as u8;
+
+            [x as u8 + noise, y as u8 + noise, 100, 255]
+        })
+        .collect::<Vec<u8>>();
+    let image = Image::from_u8(&pixels, 128, 128, ColorSpace::RGBA);
+
+    let encode = |method| {
+        let mut options = WebPOptions::new().unwrap();
+        options.method = method;
+
+        let mut buf = vec![];
+        WebPEncoder::new_with_options(options)
+            .encode(&image, &mut buf)
+            .unwrap();
+
+        buf.len()
+    };
+
+    assert_ne!(encode(0), encode(6));
+}
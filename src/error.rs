@@ -0,0 +1,99 @@
+use std::io;
+
+use zune_image::errors::ImageErrors;
+
+/// Errors of rimage operations
+///
+/// [`OperationsTrait`](zune_image::traits::OperationsTrait) requires operations to return
+/// [`ImageErrors`], so these errors are carried inside of [`ImageErrors::IoError`].
+/// Use [`Error::from_image_errors`] to get the typed error back.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Resized image would exceed the limit of pixels
+    #[error("Resize to {width}x{height} exceeds the limit of {limit} pixels")]
+    PixelLimit {
+        /// Width of the resized image
+        width: usize,
+        /// Height of the resized image
+        height: usize,
+        /// Maximum number of pixels
+        limit: usize,
+    },
+
+    /// Selected frame doesn't exist in the image
+    #[error("Frame {index} is out of range of {frames} frame(s)")]
+    FrameOutOfRange {
+        /// Index of the selected frame
+        index: usize,
+        /// Number of frames in the image
+        frames: usize,
+    },
+
+    /// Channel order isn't a permutation of channels of the image
+    #[error("Channel order {order:?} is not a permutation of {channels} channel(s)")]
+    InvalidChannelOrder {
+        /// Requested order of channels
+        order: [usize; 4],
+        /// Number of channels in the image
+        channels: usize,
+    },
+
+    /// EXIF orientation is outside of the `1..=8` range
+    #[error("Invalid EXIF orientation {0}, expected a value from 1 to 8")]
+    InvalidOrientation(u16),
+
+    /// LUT file can't be read
+    #[error("Unable to read LUT: {0}")]
+    LutRead(io::Error),
+
+    /// LUT file is malformed
+    #[error("Invalid LUT: {0}")]
+    InvalidLut(String),
+
+    /// Image buffer can't be used for resizing
+    #[cfg(feature = "resize")]
+    #[error("Invalid resize buffer: {0}")]
+    ResizeBuffer(#[from] fast_image_resize::ImageBufferError),
+
+    /// Resize failed
+    #[cfg(feature = "resize")]
+    #[error("Resize failed: {0}")]
+    Resize(#[from] fast_image_resize::DifferentTypesOfPixelsError),
+
+    /// Quantization failed
+    #[cfg(feature = "quantization")]
+    #[error("Quantization failed: {0}")]
+    Quantize(#[from] imagequant::Error),
+
+    /// Thread pool for the operation can't be created
+    #[cfg(feature = "threads")]
+    #[error("Unable to create thread pool: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+
+    /// ICC profile file can't be read
+    #[cfg(feature = "icc")]
+    #[error("Unable to read ICC profile: {0}")]
+    IccRead(io::Error),
+
+    /// Applying ICC profile failed
+    #[cfg(feature = "icc")]
+    #[error("ICC profile error: {0}")]
+    Icc(#[from] lcms2::Error),
+}
+
+impl Error {
+    /// Returns the rimage error carried by `error`, if there is one
+    pub fn from_image_errors(error: &ImageErrors) -> Option<&Error> {
+        match error {
+            ImageErrors::IoError(e) => e.get_ref()?.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for ImageErrors {
+    fn from(e: Error) -> Self {
+        ImageErrors::IoError(io::Error::other(e))
+    }
+}
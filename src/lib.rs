@@ -117,8 +117,23 @@ quantize.execute(&mut img).unwrap();
 /// All additional operations for the zune_image
 pub mod operations;
 
+/// Errors of rimage operations
+mod error;
+
+pub use error::Error;
+
 /// All additional codecs for the zune_image
 pub mod codecs;
 
+pub use codecs::{can_decode, can_decode_bytes, can_encode};
+
+/// Pipelines chaining operations with an encoder
+pub mod pipeline;
+
+/// Traversal of directories with images
+mod walk;
+
+pub use walk::{walk_images, walk_images_with, WalkOptions};
+
 #[cfg(test)]
 mod test_utils;
@@ -1,58 +1,368 @@
 use std::{
+    ffi::OsString,
     fs::{self, File},
-    path::PathBuf,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use clap::{error::ErrorKind, ArgMatches};
 use cli::{
+    analyze::{is_blank, Analysis},
     cli,
-    pipeline::{decode, operations},
-    utils::paths::{collect_files, get_paths},
+    config::{config_path, Config},
+    pipeline::{decode, decode_with_delays, describe_operations, operations},
+    sidecar::{is_sidecar, Sidecar},
+    utils::{
+        archive::{write_zip, Extracted, ScratchDir},
+        atomic::{create_parent_dirs, write_atomic},
+        complexity::QualityRange,
+        diff::{diff, AMPLIFY},
+        log_json::json_line,
+        overwrite::{OverwritePolicy, Resolved},
+        paths::{collect_files, get_paths, set_extension, Traversal},
+        pipelined::pipelined,
+        report::{write_csv, write_summary, write_summary_json, Report},
+        srcset::{Srcset, SrcsetEntry},
+        template::{expand_template, NameValues},
+    },
 };
 use console::{style, Term};
 use indicatif::{
-    DecimalBytes, MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget,
-    ProgressStyle,
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
 };
 use indicatif_log_bridge::LogWrapper;
+use lcms2::{Profile, ThreadContext};
 use rayon::prelude::*;
-use rimage::operations::icc::ApplySRGB;
+use rimage::operations::{
+    depth::Depth,
+    icc::{ApplyICC, ApplySRGB},
+    select_frame::SelectFrame,
+};
 use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
 use zune_image::{
-    core_filters::{colorspace::ColorspaceConv, depth::Depth},
-    image::Image,
-    pipelines::Pipeline,
+    codecs::ImageFormat, core_filters::colorspace::ColorspaceConv, errors::ImageErrors,
+    image::Image, metadata::AlphaState, pipelines::Pipeline, traits::OperationsTrait,
+};
+
+#[cfg(feature = "resize")]
+use cli::{
+    montage::{montage_images, thumbnail},
+    preprocessors::ResizeValue,
+    utils::filter_compare::compare_filters,
 };
-use zune_imageprocs::auto_orient::AutoOrient;
+#[cfg(all(feature = "metadata", feature = "resize"))]
+use rimage::operations::resize::{FilterType, Resize, ResizeAlg};
+#[cfg(all(feature = "metadata", feature = "resize"))]
+use zune_core::{bytestream::ZCursor, options::DecoderOptions};
 
-use crate::cli::pipeline::encoder;
+#[cfg(feature = "metadata")]
+use crate::cli::pipeline::metadata_operations;
+use crate::cli::pipeline::{
+    auto_orient, check_alpha, decode_encoded, default_encoder, discard_frames, encode_to_vec,
+    encoder, encoder_for_complexity, encoder_for_target, encoder_with_quality, has_quality,
+    quality, restore_grayscale, straight_alpha, verify_lossless, AvailableEncoders,
+};
+#[cfg(all(feature = "metadata", feature = "resize"))]
+use crate::cli::utils::thumbnail::exif_thumbnail;
 
 mod cli;
+#[cfg(test)]
+mod test_utils {
+    #[cfg(feature = "metadata")]
+    pub(crate) use self::metadata::raw_exif;
+    pub(crate) use self::temp_dir::TempDir;
+
+    #[cfg(feature = "metadata")]
+    mod metadata;
+    mod temp_dir;
+}
+
+/// Codecs able to embed ICC profiles into output images
+const SUPPORTS_ICC: &[&str] = &["avif", "mozjpeg", "oxipng", "webp"];
+
+/// Size generated thumbnails are fitted into, the common size of EXIF thumbnails
+#[cfg(all(feature = "metadata", feature = "resize"))]
+const THUMBNAIL_SIZE: usize = 160;
 
 macro_rules! handle_error {
     ( $path:expr, $e:expr ) => {
+        handle_error!($path, $e, ())
+    };
+    ( $path:expr, $e:expr, $ret:expr ) => {
         match $e {
             Ok(v) => v,
             Err(e) => {
-                log::error!("{}: {e}", $path.display());
-                return;
+                log::error!(file:% = $path.display(); "{}: {e}", $path.display());
+                return $ret;
             }
         }
     };
 }
 
-struct Result {
+/// Chooses codec for the image based on its content
+///
+/// The selected codec is kept when it already suits the image, otherwise
+/// images with few colors go to a lossless palette codec and photos to a lossy one
+/// that preserves transparency when needed.
+fn choose_codec<'a>(subcommand: &'a str, analysis: &Analysis) -> &'a str {
+    let lossless = matches!(
+        subcommand,
+        "farbfeld" | "jpeg_xl" | "oxipng" | "png" | "ppm" | "qoi"
+    );
+    let supports_alpha = !matches!(subcommand, "jpeg" | "mozjpeg" | "ppm");
+
+    if analysis.unique_colors <= 256 {
+        if lossless {
+            subcommand
+        } else {
+            "oxipng"
+        }
+    } else if !lossless && (analysis.opaque || supports_alpha) {
+        subcommand
+    } else {
+        analysis.suggested_codec()
+    }
+}
+
+/// Checks if the summary is printed in `--summary` mode
+///
+/// Only `--quiet` hides the summary in `auto` mode, `--no-progress` just hides progress bars.
+fn show_summary(mode: &str, quiet: bool) -> bool {
+    match mode {
+        "always" => true,
+        "never" => false,
+        _ => !quiet,
+    }
+}
+
+/// Writes metadata files of the batch, then the summary into `out` if `summary` is set
+///
+/// Failures to write metadata files are logged, only writing the summary returns an error.
+fn write_reports(
+    results: &[Report],
+    csv: Option<&Path>,
+    summary_json: Option<&Path>,
+    summary: bool,
+    out: impl Write,
+) -> io::Result<()> {
+    if let Some(csv) = csv {
+        let result = File::create(csv).and_then(|file| write_csv(BufWriter::new(file), results));
+
+        if let Err(e) = result {
+            log::error!(file:% = csv.display(); "{}: {e}", csv.display());
+        }
+    }
+
+    if let Some(json) = summary_json {
+        let result =
+            File::create(json).and_then(|file| write_summary_json(BufWriter::new(file), results));
+
+        if let Err(e) = result {
+            log::error!(file:% = json.display(); "{}: {e}", json.display());
+        }
+    }
+
+    if summary {
+        write_summary(out, results)?;
+    }
+
+    Ok(())
+}
+
+/// Renames `input` to `<stem>@backup.<ext>` next to it
+fn backup_input(input: &Path) -> io::Result<()> {
+    let name = format!(
+        "{}@backup.{}",
+        input.file_stem().unwrap_or_default().to_string_lossy(),
+        input.extension().unwrap_or_default().to_string_lossy()
+    );
+
+    fs::rename(input, input.with_file_name(name))
+}
+
+/// Writes every entry of a responsive set with `process`, backing up `input` afterwards
+///
+/// Outputs of the set never replace the input, so it's renamed only once all of them are written.
+fn process_srcset(
+    input: &Path,
+    entries: &[SrcsetEntry],
+    backup: bool,
+    process: impl FnMut(&SrcsetEntry),
+) -> io::Result<()> {
+    entries.iter().for_each(process);
+
+    if backup {
+        backup_input(input)?;
+    }
+
+    Ok(())
+}
+
+/// Message of `e`, using the rimage error it carries if there is one
+fn error_message(e: ImageErrors) -> String {
+    match rimage::Error::from_image_errors(&e) {
+        Some(e) => e.to_string(),
+        None => e.to_string(),
+    }
+}
+
+/// Writes a line to the terminal, exiting quietly once its reader is gone
+///
+/// Output piped into commands like `head` closes early, which isn't an error.
+fn write_line(term: &Term, line: &str) {
+    if let Err(e) = term.write_line(line) {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            std::process::exit(0);
+        }
+
+        log::error!("{e}");
+    }
+}
+
+/// Operations converting the decoded image to 8-bit sRGB RGBA in its display orientation
+///
+/// Images are converted with `apply_icc` instead of to sRGB when it's given.
+/// With `--raw-convert` only depth and colorspace are adjusted, leaving pixels as decoded.
+fn prepare_operations(
+    matches: &ArgMatches,
+    keep_icc: bool,
+    apply_icc: Option<&ApplyICC>,
+) -> Vec<Box<dyn OperationsTrait>> {
+    let mut operations: Vec<Box<dyn OperationsTrait>> = vec![
+        Box::new(Depth::new(BitDepth::Eight).with_dithering(matches.get_flag("dither-depth"))),
+        Box::new(ColorspaceConv::new(ColorSpace::RGBA)),
+    ];
+
+    if matches.get_flag("first-frame-only") {
+        operations.insert(0, Box::new(SelectFrame::new(0)));
+    }
+
+    if matches.get_flag("raw-convert") {
+        return operations;
+    }
+
+    operations.extend(auto_orient(matches));
+
+    match apply_icc {
+        _ if keep_icc => {}
+        None => operations.push(Box::new(ApplySRGB)),
+        Some(apply_icc) => operations.push(Box::new(apply_icc.clone())),
+    }
+
+    operations
+}
+
+/// ICC conversion for `--icc` and `--assume-profile`, shared by all images
+///
+/// Profiles are read and validated once, so an unusable profile is reported
+/// before any image is processed.
+fn icc_operation(matches: &ArgMatches) -> Result<Option<ApplyICC>, String> {
+    let read = |id| {
+        matches
+            .get_one::<PathBuf>(id)
+            .map(|path| {
+                fs::read(path)
+                    .map(|icc| (path, icc))
+                    .map_err(|e| format!("{}: {e}", path.display()))
+            })
+            .transpose()
+    };
+
+    let target_profile = read("icc")?;
+    let assume_profile = read("assume-profile")?;
+
+    if target_profile.is_none() && assume_profile.is_none() {
+        return Ok(None);
+    }
+
+    let mut apply_icc = match &target_profile {
+        Some((path, icc)) => {
+            ApplyICC::from_icc(icc).map_err(|e| format!("{}: {e}", path.display()))?
+        }
+        None => ApplyICC::new(Profile::new_srgb_context(ThreadContext::new())),
+    };
+
+    if let Some((path, icc)) = &assume_profile {
+        let profile = Profile::new_icc_context(ThreadContext::new(), icc)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        apply_icc = apply_icc.with_input_profile(profile);
+    }
+
+    apply_icc.validate().map_err(|e| {
+        let (path, _) = target_profile
+            .as_ref()
+            .or(assume_profile.as_ref())
+            .expect("one of the profiles is given");
+        format!("{}: {e}", path.display())
+    })?;
+
+    Ok(Some(apply_icc))
+}
+
+/// Name of the diff image written for the output file, like `image.diff.png`
+///
+/// The distinct suffix keeps diffs from overwriting PNG output files in the same directory.
+fn diff_name(output: &Path) -> OsString {
+    let mut name = output.file_stem().unwrap_or_default().to_os_string();
+    name.push(".diff.png");
+
+    name
+}
+
+/// Extension of output files written by the encoder, unless overridden with `--extension`
+fn output_extension<'a>(encoder: &AvailableEncoders, matches: &'a ArgMatches) -> &'a str {
+    let jpeg_ext = matches
+        .get_one::<String>("jpeg-ext")
+        .expect("`jpeg-ext` has default value");
+
+    match matches.get_one::<String>("extension") {
+        Some(ext) => ext,
+        None if encoder.to_extension() == "jpg" => jpeg_ext,
+        None => encoder.to_extension(),
+    }
+}
+
+/// Output path known before decoding the input, `None` if it depends on the image
+///
+/// Codecs picked by `--auto-codec`, dimensions in `--name-template` and sidecar
+/// overrides are only known once the image is processed.
+fn planned_output(
+    subcommand: &str,
+    matches: &ArgMatches,
+    input: &Path,
+    output: &Path,
+) -> Option<PathBuf> {
+    if matches.get_flag("auto-codec")
+        || matches.contains_id("name-template")
+        || (cfg!(feature = "resize") && matches.contains_id("srcset"))
+        || !matches!(Sidecar::load(input), Ok(None))
+    {
+        return None;
+    }
+
+    let encoder = encoder(subcommand, matches).ok()?;
+
+    let mut output = output.to_path_buf();
+    set_extension(&mut output, output_extension(&encoder, matches));
+
+    Some(output)
+}
+
+/// Decoded input file waiting to be processed
+#[derive(Clone)]
+struct Decoded {
+    input: PathBuf,
     output: PathBuf,
     input_size: u64,
-    output_size: u64,
+    img: Image,
+    delays: Vec<u16>,
+    pb: ProgressBar,
+    start: Instant,
 }
 
 fn main() {
-    let logger = pretty_env_logger::formatted_builder()
-        .parse_default_env()
-        .build();
-
     let multi = MultiProgress::new();
     let sty_main = ProgressStyle::with_template("{bar:40.green/yellow} {pos:>4}/{len:4}")
         .unwrap()
@@ -61,13 +371,11 @@ fn main() {
     let sty_aux_operations = ProgressStyle::with_template("{spinner:.yellow} {msg}").unwrap();
     let sty_aux_encode = ProgressStyle::with_template("{spinner:.green} {msg}").unwrap();
 
-    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
-
-    let matches = cli().get_matches_from(
+    let args = {
         #[cfg(not(windows))]
         {
             std::env::args()
-        },
+        }
         #[cfg(windows)]
         {
             std::env::args().map(|mut arg| {
@@ -76,12 +384,137 @@ fn main() {
                     .trim_matches(['\\', '/', '\n', '\r', '"', '\'', ' ', '\t'])
                     .to_string()
             })
-        },
-    );
+        }
+    }
+    .map(OsString::from)
+    .collect::<Vec<_>>();
 
-    let results: Arc<Mutex<Vec<Result>>> = Arc::new(Mutex::new(vec![]));
+    let (args, config_error) = match config_path().map(|path| Config::load(&path)) {
+        Some(Ok(Some(config))) => (config.apply(args), None),
+        Some(Err(e)) => (args, Some(e)),
+        _ => (args, None),
+    };
+
+    let matches = cli().get_matches_from(args);
+
+    let mut logger = pretty_env_logger::formatted_builder();
+    logger.parse_default_env();
+    if matches.subcommand().is_some_and(|(_, matches)| {
+        matches!(matches.try_get_one::<bool>("log-json"), Ok(Some(true)))
+    }) {
+        logger.format(|buf, record| writeln!(buf, "{}", json_line(record)));
+    }
+    let logger = logger.build();
+
+    LogWrapper::new(multi.clone(), logger).try_init().unwrap();
+
+    if let Some(e) = config_error {
+        log::error!("{e:#}");
+    }
+
+    let results: Arc<Mutex<Vec<Report>>> = Arc::new(Mutex::new(vec![]));
 
     match matches.subcommand() {
+        Some(("analyze", matches)) => {
+            let term = Term::stdout();
+
+            let files = collect_files(
+                matches
+                    .get_many::<PathBuf>("files")
+                    .expect("`files` is required")
+                    .collect::<Vec<_>>()
+                    .as_ref(),
+                &Traversal {
+                    images_only: true,
+                    ..Default::default()
+                },
+            );
+
+            for input in files.iter().filter(|f| f.is_file()) {
+                let analysis = match decode(input).and_then(|img| Analysis::new(&img)) {
+                    Ok(analysis) => analysis,
+                    Err(e) => {
+                        log::error!(file:% = input.display(); "{}: {e}", input.display());
+                        continue;
+                    }
+                };
+
+                write_line(
+                    &term,
+                    &format!(
+                        "{}: {} colors, grayscale: {}, opaque: {}, suggested codec: {}",
+                        input.display(),
+                        style(analysis.unique_colors).blue(),
+                        analysis.grayscale,
+                        analysis.opaque,
+                        style(analysis.suggested_codec()).green(),
+                    ),
+                );
+            }
+        }
+        #[cfg(feature = "resize")]
+        Some(("montage", matches)) => {
+            let output = matches
+                .get_one::<PathBuf>("output")
+                .expect("`output` is required");
+            let codec = matches
+                .get_one::<String>("codec")
+                .expect("`codec` has default value");
+            let cell = *matches
+                .get_one::<u32>("cell")
+                .expect("`cell` has default value") as usize;
+
+            let files = collect_files(
+                matches
+                    .get_many::<PathBuf>("files")
+                    .expect("`files` is required")
+                    .collect::<Vec<_>>()
+                    .as_ref(),
+                &Traversal {
+                    images_only: true,
+                    ..Default::default()
+                },
+            );
+
+            let thumbnails = files
+                .par_iter()
+                .filter(|f| f.is_file())
+                .filter_map(|input| {
+                    decode(input)
+                        .and_then(|img| thumbnail(img, cell))
+                        .map_err(error_message)
+                        .inspect_err(
+                            |e| log::error!(file:% = input.display(); "{}: {e}", input.display()),
+                        )
+                        .ok()
+                })
+                .collect::<Vec<_>>();
+
+            // smallest square grid fitting all images
+            let columns = matches
+                .get_one::<u32>("columns")
+                .map(|c| *c as usize)
+                .unwrap_or_else(|| (1..).find(|c| c * c >= thumbnails.len()).unwrap());
+
+            let result = montage_images(&thumbnails, columns, cell).and_then(|mut sheet| {
+                let mut encoder = default_encoder(codec)?;
+
+                if !encoder.supports_alpha() {
+                    rimage::operations::alpha::Flatten::new([1., 1., 1.]).execute(&mut sheet)?;
+                }
+
+                write_atomic(output, None, |file| encoder.encode(&sheet, file))
+            });
+
+            match result {
+                Ok(_) => log::info!(
+                    "{}: {} images on the contact sheet",
+                    output.display(),
+                    thumbnails.len()
+                ),
+                Err(e) => log::error!(file:% = output.display(); "{}: {e}", output.display()),
+            }
+        }
         Some((subcommand, matches)) => {
             if let Some(threads) = matches.get_one::<u8>("threads") {
                 rayon::ThreadPoolBuilder::new()
@@ -90,15 +523,87 @@ fn main() {
                     .unwrap();
             }
 
+            let traversal = Traversal {
+                max_depth: matches.get_one::<usize>("max-depth").copied(),
+                exclude: matches
+                    .get_many::<glob::Pattern>("exclude")
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect(),
+                respect_ignore: matches.get_flag("respect-ignore"),
+                images_only: !matches.get_flag("all-files"),
+            };
+
             let files = collect_files(
                 matches
                     .get_many::<PathBuf>("files")
-                    .expect("`files` is required")
+                    .into_iter()
+                    .flatten()
                     .collect::<Vec<_>>()
                     .as_ref(),
-            );
+                &traversal,
+            )
+            .into_iter()
+            .filter(|f| !is_sidecar(f))
+            .collect::<Vec<_>>();
+
+            #[cfg(feature = "resize")]
+            if matches.get_flag("resize-filter-compare") {
+                let term = Term::stdout();
+                let value = matches
+                    .get_one::<ResizeValue>("resize")
+                    .expect("`resize-filter-compare` requires `resize`");
+
+                for input in files.iter().filter(|f| f.is_file()) {
+                    let result = decode(input).and_then(|img| {
+                        let (w, h) = img.dimensions();
+                        let (width, height) = value.map_dimensions(w, h);
+
+                        Ok(((width, height), compare_filters(&img, width, height)?))
+                    });
+                    let ((width, height), scores) = match result {
+                        Ok(result) => result,
+                        Err(e) => {
+                            log::error!(file:% = input.display(); "{}: {e}", input.display());
+                            continue;
+                        }
+                    };
+
+                    write_line(
+                        &term,
+                        &format!("{}: resize {value} to {width}x{height}", input.display()),
+                    );
+
+                    for (filter, score) in scores {
+                        write_line(&term, &format!("  {filter}: {score:.6}"));
+                    }
+                }
+
+                return;
+            }
 
-            let file_count = files.iter().filter(|f| f.is_file()).count() as u64;
+            if matches.get_flag("print-pipeline") {
+                let term = Term::stdout();
+
+                for input in files.iter().filter(|f| f.is_file()) {
+                    let img = match decode(input) {
+                        Ok(img) => img,
+                        Err(e) => {
+                            log::error!(file:% = input.display(); "{}: {e}", input.display());
+                            continue;
+                        }
+                    };
+
+                    write_line(&term, &format!("{}:", input.display()));
+
+                    for (idx, description) in describe_operations(matches, &img) {
+                        write_line(&term, &format!("  {idx}: {description}"));
+                    }
+                }
+
+                return;
+            }
 
             let out_dir = matches.get_one::<PathBuf>("directory").cloned();
 
@@ -106,8 +611,110 @@ fn main() {
             let backup = matches.get_flag("backup");
             let quiet = matches.get_flag("quiet");
             let no_progress = matches.get_flag("no-progress");
+            let concurrent_io = matches.get_flag("concurrent-io");
+
+            if matches.contains_id("target-quality") && !has_quality(subcommand) {
+                log::warn!("{subcommand} has no quality option, ignoring --target-quality");
+            }
+            if matches.contains_id("adaptive-quality") && !has_quality(subcommand) {
+                log::warn!("{subcommand} has no quality option, ignoring --adaptive-quality");
+            }
+            if matches.get_flag("keep-icc") && !SUPPORTS_ICC.contains(&subcommand) {
+                log::warn!("{subcommand} can't embed ICC profiles, converting images to sRGB");
+            }
+            if matches.contains_id("icc") && !SUPPORTS_ICC.contains(&subcommand) {
+                log::warn!("{subcommand} can't embed ICC profiles, output file(s) won't be tagged");
+            }
+            let summary = show_summary(
+                matches
+                    .get_one::<String>("summary")
+                    .expect("`summary` has default value"),
+                quiet,
+            );
+
+            let overwrite_policy = match matches
+                .get_one::<String>("overwrite-policy")
+                .expect("`overwrite-policy` has default value")
+                .as_str()
+            {
+                "skip" => OverwritePolicy::Skip,
+                "rename" => OverwritePolicy::Rename,
+                _ => OverwritePolicy::Overwrite,
+            };
 
             let suffix = matches.get_one::<String>("suffix").cloned();
+            let append_extension = matches.get_flag("append-extension");
+            let csv = matches.get_one::<PathBuf>("csv").cloned();
+            let summary_json = matches.get_one::<PathBuf>("summary-json").cloned();
+            let write_diff = matches.get_one::<PathBuf>("write-diff").cloned();
+            let verify = matches.get_flag("verify");
+            let size_hint = matches.get_one::<f64>("size-hint").copied();
+            #[cfg(all(feature = "metadata", feature = "resize"))]
+            let extract_thumbnail = matches.get_flag("extract-thumbnail");
+            let skip_blank = matches.get_flag("skip-blank");
+            #[cfg(all(feature = "metadata", feature = "resize"))]
+            let jpeg_ext = matches
+                .get_one::<String>("jpeg-ext")
+                .expect("`jpeg-ext` has default value");
+            #[cfg(feature = "resize")]
+            let srcset = matches.get_one::<Srcset>("srcset").cloned();
+            #[cfg(not(feature = "resize"))]
+            let srcset = None::<Srcset>;
+            let temp_dir = matches.get_one::<PathBuf>("temp-dir").cloned();
+            let apply_icc = match icc_operation(matches) {
+                Ok(apply_icc) => apply_icc,
+                Err(e) => cli().error(ErrorKind::InvalidValue, e).exit(),
+            };
+
+            let to_zip = matches.get_one::<PathBuf>("to-zip");
+            let staging = match to_zip {
+                Some(archive) => Some(handle_error!(archive, ScratchDir::new(temp_dir.as_deref()))),
+                None => None,
+            };
+            let out_dir = staging
+                .as_ref()
+                .map(|dir| dir.path().to_path_buf())
+                .or(out_dir);
+
+            let from_zip = matches.get_one::<PathBuf>("from-zip");
+            let extracted = match from_zip {
+                Some(archive) => Some(handle_error!(
+                    archive,
+                    Extracted::new(archive, temp_dir.as_deref())
+                )),
+                None => None,
+            };
+            let zip_paths = match (from_zip, &extracted) {
+                (Some(archive), Some(extracted)) => extracted
+                    .paths(
+                        out_dir
+                            .as_deref()
+                            .unwrap_or(archive.parent().unwrap_or(Path::new(""))),
+                        suffix.as_deref(),
+                        append_extension,
+                    )
+                    .into_iter()
+                    .filter(|(input, _)| !is_sidecar(input))
+                    .collect(),
+                _ => vec![],
+            };
+
+            let file_count =
+                (files.iter().filter(|f| f.is_file()).count() + zip_paths.len()) as u64;
+
+            if let Some(path) = write_diff.as_ref().filter(|path| !path.is_dir()) {
+                if file_count > 1 || srcset.is_some() {
+                    cli()
+                        .error(
+                            ErrorKind::ArgumentConflict,
+                            format!(
+                                "--write-diff {} must be a directory to hold diffs of multiple output files",
+                                path.display()
+                            ),
+                        )
+                        .exit();
+                }
+            }
 
             if quiet || no_progress {
                 multi.set_draw_target(ProgressDrawTarget::hidden());
@@ -119,153 +726,678 @@ fn main() {
                 pb_main.set_draw_target(ProgressDrawTarget::hidden());
             }
 
-            get_paths(files, out_dir, suffix, recursive)
-                .progress_with(pb_main)
-                .for_each(|(input, mut output)| {
-                    let pb = multi.add(ProgressBar::new_spinner());
-                    pb.set_style(sty_aux_decode.clone());
-                    pb.set_message(format!("{}", input.display()));
-                    pb.enable_steady_tick(Duration::from_millis(100));
+            let decode_stage = |(input, output): (PathBuf, PathBuf)| {
+                let start = Instant::now();
+
+                let pb = multi.add(ProgressBar::new_spinner());
+                pb.set_style(sty_aux_decode.clone());
+                pb.set_message(format!("{}", input.display()));
+                pb.enable_steady_tick(Duration::from_millis(100));
 
-                    let mut pipeline = Pipeline::<Image>::new();
+                let input_size = handle_error!(input, input.metadata(), None).len();
 
-                    let input_size = handle_error!(input, input.metadata()).len();
+                #[cfg(all(feature = "metadata", feature = "resize"))]
+                if extract_thumbnail {
+                    if let Some(thumbnail) = handle_error!(input, exif_thumbnail(&input), None) {
+                        let mut output = output;
+                        set_extension(&mut output, jpeg_ext);
 
-                    let img = handle_error!(input, decode(&input));
+                        pb.finish_and_clear();
 
-                    pb.set_style(sty_aux_operations.clone());
+                        handle_error!(output, create_parent_dirs(&output), None);
+                        let Some(resolved) =
+                            handle_error!(output, overwrite_policy.resolve(&output), None)
+                        else {
+                            log::info!(
+                                file:% = output.display();
+                                "{}: already exists, skipping",
+                                output.display()
+                            );
+                            return None;
+                        };
 
-                    let mut available_encoder = handle_error!(input, encoder(subcommand, matches));
-                    if let Some(ext) = output.extension() {
-                        output.set_extension({
-                            let mut os_str = ext.to_os_string();
-                            os_str.push(".");
-                            os_str.push(available_encoder.to_extension());
-                            os_str
+                        let dimensions = handle_error!(
+                            input,
+                            Image::read(ZCursor::new(&thumbnail), DecoderOptions::default()),
+                            None
+                        )
+                        .dimensions();
+
+                        handle_error!(
+                            output,
+                            write_atomic(resolved.path(), temp_dir.as_deref(), |file| file
+                                .write_all(&thumbnail)),
+                            None
+                        );
+                        let output = resolved.written();
+
+                        results.lock().unwrap().push(Report {
+                            input,
+                            output,
+                            codec: "jpeg".to_string(),
+                            input_size,
+                            output_size: thumbnail.len() as u64,
+                            dimensions,
+                            duration: start.elapsed(),
                         });
-                    } else {
-                        output.set_extension(available_encoder.to_extension());
-                    }
 
-                    pipeline.chain_operations(Box::new(Depth::new(BitDepth::Eight)));
-                    pipeline.chain_operations(Box::new(ColorspaceConv::new(ColorSpace::RGBA)));
+                        return None;
+                    }
+                }
 
-                    pipeline.chain_operations(Box::new(AutoOrient));
-                    pipeline.chain_operations(Box::new(ApplySRGB));
+                let resolved = match planned_output(subcommand, matches, &input, &output) {
+                    Some(output) => {
+                        handle_error!(output, create_parent_dirs(&output), None);
 
-                    operations(matches, &img)
-                        .into_iter()
-                        .for_each(|(_, operations)| match operations.name() {
-                            "quantize" => {
-                                pipeline.chain_operations(Box::new(ColorspaceConv::new(
-                                    ColorSpace::RGBA,
-                                )));
-                                pipeline.chain_operations(operations);
-                            }
-                            _ => {
-                                pipeline.chain_operations(operations);
+                        match handle_error!(output, overwrite_policy.resolve(&output), None) {
+                            Some(resolved) => Some(resolved),
+                            None => {
+                                pb.finish_and_clear();
+                                log::info!(
+                                    file:% = output.display();
+                                    "{}: already exists, skipping",
+                                    output.display()
+                                );
+                                return None;
                             }
-                        });
+                        }
+                    }
+                    None => None,
+                };
+
+                let (img, delays) = handle_error!(input, decode_with_delays(&input), None);
+
+                if skip_blank && is_blank(&img) {
+                    pb.finish_and_clear();
+                    log::warn!(
+                        file:% = input.display();
+                        "{}: image is blank, skipping",
+                        input.display()
+                    );
+                    return None;
+                }
 
-                    pipeline.chain_decoder(img);
+                Some((
+                    Decoded {
+                        input,
+                        output,
+                        input_size,
+                        img,
+                        delays,
+                        pb,
+                        start,
+                    },
+                    resolved,
+                ))
+            };
 
-                    handle_error!(input, pipeline.advance_to_end());
+            let process_stage = |decoded: Decoded,
+                                 srcset_entry: Option<&SrcsetEntry>,
+                                 resolved: Option<Resolved>| {
+                let Decoded {
+                    input,
+                    mut output,
+                    input_size,
+                    mut img,
+                    delays,
+                    pb,
+                    start,
+                } = decoded;
 
-                    pb.set_style(sty_aux_encode.clone());
+                let sidecar = handle_error!(input, Sidecar::load(&input));
+                let file_matches = match sidecar {
+                    Some(sidecar) => Some(handle_error!(input, sidecar.apply(subcommand, matches))),
+                    None => None,
+                };
+                let (subcommand, matches) = match &file_matches {
+                    Some((codec, matches)) => (codec.as_str(), matches),
+                    None => (subcommand, matches),
+                };
 
-                    if backup {
-                        handle_error!(
-                            input,
-                            fs::rename(
-                                &input,
-                                format!(
-                                    "{}@backup.{}",
-                                    input.file_stem().unwrap().to_str().unwrap(),
-                                    input.extension().unwrap().to_str().unwrap()
-                                ),
-                            )
-                        );
+                let auto_codec = matches.get_flag("auto-codec");
+                let keep_icc = matches.get_flag("keep-icc") && SUPPORTS_ICC.contains(&subcommand);
+
+                // codec chosen by --auto-codec is known only after operations,
+                // other frames are dropped before operations run on each of them
+                if !auto_codec {
+                    let encoder = handle_error!(input, default_encoder(subcommand));
+
+                    if let Some(warning) =
+                        handle_error!(input, discard_frames(&mut img, &encoder, subcommand))
+                    {
+                        log::warn!(file:% = input.display(); "{}: {warning}", input.display());
                     }
+                }
 
-                    handle_error!(output, fs::create_dir_all(output.parent().unwrap()));
-                    let output_file = handle_error!(output, File::create(&output));
+                let mut pipeline = Pipeline::<Image>::new();
 
-                    handle_error!(
-                        output,
-                        available_encoder.encode(&pipeline.images()[0], output_file)
-                    );
+                pb.set_style(sty_aux_operations.clone());
 
-                    let output_size = handle_error!(output, output.metadata()).len();
+                for operation in prepare_operations(matches, keep_icc, apply_icc.as_ref()) {
+                    pipeline.chain_operations(operation);
+                }
 
-                    let mut results = results.lock().unwrap();
+                #[cfg(feature = "metadata")]
+                for operation in metadata_operations(matches) {
+                    pipeline.chain_operations(operation);
+                }
 
-                    results.push(Result {
-                        output,
-                        input_size,
-                        output_size,
+                // inputs with embedded thumbnail are written in the decode stage
+                #[cfg(all(feature = "metadata", feature = "resize"))]
+                if extract_thumbnail {
+                    pipeline.chain_operations(Box::new(Resize::downscale_only(
+                        THUMBNAIL_SIZE,
+                        THUMBNAIL_SIZE,
+                        ResizeAlg::Convolution(FilterType::Lanczos3),
+                    )));
+                }
+
+                #[cfg(feature = "resize")]
+                if let Some(entry) = srcset_entry {
+                    pipeline.chain_operations(Box::new(entry.resize(matches)));
+                }
+
+                operations(matches, &img)
+                    .into_iter()
+                    .for_each(|(_, operations)| match operations.name() {
+                        "quantize" => {
+                            pipeline
+                                .chain_operations(Box::new(ColorspaceConv::new(ColorSpace::RGBA)));
+                            pipeline.chain_operations(operations);
+                        }
+                        _ => {
+                            pipeline.chain_operations(operations);
+                        }
                     });
 
-                    pb.finish_and_clear();
+                if matches.get_flag("alpha-premultiplied") {
+                    img.metadata_mut().set_alpha(AlphaState::PreMultiplied);
+                }
+
+                let source_colorspace = img.colorspace();
+                pipeline.chain_decoder(img);
+
+                handle_error!(input, pipeline.advance_to_end().map_err(error_message));
+
+                let codec = if auto_codec {
+                    let analysis = handle_error!(input, Analysis::new(&pipeline.images()[0]));
+                    let codec = choose_codec(subcommand, &analysis);
+
+                    log::info!(
+                        file:% = input.display();
+                        "{}: using {codec} codec",
+                        input.display()
+                    );
+
+                    codec
+                } else {
+                    subcommand
+                };
+
+                let srcset_quality = srcset_entry.and_then(|entry| {
+                    let quality = entry.quality(codec);
+
+                    if quality.is_none() && entry.quality.is_some() {
+                        log::warn!(
+                            file:% = input.display();
+                            "{}: {codec} has no quality option, ignoring quality of {}w srcset entry",
+                            input.display(),
+                            entry.width
+                        );
+                    }
+
+                    quality
                 });
 
-            let mut results = results.lock().unwrap();
+                let mut available_encoder = match srcset_quality {
+                    Some(quality) => handle_error!(
+                        input,
+                        encoder_with_quality(
+                            codec,
+                            (codec == subcommand).then_some(matches),
+                            quality
+                        )
+                    ),
+                    None if codec == subcommand => {
+                        handle_error!(input, encoder(subcommand, matches))
+                    }
+                    None => handle_error!(input, default_encoder(codec)),
+                };
+
+                handle_error!(input, straight_alpha(&mut pipeline.images_mut()[0]));
 
-            results.sort_by(|a, b| b.output_size.cmp(&a.output_size));
+                // checked before restoring grayscale, which drops alpha as well
+                if handle_error!(
+                    input,
+                    check_alpha(&mut pipeline.images_mut()[0], &available_encoder, matches)
+                ) {
+                    log::warn!(
+                        file:% = input.display();
+                        "{}: {codec} doesn't support transparency, flattening onto white",
+                        input.display()
+                    );
+                }
 
-            let path_width = results
-                .iter()
-                .map(|r| r.output.display().to_string().len())
-                .max()
-                .unwrap_or(0);
+                handle_error!(
+                    input,
+                    restore_grayscale(
+                        &mut pipeline.images_mut()[0],
+                        source_colorspace,
+                        &available_encoder
+                    )
+                );
 
-            if !quiet {
-                let term = Term::stdout();
+                if let Some(warning) = handle_error!(
+                    input,
+                    discard_frames(&mut pipeline.images_mut()[0], &available_encoder, codec)
+                ) {
+                    log::warn!(file:% = input.display(); "{}: {warning}", input.display());
+                }
 
-                if results.len() > 1 {
-                    term.write_line(&format!(
-                        "{:<path_width$} {}",
-                        style("File").bold(),
-                        style("Size").bold(),
-                    ))
-                    .unwrap();
+                let output_img = &pipeline.images()[0];
 
-                    for result in results.iter() {
-                        let difference =
-                            (result.output_size as f64 / result.input_size as f64) * 100.0;
-
-                        term.write_line(&format!(
-                            "{:<path_width$} {} > {} {}",
-                            result.output.display(),
-                            style(DecimalBytes(result.input_size)).blue(),
-                            style(DecimalBytes(result.output_size)).blue(),
-                            if difference > 100.0 {
-                                style(format!("{:.2}%", difference - 100.0)).red()
-                            } else {
-                                style(format!("{:.2}%", difference - 100.0)).green()
-                            },
-                        ))
-                        .unwrap();
+                if let Some(target) = matches.get_one::<f64>("target-quality") {
+                    let matches = (codec == subcommand).then_some(matches);
+
+                    if let Some(encoder) = handle_error!(
+                        input,
+                        encoder_for_target(codec, matches, output_img, *target)
+                    ) {
+                        available_encoder = encoder;
+                    }
+                } else if let Some(range) = matches.get_one::<QualityRange>("adaptive-quality") {
+                    let matches = (codec == subcommand).then_some(matches);
+
+                    if let Some(encoder) = handle_error!(
+                        input,
+                        encoder_for_complexity(codec, matches, output_img, *range)
+                    ) {
+                        available_encoder = encoder;
+                    }
+                }
+
+                let mut available_encoder = available_encoder.with_frame_delays(delays);
+
+                let extension = output_extension(&available_encoder, matches);
+
+                let name_template = srcset_entry
+                    .map(SrcsetEntry::name_template)
+                    .or_else(|| matches.get_one::<String>("name-template").cloned());
+
+                match name_template {
+                    Some(template) => {
+                        let (width, height) = output_img.dimensions();
+                        let values = NameValues {
+                            stem: input
+                                .file_stem()
+                                .and_then(|f| f.to_str())
+                                .unwrap_or_default(),
+                            width,
+                            height,
+                            codec,
+                            extension: extension.trim_start_matches('.'),
+                            quality: srcset_quality.or_else(|| {
+                                (codec == subcommand)
+                                    .then(|| quality(codec, matches))
+                                    .flatten()
+                            }),
+                        };
+
+                        output.set_file_name(handle_error!(
+                            input,
+                            expand_template(&template, &values)
+                        ));
                     }
+                    None => set_extension(&mut output, extension),
                 }
 
-                let total_input_size = results.iter().map(|r| r.input_size).sum::<u64>();
-                let total_output_size = results.iter().map(|r| r.output_size).sum::<u64>();
+                let resolved = match resolved {
+                    Some(resolved) => resolved,
+                    None => {
+                        handle_error!(output, create_parent_dirs(&output));
+
+                        let Some(resolved) =
+                            handle_error!(output, overwrite_policy.resolve(&output))
+                        else {
+                            log::info!(
+                                file:% = output.display();
+                                "{}: already exists, skipping",
+                                output.display()
+                            );
+                            pb.finish_and_clear();
+                            return;
+                        };
+
+                        resolved
+                    }
+                };
+
+                pb.set_style(sty_aux_encode.clone());
+
+                // srcset outputs never replace the input, it's backed up after all of them
+                if backup && srcset_entry.is_none() {
+                    handle_error!(input, backup_input(&input));
+                }
+
+                handle_error!(
+                    output,
+                    write_atomic(resolved.path(), temp_dir.as_deref(), |file| {
+                        let capacity = size_hint
+                            .filter(|_| available_encoder.streams())
+                            .map(|ratio| (input_size as f64 * ratio) as usize);
+
+                        if !verify && capacity.is_none() {
+                            return available_encoder.encode(output_img, file);
+                        }
+
+                        let buf = encode_to_vec(
+                            &mut available_encoder,
+                            output_img,
+                            capacity.unwrap_or_default(),
+                        )?;
+
+                        if verify {
+                            verify_lossless(output_img, &buf, &available_encoder)?;
+                        }
 
-                let difference = (total_output_size as f64 / total_input_size as f64) * 100.0;
+                        file.write_all(&buf)?;
 
-                term.write_line(&format!(
-                    "Total: {} > {} {}",
-                    style(DecimalBytes(total_input_size)).blue(),
-                    style(DecimalBytes(total_output_size)).blue(),
-                    if difference > 100.0 {
-                        style(format!("{:.2}%", difference - 100.0)).red()
+                        Ok(buf.len())
+                    })
+                );
+                let output = resolved.written();
+
+                let output_size = handle_error!(output, output.metadata()).len();
+
+                if let Some(path) = &write_diff {
+                    let path = if path.is_dir() {
+                        path.join(diff_name(&output))
                     } else {
-                        style(format!("{:.2}%", difference - 100.0)).green()
+                        path.clone()
+                    };
+
+                    let result = fs::read(&output)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|data| Ok(decode_encoded(&data, &available_encoder)?))
+                        .and_then(|decoded| Ok(diff(output_img, &decoded, AMPLIFY)?))
+                        .and_then(|diff| Ok(diff.save_to(&path, ImageFormat::PNG)?));
+
+                    if let Err(e) = result {
+                        log::error!(file:% = path.display(); "{}: {e}", path.display());
+                    }
+                }
+
+                let mut results = results.lock().unwrap();
+
+                results.push(Report {
+                    input,
+                    output,
+                    codec: codec.to_string(),
+                    input_size,
+                    output_size,
+                    dimensions: output_img.dimensions(),
+                    duration: start.elapsed(),
+                });
+
+                pb.finish_and_clear();
+            };
+
+            let process_decoded = |(decoded, resolved): (Decoded, Option<Resolved>)| match &srcset {
+                Some(Srcset(entries)) => {
+                    let input = decoded.input.clone();
+
+                    handle_error!(
+                        input,
+                        process_srcset(&input, entries, backup, |entry| {
+                            process_stage(decoded.clone(), Some(entry), None)
+                        })
+                    );
+                }
+                None => process_stage(decoded, None, resolved),
+            };
+
+            let paths =
+                get_paths(files, out_dir, suffix, recursive, append_extension).chain(zip_paths);
+
+            if concurrent_io {
+                let paths = paths.collect::<Vec<_>>();
+
+                pipelined(
+                    paths,
+                    rayon::current_num_threads(),
+                    |paths| {
+                        let decoded = decode_stage(paths);
+
+                        // skipped and undecodable files never reach the consumer
+                        if decoded.is_none() {
+                            pb_main.inc(1);
+                        }
+
+                        decoded
                     },
-                ))
-                .unwrap();
+                    |decoded| {
+                        process_decoded(decoded);
+                        pb_main.inc(1);
+                    },
+                );
+
+                pb_main.finish();
+            } else {
+                paths.progress_with(pb_main).for_each(|paths| {
+                    if let Some(decoded) = decode_stage(paths) {
+                        process_decoded(decoded);
+                    }
+                });
+            }
+
+            let mut results = results.lock().unwrap();
+
+            results.sort_by_key(|r| std::cmp::Reverse(r.output_size));
+
+            if let (Some(archive), Some(staging)) = (to_zip, &staging) {
+                let outputs = results.iter().map(|r| r.output.clone()).collect::<Vec<_>>();
+
+                match write_zip(archive, staging.path(), &outputs) {
+                    Ok(()) => results.iter_mut().for_each(|r| {
+                        if let Ok(relative) = r.output.strip_prefix(staging.path()) {
+                            r.output = archive.join(relative);
+                        }
+                    }),
+                    Err(e) => {
+                        log::error!(file:% = archive.display(); "{}: {e}", archive.display())
+                    }
+                }
+            }
+
+            let result = write_reports(
+                &results,
+                csv.as_deref(),
+                summary_json.as_deref(),
+                summary,
+                io::stdout().lock(),
+            );
+
+            if let Err(e) = result {
+                if e.kind() == io::ErrorKind::BrokenPipe {
+                    std::process::exit(0);
+                }
+
+                log::error!("{e}");
             }
         }
         None => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    const LOGO: Analysis = Analysis {
+        unique_colors: 4,
+        grayscale: false,
+        opaque: false,
+    };
+
+    const PHOTO: Analysis = Analysis {
+        unique_colors: 50_000,
+        grayscale: false,
+        opaque: true,
+    };
+
+    fn prepared(args: &[&str]) -> Vec<&'static str> {
+        let matches = cli()
+            .try_get_matches_from(["rimage", "png"].iter().chain(args).chain(&["-"]))
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        prepare_operations(matches, false, None)
+            .iter()
+            .map(|operation| operation.name())
+            .collect()
+    }
+
+    #[test]
+    fn raw_convert_skips_orient_and_icc() {
+        assert_eq!(
+            prepared(&[]),
+            [
+                "Depth",
+                "Colorspace conversion",
+                "Auto orient",
+                "apply srgb profile"
+            ]
+        );
+        assert_eq!(
+            prepared(&["--raw-convert"]),
+            ["Depth", "Colorspace conversion"]
+        );
+
+        #[cfg(feature = "resize")]
+        assert!(cli()
+            .try_get_matches_from(["rimage", "png", "--raw-convert", "--resize", "50%", "-"])
+            .is_err());
+    }
+
+    #[test]
+    fn summary_modes() {
+        for quiet in [false, true] {
+            assert!(show_summary("always", quiet));
+            assert!(!show_summary("never", quiet));
+            assert_eq!(show_summary("auto", quiet), !quiet);
+        }
+    }
+
+    #[test]
+    fn summary_never_writes_metadata() {
+        let dir = TempDir::new("summary_never_writes_metadata");
+        let csv = dir.join("report.csv");
+        let json = dir.join("summary.json");
+
+        let results = [Report {
+            input: PathBuf::from("a.png"),
+            output: PathBuf::from("a.jpg"),
+            codec: "mozjpeg".to_string(),
+            input_size: 200,
+            output_size: 100,
+            dimensions: (10, 20),
+            duration: Duration::from_millis(15),
+        }];
+
+        let mut stdout = vec![];
+        write_reports(&results, Some(&csv), Some(&json), false, &mut stdout).unwrap();
+
+        assert!(stdout.is_empty());
+        assert!(fs::read_to_string(&csv).unwrap().contains("a.jpg"));
+        assert!(json.is_file());
+
+        write_reports(&results, None, None, true, &mut stdout).unwrap();
+
+        assert!(String::from_utf8(stdout).unwrap().contains("Total:"));
+    }
+
+    #[test]
+    fn srcset_with_backup() {
+        let dir = TempDir::new("srcset_with_backup");
+        let input = dir.join("image.png");
+        fs::write(&input, b"image").unwrap();
+
+        let Srcset(entries) = "480,800:80,1200".parse().unwrap();
+
+        process_srcset(&input, &entries, true, |entry| {
+            fs::write(dir.join(format!("image-{}w.png", entry.width)), b"output").unwrap()
+        })
+        .unwrap();
+
+        for width in [480, 800, 1200] {
+            assert!(dir.join(format!("image-{width}w.png")).is_file());
+        }
+        assert!(!input.exists());
+        assert_eq!(fs::read(dir.join("image@backup.png")).unwrap(), b"image");
+    }
+
+    #[test]
+    fn first_frame_only_selects_frame_first() {
+        assert_eq!(
+            prepared(&["--first-frame-only", "--raw-convert"]),
+            ["select frame", "Depth", "Colorspace conversion"]
+        );
+    }
+
+    #[test]
+    fn icc_operation_validated() {
+        let icc_operation = |args: &[&str]| {
+            let matches = cli()
+                .try_get_matches_from(["rimage", "png"].iter().chain(args).chain(&["-"]))
+                .unwrap();
+            let (_, matches) = matches.subcommand().unwrap();
+
+            icc_operation(matches)
+        };
+
+        assert!(icc_operation(&[]).unwrap().is_none());
+        assert!(icc_operation(&["--icc", "tests/files/icc/tinysrgb.icc"])
+            .unwrap()
+            .is_some());
+        assert!(
+            icc_operation(&["--assume-profile", "tests/files/icc/tinysrgb.icc"])
+                .unwrap()
+                .is_some()
+        );
+
+        let Err(error) = icc_operation(&["--assume-profile", "tests/files/png/f1t.png"]) else {
+            panic!("invalid profile is accepted");
+        };
+        assert!(error.starts_with("tests/files/png/f1t.png"), "{error}");
+        assert!(icc_operation(&["--icc", "tests/files/icc/missing.icc"]).is_err());
+    }
+
+    #[test]
+    fn diff_names() {
+        assert_eq!(diff_name(Path::new("out/image.png")), "image.diff.png");
+        assert_eq!(diff_name(Path::new("image.tar.webp")), "image.tar.diff.png");
+    }
+
+    #[test]
+    fn flat_logo_to_oxipng() {
+        assert_eq!(choose_codec("mozjpeg", &LOGO), "oxipng");
+        assert_eq!(choose_codec("avif", &LOGO), "oxipng");
+        assert_eq!(choose_codec("png", &LOGO), "png");
+    }
+
+    #[test]
+    fn photo_to_lossy() {
+        assert_eq!(choose_codec("oxipng", &PHOTO), "mozjpeg");
+        assert_eq!(choose_codec("avif", &PHOTO), "avif");
+
+        let transparent = Analysis {
+            opaque: false,
+            ..PHOTO
+        };
+
+        assert_eq!(choose_codec("oxipng", &transparent), "webp");
+        assert_eq!(choose_codec("mozjpeg", &transparent), "webp");
+        assert_eq!(choose_codec("avif", &transparent), "avif");
+    }
+}
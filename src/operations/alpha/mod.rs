@@ -0,0 +1,189 @@
+use zune_core::{bit_depth::BitType, colorspace::ColorSpace};
+use zune_image::{
+    errors::{ImageErrors, ImageOperationsErrors},
+    image::Image,
+    metadata::AlphaState,
+    traits::OperationsTrait,
+};
+
+use crate::operations::color::components;
+
+/// Flatten a transparent image onto a background color
+///
+/// Colors are composited over the background using alpha, which is dropped
+/// afterwards, so RGBA becomes RGB and LumaA becomes Luma.
+/// Images without alpha are left as is.
+pub struct Flatten {
+    background: [f32; 3],
+}
+
+impl Flatten {
+    /// Create a new flatten operation
+    ///
+    /// # Arguments
+    /// - background: RGB color in `0.0..=1.0` range, converted to luma for grayscale images
+    #[must_use]
+    pub fn new(background: [f32; 3]) -> Self {
+        Self { background }
+    }
+}
+
+impl OperationsTrait for Flatten {
+    fn name(&self) -> &'static str {
+        "flatten"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let colorspace = image.colorspace();
+
+        let Some(alpha_idx) = alpha_index(colorspace) else {
+            return Ok(());
+        };
+
+        let target = match colorspace {
+            ColorSpace::RGBA | ColorSpace::ARGB => ColorSpace::RGB,
+            ColorSpace::BGRA => ColorSpace::BGR,
+            ColorSpace::LumaA => ColorSpace::Luma,
+            _ => unreachable!("This should be handled in supported_colorspaces"),
+        };
+
+        let [r, g, b] = self.background;
+        let mut background = components(colorspace, [r, g, b, 1.])
+            .expect("This should be handled in supported_colorspaces");
+        background.remove(alpha_idx);
+        let premultiplied = image.metadata().is_premultiplied_alpha();
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_vec();
+            let alpha = channels.remove(alpha_idx);
+
+            for (channel, background) in channels.iter_mut().zip(&background) {
+                let composite = Composite {
+                    background: *background,
+                    premultiplied,
+                };
+
+                match depth {
+                    BitType::U8 => composite.apply(
+                        channel.reinterpret_as_mut::<u8>()?,
+                        alpha.reinterpret_as::<u8>()?,
+                        |v| f32::from(v) / 255.,
+                        |v| (v * 255.).round() as u8,
+                    ),
+                    BitType::U16 => composite.apply(
+                        channel.reinterpret_as_mut::<u16>()?,
+                        alpha.reinterpret_as::<u16>()?,
+                        |v| f32::from(v) / 65535.,
+                        |v| (v * 65535.).round() as u16,
+                    ),
+                    BitType::F32 => composite.apply(
+                        channel.reinterpret_as_mut::<f32>()?,
+                        alpha.reinterpret_as::<f32>()?,
+                        |v| v,
+                        |v| v,
+                    ),
+                    d => {
+                        return Err(ImageErrors::OperationsError(
+                            ImageOperationsErrors::UnsupportedType(self.name(), d),
+                        ))
+                    }
+                }
+            }
+        }
+
+        image.metadata_mut().set_colorspace(target);
+        image.metadata_mut().set_alpha(AlphaState::NonPreMultiplied);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::BGR,
+            ColorSpace::BGRA,
+            ColorSpace::ARGB,
+            ColorSpace::Luma,
+            ColorSpace::LumaA,
+        ]
+    }
+}
+
+/// Compositing of a single color channel over the background
+struct Composite {
+    background: f32,
+    premultiplied: bool,
+}
+
+impl Composite {
+    fn apply<T: Copy>(
+        &self,
+        color: &mut [T],
+        alpha: &[T],
+        to_f32: impl Fn(T) -> f32,
+        from_f32: impl Fn(f32) -> T,
+    ) {
+        for (c, a) in color.iter_mut().zip(alpha) {
+            let a = to_f32(*a).clamp(0., 1.);
+            let mut v = to_f32(*c);
+
+            if !self.premultiplied {
+                v *= a;
+            }
+
+            *c = from_f32((v + self.background * (1. - a)).clamp(0., 1.));
+        }
+    }
+}
+
+/// Index of the alpha channel in `colorspace`, if it has one
+pub(crate) fn alpha_index(colorspace: ColorSpace) -> Option<usize> {
+    match colorspace {
+        _ if !colorspace.has_alpha() => None,
+        ColorSpace::ARGB => Some(0),
+        _ => Some(colorspace.num_components() - 1),
+    }
+}
+
+/// Checks if every pixel of the image is fully opaque
+///
+/// Only the alpha channel is read, images without alpha are always opaque.
+pub fn is_opaque(image: &Image) -> Result<bool, ImageErrors> {
+    let Some(alpha_idx) = alpha_index(image.colorspace()) else {
+        return Ok(true);
+    };
+    let depth = image.depth().bit_type();
+
+    for frame in image.frames_ref() {
+        let alpha = &frame.channels_vec_ref()[alpha_idx];
+
+        let opaque = match depth {
+            BitType::U8 => alpha.reinterpret_as::<u8>()?.iter().all(|a| *a == u8::MAX),
+            BitType::U16 => alpha
+                .reinterpret_as::<u16>()?
+                .iter()
+                .all(|a| *a == u16::MAX),
+            BitType::F32 => alpha.reinterpret_as::<f32>()?.iter().all(|a| *a >= 1.),
+            d => {
+                return Err(ImageErrors::OperationsError(
+                    ImageOperationsErrors::UnsupportedType("is opaque", d),
+                ))
+            }
+        };
+
+        if !opaque {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests;
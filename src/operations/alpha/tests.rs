@@ -0,0 +1,71 @@
+use zune_core::colorspace::ColorSpace;
+
+use crate::test_utils::*;
+
+use super::*;
+
+#[test]
+fn flatten_u8() {
+    let flatten = Flatten::new([1., 1., 1.]);
+    let mut image = Image::from_u8(
+        &[255, 0, 0, 255, 255, 0, 0, 0, 0, 0, 255, 128],
+        3,
+        1,
+        ColorSpace::RGBA,
+    );
+
+    let result = flatten.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.colorspace(), ColorSpace::RGB);
+    assert_eq!(
+        image.flatten_to_u8()[0],
+        [255, 0, 0, 255, 255, 255, 127, 127, 255]
+    );
+}
+
+#[test]
+fn flatten_colorspaces() {
+    let flatten = Flatten::new([1., 0.5, 0.]);
+
+    for (colorspace, pixel, expected) in [
+        (ColorSpace::BGRA, [0, 0, 0, 0], [0, 128, 255].as_slice()),
+        (ColorSpace::ARGB, [0, 0, 0, 0], &[255, 128, 0]),
+        (ColorSpace::LumaA, [0, 0, 0, 0], &[151]),
+        (ColorSpace::RGB, [10, 20, 30, 40], &[10, 20, 30]),
+    ] {
+        let pixel = &pixel[..colorspace.num_components()];
+        let mut image = Image::from_u8(pixel, 1, 1, colorspace);
+
+        let result = flatten.execute(&mut image);
+        dbg!(&result);
+
+        assert!(result.is_ok());
+        assert!(!image.colorspace().has_alpha());
+        assert_eq!(image.flatten_to_u8()[0], expected, "{colorspace:?}");
+    }
+}
+
+#[test]
+fn flatten_premultiplied() {
+    let flatten = Flatten::new([1., 1., 1.]);
+    // quarter gray premultiplied by half alpha
+    let mut image = Image::from_u16(&[16384, 32768], 1, 1, ColorSpace::LumaA);
+    image.metadata_mut().set_alpha(AlphaState::PreMultiplied);
+
+    flatten.execute(&mut image).unwrap();
+
+    assert_eq!(image.colorspace(), ColorSpace::Luma);
+    assert_eq!(image.metadata().alpha(), AlphaState::NonPreMultiplied);
+    assert_eq!(image.flatten_to_u8()[0], [191]);
+}
+
+#[test]
+fn opaque() {
+    assert!(is_opaque(&create_test_image_u8(10, 10, ColorSpace::RGB)).unwrap());
+    assert!(is_opaque(&Image::fill(255_u8, ColorSpace::RGBA, 10, 10)).unwrap());
+    assert!(!is_opaque(&Image::fill(254_u8, ColorSpace::RGBA, 10, 10)).unwrap());
+    assert!(is_opaque(&Image::fill(1_f32, ColorSpace::LumaA, 10, 10)).unwrap());
+    assert!(!is_opaque(&Image::from_u8(&[0, 0, 0, 255], 1, 1, ColorSpace::ARGB)).unwrap());
+}
@@ -0,0 +1,138 @@
+use zune_core::bit_depth::BitType;
+use zune_image::{
+    errors::{ImageErrors, ImageOperationsErrors},
+    image::Image,
+    traits::OperationsTrait,
+};
+
+/// Stretch contrast of an image to the full value range
+///
+/// Every color channel is stretched separately over all frames, alpha is left intact.
+pub struct AutoContrast {
+    clip_percent: f32,
+}
+
+impl AutoContrast {
+    /// Create a new auto contrast operation
+    ///
+    /// # Arguments
+    /// - clip_percent: Percentage of the darkest and the brightest values of each channel
+    ///   ignored as outliers when looking for its range
+    #[must_use]
+    pub fn new(clip_percent: f32) -> Self {
+        Self { clip_percent }
+    }
+
+    /// Builds lookup table stretching the range found in `histogram` to the full scale
+    ///
+    /// Returns `None` when the range is empty and there is nothing to stretch.
+    fn lut(&self, histogram: &[usize]) -> Option<Vec<u16>> {
+        let total = histogram.iter().sum::<usize>();
+        let clip = (total as f64 * self.clip_percent.clamp(0., 50.) as f64 / 100.) as usize;
+
+        let bound = |mut values: Box<dyn Iterator<Item = (usize, &usize)> + '_>| {
+            let mut count = 0;
+
+            values.find_map(|(value, n)| {
+                count += n;
+                (count > clip).then_some(value)
+            })
+        };
+
+        let low = bound(Box::new(histogram.iter().enumerate()))?;
+        let high = bound(Box::new(histogram.iter().enumerate().rev()))?;
+
+        if high <= low {
+            return None;
+        }
+
+        let max = histogram.len() - 1;
+
+        Some(
+            (0..=max)
+                .map(|value| {
+                    let value = value.clamp(low, high) - low;
+
+                    ((value * max) as f64 / (high - low) as f64).round() as u16
+                })
+                .collect(),
+        )
+    }
+}
+
+impl OperationsTrait for AutoContrast {
+    fn name(&self) -> &'static str {
+        "auto contrast"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let depth = image.depth().bit_type();
+        let colorspace = image.colorspace();
+
+        let components = colorspace.num_components();
+        let alpha = colorspace.alpha_position();
+
+        let mut histograms = vec![vec![0; image.depth().max_value() as usize + 1]; components];
+
+        // alpha is skipped by its position, as `ignore_alpha` mistakes luma for alpha in LumaA
+        for (idx, channel) in image.channels_ref(false).into_iter().enumerate() {
+            if alpha == Some(idx % components) {
+                continue;
+            }
+
+            let histogram = &mut histograms[idx % components];
+
+            match depth {
+                BitType::U8 => channel
+                    .reinterpret_as::<u8>()?
+                    .iter()
+                    .for_each(|v| histogram[*v as usize] += 1),
+                BitType::U16 => channel
+                    .reinterpret_as::<u16>()?
+                    .iter()
+                    .for_each(|v| histogram[*v as usize] += 1),
+                d => {
+                    return Err(ImageErrors::OperationsError(
+                        ImageOperationsErrors::UnsupportedType(self.name(), d),
+                    ))
+                }
+            }
+        }
+
+        let luts = histograms
+            .iter()
+            .map(|histogram| self.lut(histogram))
+            .collect::<Vec<_>>();
+
+        for (idx, channel) in image.channels_mut(false).into_iter().enumerate() {
+            if alpha == Some(idx % components) {
+                continue;
+            }
+
+            let Some(lut) = &luts[idx % components] else {
+                continue;
+            };
+
+            match depth {
+                BitType::U8 => channel
+                    .reinterpret_as_mut::<u8>()?
+                    .iter_mut()
+                    .for_each(|v| *v = lut[*v as usize] as u8),
+                BitType::U16 => channel
+                    .reinterpret_as_mut::<u16>()?
+                    .iter_mut()
+                    .for_each(|v| *v = lut[*v as usize]),
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16]
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,85 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+
+use super::*;
+
+#[test]
+fn stretch_u8() {
+    let auto_contrast = AutoContrast::new(0.);
+    let mut image = Image::from_fn(16, 16, ColorSpace::RGB, |x, y, px: &mut [u8; 4]| {
+        px[..3].copy_from_slice(&[100 + x as u8, 120 + y as u8, 100 + (x + y) as u8 / 2]);
+    });
+
+    let result = auto_contrast.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let pixels = &image.flatten_to_u8()[0];
+
+    for channel in 0..3 {
+        let values = pixels.iter().skip(channel).step_by(3);
+
+        assert_eq!(values.clone().min(), Some(&0));
+        assert_eq!(values.max(), Some(&255));
+    }
+}
+
+#[test]
+fn stretch_u16_keeps_alpha() {
+    let auto_contrast = AutoContrast::new(0.);
+    let pixels = (0..256u16)
+        .flat_map(|v| [30_000 + v * 10, 40_000])
+        .collect::<Vec<_>>();
+    let mut image = Image::from_u16(&pixels, 16, 16, ColorSpace::LumaA);
+
+    let result = auto_contrast.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.depth(), BitDepth::Sixteen);
+
+    let channels = image.channels_ref(false);
+    let luma = channels[0].reinterpret_as::<u16>().unwrap();
+    let alpha = channels[1].reinterpret_as::<u16>().unwrap();
+
+    assert_eq!(luma.iter().min(), Some(&0));
+    assert_eq!(luma.iter().max(), Some(&u16::MAX));
+    assert!(alpha.iter().all(|v| *v == 40_000));
+}
+
+#[test]
+fn clip_outliers() {
+    let auto_contrast = AutoContrast::new(1.);
+    let pixels = (0..256)
+        .map(|idx| match idx {
+            0 => 0,
+            255 => 255,
+            idx => 100 + (idx % 16 + idx / 16) as u8,
+        })
+        .collect::<Vec<_>>();
+    let mut image = Image::from_u8(&pixels, 16, 16, ColorSpace::Luma);
+
+    let result = auto_contrast.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let pixels = &image.flatten_to_u8()[0];
+
+    // 1% of 256 pixels is 2, so the outliers along with one more value on each end are clipped
+    assert_eq!(pixels[1], 0);
+    assert_eq!(pixels[16 * 16 - 2], 255);
+    assert!(pixels.iter().filter(|v| **v == 0).count() >= 2);
+}
+
+#[test]
+fn flat_image_untouched() {
+    let auto_contrast = AutoContrast::new(0.);
+    let mut image = Image::from_u8(&[42; 16], 4, 4, ColorSpace::Luma);
+
+    let result = auto_contrast.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.flatten_to_u8()[0], [42; 16]);
+}
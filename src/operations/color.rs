@@ -0,0 +1,38 @@
+use zune_core::colorspace::ColorSpace;
+
+/// Converts straight RGBA color in `0.0..=1.0` range into components of `colorspace`
+///
+/// Components are in channel order of the colorspace, CMYK is stored inverted
+/// like zune-image decodes it, and YCbCr uses full range BT.601.
+/// Returns `None` for colorspaces without a known conversion.
+pub(crate) fn components(colorspace: ColorSpace, rgba: [f32; 4]) -> Option<Vec<f32>> {
+    let [r, g, b, a] = rgba.map(|c| c.clamp(0., 1.));
+    let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+
+    let components = match colorspace {
+        ColorSpace::RGB => vec![r, g, b],
+        ColorSpace::RGBA => vec![r, g, b, a],
+        ColorSpace::BGR => vec![b, g, r],
+        ColorSpace::BGRA => vec![b, g, r, a],
+        ColorSpace::ARGB => vec![a, r, g, b],
+        ColorSpace::Luma => vec![luma],
+        ColorSpace::LumaA => vec![luma, a],
+        ColorSpace::YCbCr => vec![
+            luma,
+            0.5 - 0.168_736 * r - 0.331_264 * g + 0.5 * b,
+            0.5 + 0.5 * r - 0.418_688 * g - 0.081_312 * b,
+        ],
+        ColorSpace::CMYK => {
+            let max = r.max(g).max(b);
+
+            if max == 0. {
+                vec![1., 1., 1., 0.]
+            } else {
+                vec![r / max, g / max, b / max, max]
+            }
+        }
+        _ => return None,
+    };
+
+    Some(components.into_iter().map(|c| c.clamp(0., 1.)).collect())
+}
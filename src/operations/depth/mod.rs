@@ -0,0 +1,125 @@
+use zune_core::bit_depth::{BitDepth, BitType};
+use zune_image::{
+    channel::Channel, core_filters::depth, errors::ImageErrors, image::Image,
+    traits::OperationsTrait,
+};
+
+/// Change bit depth of an image
+///
+/// Works like [`depth::Depth`], but can diffuse the rounding error
+/// when reducing 16-bit or float images to 8 bits, so smooth gradients
+/// don't break into visible bands. Alpha is rounded without dithering.
+#[derive(Clone, Copy)]
+pub struct Depth {
+    depth: BitDepth,
+    dither: bool,
+}
+
+impl Depth {
+    /// Create a new depth operation converting the image to `depth`
+    #[must_use]
+    pub fn new(depth: BitDepth) -> Self {
+        Self {
+            depth,
+            dither: false,
+        }
+    }
+
+    /// Use Floyd-Steinberg error diffusion when reducing depth to 8 bits
+    #[must_use]
+    pub fn with_dithering(mut self, dither: bool) -> Self {
+        self.dither = dither;
+        self
+    }
+}
+
+impl OperationsTrait for Depth {
+    fn name(&self) -> &'static str {
+        "Depth"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let scale = match image.depth() {
+            BitDepth::Sixteen => 255. / f32::from(u16::MAX),
+            BitDepth::Float32 => 255.,
+            _ => return depth::Depth::new(self.depth).execute_impl(image),
+        };
+
+        if !self.dither || self.depth != BitDepth::Eight {
+            return depth::Depth::new(self.depth).execute_impl(image);
+        }
+
+        let (width, _) = image.dimensions();
+        let colorspace = image.colorspace();
+        let alpha = colorspace
+            .has_alpha()
+            .then(|| colorspace.num_components() - 1);
+        let bit_type = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            for (idx, channel) in frame.channels_vec().iter_mut().enumerate() {
+                let values = match bit_type {
+                    BitType::U16 => channel
+                        .reinterpret_as::<u16>()?
+                        .iter()
+                        .map(|v| f32::from(*v) * scale)
+                        .collect::<Vec<_>>(),
+                    _ => channel
+                        .reinterpret_as::<f32>()?
+                        .iter()
+                        .map(|v| v * scale)
+                        .collect(),
+                };
+
+                let mut new_channel = Channel::new_with_length::<u8>(values.len());
+                let out = new_channel.reinterpret_as_mut::<u8>()?;
+
+                if Some(idx) == alpha {
+                    out.iter_mut()
+                        .zip(&values)
+                        .for_each(|(out, v)| *out = v.round().clamp(0., 255.) as u8);
+                } else {
+                    diffuse(&values, out, width);
+                }
+
+                *channel = new_channel;
+            }
+        }
+
+        image.set_depth(BitDepth::Eight);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Quantizes `values` in `0.0..=255.0` range to `out` with Floyd-Steinberg dithering
+fn diffuse(values: &[f32], out: &mut [u8], width: usize) {
+    // errors carried to the current and the next row, padded by a pixel on both sides
+    let mut current = vec![0f32; width + 2];
+    let mut next = vec![0f32; width + 2];
+
+    for (row, out_row) in values.chunks_exact(width).zip(out.chunks_exact_mut(width)) {
+        for (x, (value, out)) in row.iter().zip(out_row.iter_mut()).enumerate() {
+            let value = value + current[x + 1];
+            let quantized = value.round().clamp(0., 255.);
+            let error = value - quantized;
+
+            *out = quantized as u8;
+
+            current[x + 2] += error * 7. / 16.;
+            next[x] += error * 3. / 16.;
+            next[x + 1] += error * 5. / 16.;
+            next[x + 2] += error / 16.;
+        }
+
+        std::mem::swap(&mut current, &mut next);
+        next.fill(0.);
+    }
+}
+
+#[cfg(test)]
+mod tests;
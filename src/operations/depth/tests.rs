@@ -0,0 +1,94 @@
+use zune_core::colorspace::ColorSpace;
+
+use super::*;
+
+/// Mean distance between averages of 16x16 blocks of `image` and `source`, in 8-bit units
+fn banding(image: &Image, source: &[u16], width: usize) -> f64 {
+    let pixels = &image.flatten_to_u8()[0];
+
+    let mut total = 0.;
+    let blocks = width / 16;
+
+    for block in 0..blocks {
+        let (mut out, mut expected) = (0., 0.);
+
+        for y in 0..16 {
+            for x in block * 16..(block + 1) * 16 {
+                out += f64::from(pixels[y * width + x]);
+                expected += f64::from(source[y * width + x]) / 257.;
+            }
+        }
+
+        total += (out - expected).abs() / 256.;
+    }
+
+    total / blocks as f64
+}
+
+#[test]
+fn dither_gradient() {
+    // shallow gradient, every 8-bit level spans 8 pixels
+    let width = 512;
+    let source = (0..width * 16)
+        .map(|idx| (idx % width * 32) as u16)
+        .collect::<Vec<_>>();
+    let image = Image::from_u16(&source, width, 16, ColorSpace::Luma);
+
+    let mut truncated = image.clone();
+    Depth::new(BitDepth::Eight).execute(&mut truncated).unwrap();
+
+    let mut dithered = image.clone();
+    Depth::new(BitDepth::Eight)
+        .with_dithering(true)
+        .execute(&mut dithered)
+        .unwrap();
+
+    assert_eq!(dithered.depth(), BitDepth::Eight);
+    assert_eq!(dithered.dimensions(), (width, 16));
+
+    let truncated = banding(&truncated, &source, width);
+    let dithered_banding = banding(&dithered, &source, width);
+
+    assert!(
+        dithered_banding * 4. < truncated,
+        "{dithered_banding} vs {truncated}"
+    );
+
+    // noise stays within a single level of the source
+    let pixels = &dithered.flatten_to_u8()[0];
+    assert!(pixels
+        .iter()
+        .zip(&source)
+        .all(|(out, v)| (f32::from(*out) - f32::from(*v) / 257.).abs() <= 1.));
+}
+
+#[test]
+fn dither_keeps_alpha() {
+    let pixels = [30_000u16, 40_000, 50_000, 32_896].repeat(32 * 32);
+    let mut image = Image::from_u16(&pixels, 32, 32, ColorSpace::RGBA);
+
+    Depth::new(BitDepth::Eight)
+        .with_dithering(true)
+        .execute(&mut image)
+        .unwrap();
+
+    let pixels = &image.flatten_to_u8()[0];
+
+    assert!(pixels.chunks_exact(4).all(|px| px[3] == 128));
+    assert!(pixels.chunks_exact(4).any(|px| px[0] != pixels[0]));
+}
+
+#[test]
+fn without_dithering() {
+    let image = Image::from_u16(&[0, 257, 40_000, u16::MAX], 2, 2, ColorSpace::Luma);
+
+    let mut ours = image.clone();
+    Depth::new(BitDepth::Eight).execute(&mut ours).unwrap();
+
+    let mut zune = image.clone();
+    depth::Depth::new(BitDepth::Eight)
+        .execute(&mut zune)
+        .unwrap();
+
+    assert_eq!(ours.flatten_to_u8(), zune.flatten_to_u8());
+}
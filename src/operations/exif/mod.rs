@@ -0,0 +1,124 @@
+use exif::{experimental::Writer, Context, Field, In, Tag, Value};
+use zune_core::bit_depth::BitType;
+use zune_image::{errors::ImageErrors, image::Image, traits::OperationsTrait};
+
+use crate::Error;
+
+/// Strip GPS tags from the image EXIF metadata
+///
+/// All other tags, like camera information or orientation, are left intact.
+pub struct StripGps;
+
+impl OperationsTrait for StripGps {
+    fn name(&self) -> &'static str {
+        "strip gps"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if let Some(fields) = image.metadata_mut().exif_mut() {
+            fields.retain(|field| {
+                field.tag.context() != Context::Gps && field.tag != Tag::GPSInfoIFDPointer
+            });
+        }
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Strip the MakerNote tag and the Interoperability IFD from the image EXIF metadata
+///
+/// Vendor MakerNotes, which carry things like as-shot white balance, often reference
+/// data by absolute offsets that are no longer valid once the EXIF block is rewritten.
+pub struct StripMakerNote;
+
+impl OperationsTrait for StripMakerNote {
+    fn name(&self) -> &'static str {
+        "strip makernote"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if let Some(fields) = image.metadata_mut().exif_mut() {
+            fields.retain(|field| {
+                field.tag.context() != Context::Interop
+                    && field.tag != Tag::MakerNote
+                    && field.tag != Tag::InteropIFDPointer
+            });
+        }
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+/// Write the EXIF orientation tag without transforming pixels
+///
+/// Useful to fix images from cameras that tag orientation wrongly. Images
+/// without EXIF metadata get a new block holding only the orientation.
+pub struct SetOrientation {
+    orientation: u16,
+}
+
+impl SetOrientation {
+    /// Create a new operation writing `orientation`
+    ///
+    /// Valid values are from 1 to 8, as defined by the EXIF specification.
+    #[must_use]
+    pub fn new(orientation: u16) -> Self {
+        Self { orientation }
+    }
+}
+
+impl OperationsTrait for SetOrientation {
+    fn name(&self) -> &'static str {
+        "set orientation"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        if !(1..=8).contains(&self.orientation) {
+            return Err(Error::InvalidOrientation(self.orientation).into());
+        }
+
+        let field = Field {
+            tag: Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value: Value::Short(vec![self.orientation]),
+        };
+
+        match image.metadata_mut().exif_mut() {
+            Some(fields) => match fields
+                .iter_mut()
+                .find(|f| f.tag == Tag::Orientation && f.ifd_num == In::PRIMARY)
+            {
+                Some(existing) => *existing = field,
+                None => fields.push(field),
+            },
+            None => {
+                let mut writer = Writer::new();
+                writer.push_field(&field);
+
+                let mut buf = std::io::Cursor::new(vec![]);
+                writer
+                    .write(&mut buf, false)
+                    .map_err(|e| ImageErrors::GenericString(e.to_string()))?;
+
+                image.metadata_mut().parse_raw_exif(buf.get_ref());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,123 @@
+use exif::{Field, In, Rational, Tag, Value};
+use zune_core::colorspace::ColorSpace;
+
+use crate::test_utils::*;
+
+use super::*;
+
+fn field(tag: Tag, value: Value) -> Field {
+    Field {
+        tag,
+        ifd_num: In::PRIMARY,
+        value,
+    }
+}
+
+#[test]
+fn strip_gps() {
+    let mut image = create_test_image_u8(100, 100, ColorSpace::RGB);
+
+    let fields = [
+        field(
+            Tag::DateTimeOriginal,
+            Value::Ascii(vec![b"2024:01:01 12:00:00".to_vec()]),
+        ),
+        field(Tag::Orientation, Value::Short(vec![1])),
+        field(Tag::GPSLatitudeRef, Value::Ascii(vec![b"N".to_vec()])),
+        field(
+            Tag::GPSLatitude,
+            Value::Rational(vec![
+                Rational::from((50, 1)),
+                Rational::from((27, 1)),
+                Rational::from((0, 1)),
+            ]),
+        ),
+    ];
+
+    image.metadata_mut().parse_raw_exif(&raw_exif(&fields));
+
+    assert!(image
+        .metadata()
+        .exif()
+        .unwrap()
+        .iter()
+        .any(|f| f.tag == Tag::GPSLatitude));
+
+    StripGps.execute(&mut image).unwrap();
+
+    let exif = image.metadata().exif().unwrap();
+
+    assert!(exif.iter().all(|f| f.tag.context() != Context::Gps));
+    assert!(exif.iter().any(|f| f.tag == Tag::DateTimeOriginal));
+    assert!(exif.iter().any(|f| f.tag == Tag::Orientation));
+}
+
+#[test]
+fn strip_makernote() {
+    let mut image = create_test_image_u8(100, 100, ColorSpace::RGB);
+
+    let fields = [
+        field(Tag::Orientation, Value::Short(vec![1])),
+        field(
+            Tag::MakerNote,
+            Value::Undefined(b"WB 2.1 1.0 1.6".to_vec(), 0),
+        ),
+        field(
+            Tag::InteroperabilityIndex,
+            Value::Ascii(vec![b"R98".to_vec()]),
+        ),
+    ];
+
+    image.metadata_mut().parse_raw_exif(&raw_exif(&fields));
+
+    assert!(image
+        .metadata()
+        .exif()
+        .unwrap()
+        .iter()
+        .any(|f| f.tag == Tag::MakerNote));
+
+    StripMakerNote.execute(&mut image).unwrap();
+
+    let exif = image.metadata().exif().unwrap();
+
+    assert!(exif.iter().all(|f| f.tag.context() != Context::Interop));
+    assert!(exif.iter().all(|f| f.tag != Tag::MakerNote));
+    assert!(exif.iter().any(|f| f.tag == Tag::Orientation));
+}
+
+#[test]
+fn set_orientation() {
+    let orientation = |image: &Image| {
+        image
+            .metadata()
+            .exif()
+            .unwrap()
+            .iter()
+            .find(|f| f.tag == Tag::Orientation)
+            .and_then(|f| f.value.get_uint(0))
+    };
+
+    let mut image = create_test_image_u8(100, 50, ColorSpace::RGB);
+
+    SetOrientation::new(6).execute(&mut image).unwrap();
+
+    assert_eq!(orientation(&image), Some(6));
+    assert_eq!(image.dimensions(), (100, 50));
+
+    SetOrientation::new(3).execute(&mut image).unwrap();
+
+    assert_eq!(orientation(&image), Some(3));
+    assert_eq!(
+        image
+            .metadata()
+            .exif()
+            .unwrap()
+            .iter()
+            .filter(|f| f.tag == Tag::Orientation)
+            .count(),
+        1
+    );
+
+    assert!(SetOrientation::new(9).execute(&mut image).is_err());
+}
@@ -0,0 +1,106 @@
+use zune_core::{
+    bit_depth::{BitDepth, BitType},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    channel::Channel, core_filters::colorspace::ColorspaceConv, errors::ImageErrors, image::Image,
+    traits::OperationsTrait,
+};
+
+mod scalar;
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+mod ssse3;
+
+/// Convert an image to grayscale
+///
+/// Produces the same luma as zune-image colorspace conversion, 8-bit RGB and RGBA
+/// images are converted with SSSE3 when the `simd` feature is enabled and the CPU
+/// supports it, other images fall back to zune-image.
+#[derive(Default)]
+pub struct Grayscale {
+    preserve_alpha: bool,
+}
+
+impl Grayscale {
+    /// Create a new grayscale operation
+    ///
+    /// By default alpha channel is dropped, producing a Luma image.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep alpha channel of RGBA images, producing a LumaA image
+    #[must_use]
+    pub fn with_alpha(mut self, preserve_alpha: bool) -> Self {
+        self.preserve_alpha = preserve_alpha;
+        self
+    }
+}
+
+/// Compute luma of planar 8-bit RGB into `out`
+///
+/// All slices must have the same length.
+pub fn rgb_to_luma(r: &[u8], g: &[u8], b: &[u8], out: &mut [u8]) {
+    #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+    if is_x86_feature_detected!("ssse3") {
+        // SAFETY: SSSE3 support was just checked
+        return unsafe { ssse3::rgb_to_luma(r, g, b, out) };
+    }
+
+    scalar::rgb_to_luma(r, g, b, out);
+}
+
+impl OperationsTrait for Grayscale {
+    fn name(&self) -> &'static str {
+        "grayscale"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let colorspace = image.colorspace();
+        let alpha = self.preserve_alpha && colorspace.has_alpha();
+        let target = if alpha {
+            ColorSpace::LumaA
+        } else {
+            ColorSpace::Luma
+        };
+
+        if matches!(colorspace, ColorSpace::Luma | ColorSpace::LumaA) {
+            return Ok(());
+        }
+
+        if image.depth() != BitDepth::Eight
+            || !matches!(colorspace, ColorSpace::RGB | ColorSpace::RGBA)
+        {
+            return ColorspaceConv::new(target).execute_impl(image);
+        }
+
+        let (width, height) = image.dimensions();
+
+        for frame in image.frames_mut() {
+            let channels = frame.channels_vec();
+            let mut luma = Channel::new_with_length::<u8>(width * height);
+
+            rgb_to_luma(
+                channels[0].reinterpret_as()?,
+                channels[1].reinterpret_as()?,
+                channels[2].reinterpret_as()?,
+                luma.reinterpret_as_mut()?,
+            );
+
+            let alpha_channel = alpha.then(|| channels.swap_remove(3));
+            *channels = [luma].into_iter().chain(alpha_channel).collect();
+        }
+
+        image.metadata_mut().set_colorspace(target);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,17 @@
+/// Fixed point Rec. 601 luma coefficients of red, green and blue, scaled by 2^15
+pub(super) const COEFFICIENTS: [u32; 3] = [9794, 19235, 3736];
+
+pub(super) fn rgb_to_luma(r: &[u8], g: &[u8], b: &[u8], out: &mut [u8]) {
+    for (((r, g), b), out) in r.iter().zip(g).zip(b).zip(out) {
+        *out = luma(*r, *g, *b);
+    }
+}
+
+/// Luma of a single pixel, rounding each weighted channel like zune-image does
+pub(super) fn luma(r: u8, g: u8, b: u8) -> u8 {
+    let weight = |value: u8, coefficient: u32| (u32::from(value) * coefficient + 256) >> 9;
+
+    let sum = weight(r, COEFFICIENTS[0]) + weight(g, COEFFICIENTS[1]) + weight(b, COEFFICIENTS[2]);
+
+    (sum >> 6).min(255) as u8
+}
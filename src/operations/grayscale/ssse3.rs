@@ -0,0 +1,54 @@
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use super::scalar::{self, COEFFICIENTS};
+
+/// Computes luma of 16 pixels per iteration, remaining pixels are computed by scalar code
+///
+/// `pmulhrsw` computes `(a * b + 2^14) >> 15`, which is exactly the per-channel
+/// rounding of the scalar code, so results are identical.
+///
+/// # Safety
+/// The CPU must support SSSE3.
+#[target_feature(enable = "ssse3")]
+pub(super) unsafe fn rgb_to_luma(r: &[u8], g: &[u8], b: &[u8], out: &mut [u8]) {
+    let len = out.len().min(r.len()).min(g.len()).min(b.len());
+    let simd_len = len - len % 16;
+
+    let zero = _mm_setzero_si128();
+    let coefficients = COEFFICIENTS.map(|c| _mm_set1_epi16(c as i16));
+
+    // scaling by 64 like the scalar code keeps products within 16 bits
+    let weight = |values: &[u8], coefficient: __m128i| {
+        let values = _mm_loadu_si128(values.as_ptr().cast());
+
+        [
+            _mm_unpacklo_epi8(values, zero),
+            _mm_unpackhi_epi8(values, zero),
+        ]
+        .map(|values| _mm_mulhrs_epi16(_mm_slli_epi16(values, 6), coefficient))
+    };
+
+    for idx in (0..simd_len).step_by(16) {
+        let [r_lo, r_hi] = weight(&r[idx..idx + 16], coefficients[0]);
+        let [g_lo, g_hi] = weight(&g[idx..idx + 16], coefficients[1]);
+        let [b_lo, b_hi] = weight(&b[idx..idx + 16], coefficients[2]);
+
+        let lo = _mm_srli_epi16(_mm_add_epi16(_mm_add_epi16(r_lo, g_lo), b_lo), 6);
+        let hi = _mm_srli_epi16(_mm_add_epi16(_mm_add_epi16(r_hi, g_hi), b_hi), 6);
+
+        _mm_storeu_si128(
+            out[idx..idx + 16].as_mut_ptr().cast(),
+            _mm_packus_epi16(lo, hi),
+        );
+    }
+
+    scalar::rgb_to_luma(
+        &r[simd_len..len],
+        &g[simd_len..len],
+        &b[simd_len..len],
+        &mut out[simd_len..len],
+    );
+}
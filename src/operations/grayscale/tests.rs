@@ -0,0 +1,108 @@
+use zune_core::colorspace::ColorSpace;
+
+use super::*;
+
+/// Pixels covering extreme values and a spread of colors, with a length not divisible by 8
+fn test_pixels(len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|idx| match idx % 7 {
+            0 => 255,
+            1 => 0,
+            _ => (idx * 37 + idx / 3) as u8,
+        })
+        .collect()
+}
+
+#[test]
+fn matches_colorspace_conversion() {
+    let pixels = test_pixels(37 * 13 * 4);
+    let mut image = Image::from_u8(&pixels, 37, 13, ColorSpace::RGBA);
+    let mut expected = image.clone();
+
+    let result = Grayscale::new().execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.colorspace(), ColorSpace::Luma);
+
+    expected.convert_color(ColorSpace::Luma).unwrap();
+    assert_eq!(image.flatten_to_u8(), expected.flatten_to_u8());
+}
+
+#[test]
+fn preserve_alpha() {
+    let mut image = Image::from_u8(&[255, 255, 255, 10, 0, 0, 0, 20], 2, 1, ColorSpace::RGBA);
+
+    let result = Grayscale::new().with_alpha(true).execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.colorspace(), ColorSpace::LumaA);
+    // fixed point coefficients round white slightly down, like zune-image does
+    assert_eq!(image.flatten_to_u8()[0], [254, 10, 0, 20]);
+}
+
+#[test]
+fn grayscale_u16() {
+    let mut image = Image::from_u16(&[65535, 65535, 65535, 0, 0, 0], 2, 1, ColorSpace::RGB);
+
+    let result = Grayscale::new().execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.colorspace(), ColorSpace::Luma);
+    assert_eq!(image.depth(), BitDepth::Sixteen);
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+#[test]
+fn simd_matches_scalar() {
+    let pixels = test_pixels(3 * 1021);
+    let (r, rest) = pixels.split_at(1021);
+    let (g, b) = rest.split_at(1021);
+
+    let mut simd = vec![0; 1021];
+    let mut scalar = vec![0; 1021];
+
+    if !is_x86_feature_detected!("ssse3") {
+        return;
+    }
+
+    unsafe { ssse3::rgb_to_luma(r, g, b, &mut simd) };
+    scalar::rgb_to_luma(r, g, b, &mut scalar);
+
+    assert_eq!(simd, scalar);
+}
+
+/// Run with `cargo test --release --features simd -- --ignored grayscale_benchmark`
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+#[test]
+#[ignore = "benchmark"]
+fn grayscale_benchmark() {
+    use std::time::{Duration, Instant};
+
+    const LEN: usize = 4096 * 4096;
+
+    let pixels = test_pixels(3 * LEN);
+    let (r, rest) = pixels.split_at(LEN);
+    let (g, b) = rest.split_at(LEN);
+    let mut out = vec![0; LEN];
+
+    type Convert = fn(&[u8], &[u8], &[u8], &mut [u8]);
+
+    let mut time = |convert: Convert| {
+        (0..5)
+            .map(|_| {
+                let start = Instant::now();
+                convert(r, g, b, &mut out);
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or(Duration::MAX)
+    };
+
+    let scalar = time(scalar::rgb_to_luma);
+    let simd = time(|r, g, b, out| unsafe { ssse3::rgb_to_luma(r, g, b, out) });
+
+    assert!(simd < scalar, "scalar: {scalar:?}, ssse3: {simd:?}");
+}
@@ -1,15 +1,28 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
 use lcms2::*;
 use zune_core::{bit_depth::BitType, colorspace::ColorSpace};
-use zune_image::{
-    errors::{ImageErrors, ImageOperationsErrors},
-    frame::Frame,
-    image::Image,
-    traits::OperationsTrait,
-};
+use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::OperationsTrait};
+
+use crate::Error;
 
 /// Apply icc profile
+///
+/// An embedded profile that lcms2 can't read or transform from, like a
+/// malformed one, is dropped with a warning and the image is converted as if
+/// it was untagged. Failing to transform into the target profile is an error.
+///
+/// Profiles are parsed once and shared between clones, so a single operation
+/// can be cloned for every processed image.
+#[derive(Clone)]
 pub struct ApplyICC {
-    profile: Profile<ThreadContext>,
+    profile: Arc<Mutex<Profile<ThreadContext>>>,
+    icc: Option<Vec<u8>>,
+    input_profile: Option<Arc<Mutex<Profile<ThreadContext>>>>,
 }
 
 impl ApplyICC {
@@ -19,7 +32,69 @@ impl ApplyICC {
     /// - profile: ICC profile
     #[must_use]
     pub fn new(profile: Profile<ThreadContext>) -> Self {
-        Self { profile }
+        Self {
+            profile: Arc::new(Mutex::new(profile)),
+            icc: None,
+            input_profile: None,
+        }
+    }
+
+    /// Create a new icc apply operation from contents of an ICC profile
+    ///
+    /// The profile is validated by lcms2, converted images are tagged
+    /// with `icc` exactly as given.
+    pub fn from_icc(icc: &[u8]) -> Result<Self, Error> {
+        let profile = Profile::new_icc_context(ThreadContext::new(), icc)?;
+
+        Ok(Self {
+            icc: Some(icc.to_vec()),
+            ..Self::new(profile)
+        })
+    }
+
+    /// Create a new icc apply operation from an ICC profile file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let icc = fs::read(path).map_err(Error::IccRead)?;
+
+        Self::from_icc(&icc)
+    }
+
+    /// Profile assumed for images without an embedded ICC profile
+    ///
+    /// By default such images are assumed to be sRGB.
+    #[must_use]
+    pub fn with_input_profile(mut self, profile: Profile<ThreadContext>) -> Self {
+        self.input_profile = Some(Arc::new(Mutex::new(profile)));
+        self
+    }
+
+    /// Checks that images can be converted into the target profile
+    ///
+    /// Parsing a profile doesn't guarantee that lcms2 can transform into it,
+    /// like for a grayscale profile, so this builds a transform for 8-bit RGBA
+    /// images from the assumed input profile, sRGB by default.
+    pub fn validate(&self) -> Result<(), Error> {
+        let input_profile = self.input_profile.as_deref().map(lock);
+        let srgb;
+        let src_profile = match &input_profile {
+            Some(profile) => profile,
+            None => {
+                srgb = Profile::new_srgb_context(ThreadContext::new());
+                &srgb
+            }
+        };
+
+        Transform::<u8, u8, _, _>::new_flags_context(
+            ThreadContext::new(),
+            src_profile,
+            PixelFormat::RGBA_8,
+            &lock(&self.profile),
+            PixelFormat::RGBA_8,
+            Intent::Perceptual,
+            Flags::NO_CACHE,
+        )?;
+
+        Ok(())
     }
 }
 
@@ -29,10 +104,27 @@ impl OperationsTrait for ApplyICC {
     }
 
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
-        let src_profile = match image.metadata().icc_chunk() {
-            Some(icc) => Profile::new_icc_context(ThreadContext::new(), icc)
-                .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?,
-            None => Profile::new_srgb_context(ThreadContext::new()),
+        let embedded = match image
+            .metadata()
+            .icc_chunk()
+            .map(|icc| Profile::new_icc_context(ThreadContext::new(), icc))
+            .transpose()
+        {
+            Ok(embedded) => embedded,
+            Err(e) => {
+                log::warn!("Invalid embedded icc profile, assuming untagged image: {e}");
+                None
+            }
+        };
+
+        let input_profile = self.input_profile.as_deref().map(lock);
+        let srgb;
+        let fallback = match &input_profile {
+            Some(profile) => profile,
+            None => {
+                srgb = Profile::new_srgb_context(ThreadContext::new());
+                &srgb
+            }
         };
 
         let colorspace = image.colorspace();
@@ -61,16 +153,36 @@ impl OperationsTrait for ApplyICC {
             _ => unreachable!("This should be handled in supported_colorspaces"),
         };
 
-        let t = Transform::new_flags_context(
-            ThreadContext::new(),
-            &src_profile,
-            format,
-            &self.profile,
-            format,
-            Intent::Perceptual,
-            Flags::NO_CACHE,
-        )
-        .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
+        let profile = lock(&self.profile);
+        let transform = |src_profile| {
+            Transform::new_flags_context(
+                ThreadContext::new(),
+                src_profile,
+                format,
+                &*profile,
+                format,
+                Intent::Perceptual,
+                Flags::NO_CACHE,
+            )
+        };
+
+        let t = match embedded.as_ref().map(transform) {
+            Some(Ok(t)) => t,
+            Some(Err(e)) => {
+                log::warn!(
+                    "Unable to transform embedded icc profile, assuming untagged image: {e}"
+                );
+                transform(fallback).map_err(Error::from)?
+            }
+            None => transform(fallback).map_err(Error::from)?,
+        };
+
+        let icc = match &self.icc {
+            Some(icc) => icc.clone(),
+            None => profile.icc().map_err(Error::from)?,
+        };
+        drop(profile);
+        drop(input_profile);
 
         for frame in image.frames_mut() {
             let mut buffer = frame.flatten::<u8>(colorspace);
@@ -78,11 +190,7 @@ impl OperationsTrait for ApplyICC {
             let _ = std::mem::replace(frame, Frame::from_u8(&buffer, colorspace, 0, 0));
         }
 
-        image.metadata_mut().set_icc_chunk(
-            self.profile
-                .icc()
-                .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?,
-        );
+        image.metadata_mut().set_icc_chunk(icc);
 
         Ok(())
     }
@@ -107,6 +215,11 @@ impl OperationsTrait for ApplyICC {
     }
 }
 
+/// Locks a shared profile, profiles are left intact by a panicking holder
+fn lock(profile: &Mutex<Profile<ThreadContext>>) -> MutexGuard<'_, Profile<ThreadContext>> {
+    profile.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 /// Apply srgb icc profile
 pub struct ApplySRGB;
 
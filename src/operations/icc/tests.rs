@@ -1,3 +1,6 @@
+use lcms2::{CIExyY, CIExyYTRIPLE, ToneCurve};
+use zune_image::codecs::ImageFormat;
+
 use crate::test_utils::*;
 
 use super::*;
@@ -22,6 +25,33 @@ fn apply_icc_profile() {
     assert_eq!(*image.metadata().icc_chunk().unwrap(), icc);
 }
 
+#[test]
+fn apply_icc_profile_from_file() {
+    let mut image = Image::from_u8(&[200, 100, 50, 10, 20, 30], 2, 1, ColorSpace::RGB);
+    image
+        .metadata_mut()
+        .set_icc_chunk(Profile::new_srgb().icc().unwrap());
+
+    let apply_icc = ApplyICC::from_file("tests/files/icc/tinysrgb.icc").unwrap();
+    let result = apply_icc.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(
+        *image.metadata().icc_chunk().unwrap(),
+        std::fs::read("tests/files/icc/tinysrgb.icc").unwrap()
+    );
+
+    assert!(matches!(
+        ApplyICC::from_file("tests/files/icc/missing.icc"),
+        Err(Error::IccRead(_))
+    ));
+    assert!(matches!(
+        ApplyICC::from_file("tests/files/png/f1t.png"),
+        Err(Error::Icc(_))
+    ));
+}
+
 #[test]
 fn skip_icc_profile() {
     let mut image = create_test_image_u8(100, 100, ColorSpace::RGB);
@@ -33,6 +63,62 @@ fn skip_icc_profile() {
     assert_eq!(image.metadata().icc_chunk(), None);
 }
 
+#[test]
+fn replace_corrupt_icc_profile() {
+    let mut image = Image::from_u8(&[10, 20, 30, 40, 50, 60], 2, 1, ColorSpace::RGB);
+
+    let mut corrupt = Profile::new_file("tests/files/icc/tinysrgb.icc")
+        .unwrap()
+        .icc()
+        .unwrap();
+    corrupt.truncate(corrupt.len() / 2);
+    image.metadata_mut().set_icc_chunk(corrupt);
+
+    let result = ApplySRGB.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert!(image.flatten_to_u8()[0]
+        .iter()
+        .zip([10, 20, 30, 40, 50, 60])
+        .all(|(v, expected)| v.abs_diff(expected) <= 1));
+    assert_eq!(
+        *image.metadata().icc_chunk().unwrap(),
+        Profile::new_srgb().icc().unwrap()
+    );
+    assert!(image.write_to_vec(ImageFormat::PNG).is_ok());
+}
+
+#[test]
+fn target_transform_error() {
+    let mut image = Image::from_u8(&[10, 20, 30, 40, 50, 60], 2, 1, ColorSpace::RGB);
+
+    let gray = Profile::new_gray_context(
+        ThreadContext::new(),
+        &CIExyY {
+            x: 0.3127,
+            y: 0.3290,
+            Y: 1.0,
+        },
+        &ToneCurve::new(2.2),
+    )
+    .unwrap();
+
+    let apply_icc = ApplyICC::new(gray);
+    assert!(matches!(apply_icc.validate(), Err(Error::Icc(_))));
+    assert!(ApplyICC::from_file("tests/files/icc/tinysrgb.icc")
+        .unwrap()
+        .validate()
+        .is_ok());
+
+    let result = apply_icc.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_err());
+    assert_eq!(image.flatten_to_u8()[0], [10, 20, 30, 40, 50, 60]);
+    assert_eq!(image.metadata().icc_chunk(), None);
+}
+
 #[test]
 fn apply_srgb_profile() {
     let mut image = create_test_image_u8(100, 100, ColorSpace::RGB);
@@ -52,3 +138,72 @@ fn apply_srgb_profile() {
     // Assert ICC profile is set correctly
     assert_eq!(*image.metadata().icc_chunk().unwrap(), icc);
 }
+
+#[test]
+fn assume_input_profile() {
+    let mut image = Image::from_u8(&[128; 3 * 4], 2, 2, ColorSpace::RGB);
+
+    // sRGB primaries with linear transfer function
+    let linear = Profile::new_rgb_context(
+        ThreadContext::new(),
+        &CIExyY {
+            x: 0.3127,
+            y: 0.3290,
+            Y: 1.0,
+        },
+        &CIExyYTRIPLE {
+            Red: CIExyY {
+                x: 0.64,
+                y: 0.33,
+                Y: 1.0,
+            },
+            Green: CIExyY {
+                x: 0.30,
+                y: 0.60,
+                Y: 1.0,
+            },
+            Blue: CIExyY {
+                x: 0.15,
+                y: 0.06,
+                Y: 1.0,
+            },
+        },
+        &[
+            &ToneCurve::new(1.0),
+            &ToneCurve::new(1.0),
+            &ToneCurve::new(1.0),
+        ],
+    )
+    .unwrap();
+
+    let apply_icc =
+        ApplyICC::new(Profile::new_srgb_context(ThreadContext::new())).with_input_profile(linear);
+    apply_icc.execute_impl(&mut image).unwrap();
+
+    // linear 50% gray is encoded as ~188 in sRGB
+    assert!(image.flatten_to_u8()[0]
+        .iter()
+        .all(|v| v.abs_diff(188) <= 1));
+    assert_eq!(
+        *image.metadata().icc_chunk().unwrap(),
+        Profile::new_srgb().icc().unwrap()
+    );
+
+    // embedded profile takes precedence over the assumed one
+    let mut image = Image::from_u8(&[128; 3 * 4], 2, 2, ColorSpace::RGB);
+    image
+        .metadata_mut()
+        .set_icc_chunk(Profile::new_srgb().icc().unwrap());
+
+    ApplyICC::new(Profile::new_srgb_context(ThreadContext::new()))
+        .with_input_profile(
+            Profile::new_file_context(ThreadContext::new(), "tests/files/icc/tinysrgb.icc")
+                .unwrap(),
+        )
+        .execute_impl(&mut image)
+        .unwrap();
+
+    assert!(image.flatten_to_u8()[0]
+        .iter()
+        .all(|v| v.abs_diff(128) <= 1));
+}
@@ -0,0 +1,226 @@
+use std::{fs, path::Path};
+
+use zune_core::{bit_depth::BitType, colorspace::ColorSpace};
+use zune_image::{
+    errors::{ImageErrors, ImageOperationsErrors},
+    image::Image,
+    traits::OperationsTrait,
+};
+
+use crate::Error;
+
+/// Apply a 3D lookup table to colors of an image
+///
+/// Colors are mapped with trilinear interpolation between the nearest entries of the table,
+/// alpha is left intact. Only RGB and RGBA images are supported.
+pub struct ApplyLut {
+    size: usize,
+    table: Vec<[f32; 3]>,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+}
+
+impl ApplyLut {
+    /// Create a new LUT operation from a `.cube` file
+    ///
+    /// # Arguments
+    /// - path: Path to the Adobe/Resolve `.cube` file with a 3D table
+    pub fn from_cube(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let cube = fs::read_to_string(path).map_err(Error::LutRead)?;
+
+        Self::parse_cube(&cube)
+    }
+
+    /// Create a new LUT operation from contents of a `.cube` file
+    ///
+    /// Entries are listed with red changing fastest, values outside of
+    /// `DOMAIN_MIN..=DOMAIN_MAX` (`0.0..=1.0` by default) are clamped.
+    pub fn parse_cube(cube: &str) -> Result<Self, Error> {
+        let mut size = None;
+        let mut table = vec![];
+        let mut domain_min = [0.; 3];
+        let mut domain_max = [1.; 3];
+
+        for (idx, line) in cube.lines().enumerate() {
+            let invalid = |reason: &str| Error::InvalidLut(format!("{reason} at line {}", idx + 1));
+
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next().filter(|word| !word.starts_with('#')) else {
+                continue;
+            };
+
+            match keyword {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let value = words
+                        .next()
+                        .and_then(|word| word.parse::<usize>().ok())
+                        .filter(|size| (2..=256).contains(size))
+                        .ok_or_else(|| invalid("size must be in 2..=256"))?;
+
+                    size = Some(value);
+                }
+                "LUT_1D_SIZE" => return Err(invalid("1D tables are not supported")),
+                "DOMAIN_MIN" => {
+                    domain_min = triplet(words).ok_or_else(|| invalid("expected 3 numbers"))?
+                }
+                "DOMAIN_MAX" => {
+                    domain_max = triplet(words).ok_or_else(|| invalid("expected 3 numbers"))?
+                }
+                _ => table.push(
+                    triplet(line.split_whitespace())
+                        .ok_or_else(|| invalid("expected 3 numbers"))?,
+                ),
+            }
+        }
+
+        let size = size.ok_or_else(|| Error::InvalidLut("missing LUT_3D_SIZE".to_string()))?;
+
+        if table.len() != size.pow(3) {
+            return Err(Error::InvalidLut(format!(
+                "expected {} entries for size {size}, found {}",
+                size.pow(3),
+                table.len()
+            )));
+        }
+
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err(Error::InvalidLut(
+                "DOMAIN_MAX must be above DOMAIN_MIN".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            size,
+            table,
+            domain_min,
+            domain_max,
+        })
+    }
+
+    fn entry(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[r + (g + b * self.size) * self.size]
+    }
+
+    /// Maps normalized color through the table
+    fn lookup(&self, color: [f32; 3]) -> [f32; 3] {
+        let max = (self.size - 1) as f32;
+
+        let [(r0, r1, rt), (g0, g1, gt), (b0, b1, bt)] = [0, 1, 2].map(|c| {
+            let range = self.domain_max[c] - self.domain_min[c];
+            let pos = ((color[c] - self.domain_min[c]) / range).clamp(0., 1.) * max;
+
+            let low = (pos.floor() as usize).min(self.size - 2);
+
+            (low, low + 1, pos - low as f32)
+        });
+
+        let lerp = |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t);
+
+        let plane = |b| {
+            lerp(
+                lerp(self.entry(r0, g0, b), self.entry(r1, g0, b), rt),
+                lerp(self.entry(r0, g1, b), self.entry(r1, g1, b), rt),
+                gt,
+            )
+        };
+
+        lerp(plane(b0), plane(b1), bt)
+    }
+
+    fn apply<T: Copy>(
+        &self,
+        [r, g, b]: [&mut [T]; 3],
+        to_f32: impl Fn(T) -> f32,
+        from_f32: impl Fn(f32) -> T,
+    ) {
+        for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+            let [nr, ng, nb] = self.lookup([to_f32(*r), to_f32(*g), to_f32(*b)]);
+
+            *r = from_f32(nr);
+            *g = from_f32(ng);
+            *b = from_f32(nb);
+        }
+    }
+}
+
+/// Parses exactly 3 numbers
+fn triplet<'a>(words: impl Iterator<Item = &'a str>) -> Option<[f32; 3]> {
+    let values = words
+        .map(|word| word.parse().ok())
+        .collect::<Option<Vec<f32>>>()?;
+
+    values.try_into().ok()
+}
+
+impl OperationsTrait for ApplyLut {
+    fn name(&self) -> &'static str {
+        "apply lut"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let colorspace = image.colorspace();
+
+        if !matches!(colorspace, ColorSpace::RGB | ColorSpace::RGBA) {
+            return Err(ImageErrors::UnsupportedColorspace(
+                colorspace,
+                self.name(),
+                &[ColorSpace::RGB, ColorSpace::RGBA],
+            ));
+        }
+
+        let depth = image.depth().bit_type();
+
+        for frame in image.frames_mut() {
+            let [r, g, b] = frame.channels_mut(colorspace, true) else {
+                return Err(ImageErrors::OperationsError(
+                    ImageOperationsErrors::WrongComponents(3, colorspace.num_components()),
+                ));
+            };
+
+            match depth {
+                BitType::U8 => self.apply(
+                    [
+                        r.reinterpret_as_mut::<u8>()?,
+                        g.reinterpret_as_mut::<u8>()?,
+                        b.reinterpret_as_mut::<u8>()?,
+                    ],
+                    |v| f32::from(v) / 255.,
+                    |v| (v * 255.).round().clamp(0., 255.) as u8,
+                ),
+                BitType::U16 => self.apply(
+                    [
+                        r.reinterpret_as_mut::<u16>()?,
+                        g.reinterpret_as_mut::<u16>()?,
+                        b.reinterpret_as_mut::<u16>()?,
+                    ],
+                    |v| f32::from(v) / 65535.,
+                    |v| (v * 65535.).round().clamp(0., 65535.) as u16,
+                ),
+                BitType::F32 => self.apply(
+                    [
+                        r.reinterpret_as_mut::<f32>()?,
+                        g.reinterpret_as_mut::<f32>()?,
+                        b.reinterpret_as_mut::<f32>()?,
+                    ],
+                    |v| v,
+                    |v| v,
+                ),
+                d => {
+                    return Err(ImageErrors::OperationsError(
+                        ImageOperationsErrors::UnsupportedType(self.name(), d),
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,93 @@
+use std::fs;
+
+use zune_core::colorspace::ColorSpace;
+
+use super::*;
+use crate::test_utils::TempDir;
+
+/// `.cube` file of the given size with entries computed by `map`
+fn cube(size: usize, map: impl Fn([f32; 3]) -> [f32; 3]) -> String {
+    let max = (size - 1) as f32;
+    let mut cube = format!("TITLE \"test\"\n# comment\nLUT_3D_SIZE {size}\n\n");
+
+    for b in 0..size {
+        for g in 0..size {
+            for r in 0..size {
+                let [r, g, b] = map([r as f32 / max, g as f32 / max, b as f32 / max]);
+                cube.push_str(&format!("{r:.6} {g:.6} {b:.6}\n"));
+            }
+        }
+    }
+
+    cube
+}
+
+fn test_image() -> Image {
+    Image::from_fn(16, 16, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+        *px = [(x * 16) as u8, (y * 16) as u8, (x * y) as u8, 200];
+    })
+}
+
+#[test]
+fn identity_lut() {
+    let dir = TempDir::new("identity_lut");
+
+    let path = dir.join("identity.cube");
+    fs::write(&path, cube(17, |color| color)).unwrap();
+
+    let lut = ApplyLut::from_cube(&path).unwrap();
+    let mut image = test_image();
+    let original = image.flatten_to_u8();
+
+    let result = lut.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.flatten_to_u8(), original);
+}
+
+#[test]
+fn grading_lut() {
+    // swaps red and blue and inverts green, which is linear, so interpolation is exact
+    let lut = ApplyLut::parse_cube(&cube(5, |[r, g, b]| [b, 1. - g, r])).unwrap();
+    let mut image = test_image();
+    let original = image.flatten_to_u8().swap_remove(0);
+
+    let result = lut.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let pixels = image.flatten_to_u8().swap_remove(0);
+
+    for (before, after) in original.chunks_exact(4).zip(pixels.chunks_exact(4)) {
+        assert_eq!(after, [before[2], 255 - before[1], before[0], before[3]]);
+    }
+}
+
+#[test]
+fn grading_lut_u16() {
+    let lut = ApplyLut::parse_cube(&cube(2, |[r, g, b]| [1. - r, g, b])).unwrap();
+    let mut image = Image::from_u16(&[0, 1000, 65535, 65535, 30000, 0], 2, 1, ColorSpace::RGB);
+
+    lut.execute(&mut image).unwrap();
+
+    let channels = image.channels_ref(false);
+    let red = channels[0].reinterpret_as::<u16>().unwrap();
+
+    assert_eq!(red, [65535, 0]);
+}
+
+#[test]
+fn invalid_luts() {
+    assert!(ApplyLut::parse_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1").is_err());
+    assert!(ApplyLut::parse_cube("0 0 0\n1 1 1").is_err());
+    assert!(ApplyLut::parse_cube("LUT_3D_SIZE 2\n0 0 0\n1 1 1").is_err());
+    assert!(ApplyLut::parse_cube("LUT_3D_SIZE 2\n0 0\n").is_err());
+    assert!(ApplyLut::from_cube("missing.cube").is_err());
+
+    let lut = ApplyLut::parse_cube(&cube(2, |color| color)).unwrap();
+    let mut image = Image::fill(0_u8, ColorSpace::Luma, 4, 4);
+
+    assert!(lut.execute(&mut image).is_err());
+}
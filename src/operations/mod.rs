@@ -1,3 +1,31 @@
+/// Alpha operations
+///
+/// This can be used to flatten a transparent image onto a background color.
+pub mod alpha;
+/// Auto contrast operation
+///
+/// This can be used to stretch the range of values of each channel to the full scale.
+pub mod auto_contrast;
+mod color;
+/// Depth operation
+///
+/// This can be used to change bit depth of an image, optionally dithering when reducing it to 8 bits.
+pub mod depth;
+/// EXIF metadata operations
+#[cfg(feature = "metadata")]
+pub mod exif;
+/// Grayscale operation
+///
+/// This can be used to convert an image to luma, with SIMD acceleration behind the `simd` feature.
+pub mod grayscale;
+/// LUT operation
+///
+/// This can be used to color grade an image with a 3D lookup table from a `.cube` file.
+pub mod lut;
+/// Pad operation
+///
+/// This can be used to extend the canvas of an image, filling the new area with a color.
+pub mod pad;
 /// Quantization operation
 ///
 /// This can be used to reduce image palette by performing quantization operation.
@@ -6,6 +34,14 @@ pub mod quantize;
 /// Resize an image to a new dimensions
 #[cfg(feature = "resize")]
 pub mod resize;
+/// Select frame operation
+///
+/// This can be used to reduce an animated image to a single frame, like a static poster.
+pub mod select_frame;
+/// Swap channels operation
+///
+/// This can be used to change the byte order of pixels, like RGBA to BGRA.
+pub mod swap_channels;
 
 /// Operations to apply icc profiles
 #[cfg(feature = "icc")]
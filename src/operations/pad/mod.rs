@@ -0,0 +1,130 @@
+use zune_core::{bit_depth::BitType, colorspace::ColorSpace};
+use zune_image::{
+    channel::Channel,
+    errors::{ImageErrors, ImageOperationsErrors},
+    image::Image,
+    traits::OperationsTrait,
+};
+
+use crate::operations::color::components;
+
+/// Pad an image, extending the canvas
+/// and filling the new area with a color
+pub struct Pad {
+    left: usize,
+    top: usize,
+    right: usize,
+    bottom: usize,
+    color: [f32; 4],
+}
+
+impl Pad {
+    /// Create a new pad operation
+    ///
+    /// # Arguments
+    /// - left: Number of pixels to add on the left side
+    /// - top: Number of pixels to add on the top side
+    /// - right: Number of pixels to add on the right side
+    /// - bottom: Number of pixels to add on the bottom side
+    /// - color: RGBA fill color in `0.0..=1.0` range, converted to the colorspace of the image
+    #[must_use]
+    pub fn new(left: usize, top: usize, right: usize, bottom: usize, color: [f32; 4]) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+            color,
+        }
+    }
+
+    fn pad_channel(
+        &self,
+        channel: &Channel,
+        (width, height): (usize, usize),
+        depth: BitType,
+        fill: &[u8],
+    ) -> Channel {
+        let size = fill.len();
+
+        let new_width = width + self.left + self.right;
+        let new_height = height + self.top + self.bottom;
+
+        let mut new_channel = Channel::new_with_bit_type(new_width * new_height * size, depth);
+
+        let src = unsafe { channel.alias() };
+        let dst = unsafe { new_channel.alias_mut() };
+
+        dst.chunks_exact_mut(size)
+            .for_each(|px| px.copy_from_slice(fill));
+
+        src.chunks_exact(width * size)
+            .zip(dst.chunks_exact_mut(new_width * size).skip(self.top))
+            .for_each(|(src_row, dst_row)| {
+                dst_row[self.left * size..(self.left + width) * size].copy_from_slice(src_row);
+            });
+
+        new_channel
+    }
+}
+
+impl OperationsTrait for Pad {
+    fn name(&self) -> &'static str {
+        "pad"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        let new_width = width + self.left + self.right;
+        let new_height = height + self.top + self.bottom;
+
+        let colorspace = image.colorspace();
+        let depth = image.depth().bit_type();
+
+        let color = components(colorspace, self.color)
+            .expect("This should be handled in supported_colorspaces");
+
+        for frame in image.frames_mut() {
+            for (channel, color) in frame.channels_vec().iter_mut().zip(&color) {
+                let fill = match depth {
+                    BitType::U8 => vec![(color * 255.).round() as u8],
+                    BitType::U16 => ((color * 65535.).round() as u16).to_ne_bytes().to_vec(),
+                    BitType::F32 => color.to_ne_bytes().to_vec(),
+                    d => {
+                        return Err(ImageErrors::OperationsError(
+                            ImageOperationsErrors::UnsupportedType(self.name(), d),
+                        ))
+                    }
+                };
+
+                *channel = self.pad_channel(channel, (width, height), depth, &fill);
+            }
+        }
+
+        image.set_dimensions(new_width, new_height);
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::BGR,
+            ColorSpace::BGRA,
+            ColorSpace::ARGB,
+            ColorSpace::Luma,
+            ColorSpace::LumaA,
+            ColorSpace::YCbCr,
+            ColorSpace::CMYK,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests;
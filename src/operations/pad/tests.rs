@@ -0,0 +1,117 @@
+use zune_core::colorspace::ColorSpace;
+
+use crate::test_utils::*;
+
+use super::*;
+
+#[test]
+fn pad_u8() {
+    let pad = Pad::new(1, 1, 1, 1, [1., 1., 1., 1.]);
+    let mut image = Image::from_u8(&[10, 20, 30, 40], 2, 2, ColorSpace::Luma);
+
+    let result = pad.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (4, 4));
+
+    #[rustfmt::skip]
+    assert_eq!(
+        image.flatten_to_u8()[0],
+        [
+            255, 255, 255, 255,
+            255,  10,  20, 255,
+            255,  30,  40, 255,
+            255, 255, 255, 255,
+        ]
+    );
+}
+
+#[test]
+fn pad_alpha() {
+    let pad = Pad::new(1, 0, 0, 0, [0., 0., 0., 0.5]);
+    let mut image = Image::from_u8(&[10, 20], 1, 1, ColorSpace::LumaA);
+
+    let result = pad.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (2, 1));
+    assert_eq!(image.flatten_to_u8()[0], [0, 128, 10, 20]);
+}
+
+#[test]
+fn pad_colorspaces() {
+    let pad = Pad::new(1, 0, 0, 0, [1., 0.5, 0., 0.5]);
+
+    for (colorspace, expected) in [
+        (ColorSpace::RGB, [255, 128, 0].as_slice()),
+        (ColorSpace::RGBA, &[255, 128, 0, 128]),
+        (ColorSpace::BGR, &[0, 128, 255]),
+        (ColorSpace::BGRA, &[0, 128, 255, 128]),
+        (ColorSpace::ARGB, &[128, 255, 128, 0]),
+        (ColorSpace::Luma, &[151]),
+        (ColorSpace::LumaA, &[151, 128]),
+        (ColorSpace::YCbCr, &[151, 42, 202]),
+        (ColorSpace::CMYK, &[255, 128, 0, 255]),
+    ] {
+        let components = colorspace.num_components();
+        let mut image = Image::from_u8(&vec![0; components], 1, 1, colorspace);
+
+        let result = pad.execute(&mut image);
+        dbg!(&result);
+
+        assert!(result.is_ok());
+        assert_eq!(
+            image.flatten_to_u8()[0][..components],
+            *expected,
+            "{colorspace:?}"
+        );
+    }
+
+    // padding keeps its color through conversion to RGB
+    let mut image = Image::from_u8(&[0; 4], 1, 1, ColorSpace::CMYK);
+    pad.execute(&mut image).unwrap();
+    image.convert_color(ColorSpace::RGB).unwrap();
+
+    assert!(image.flatten_to_u8()[0][..3]
+        .iter()
+        .zip([255, 128, 0])
+        .all(|(v, expected)| v.abs_diff(expected) <= 2));
+}
+
+#[test]
+fn pad_u16() {
+    let pad = Pad::new(10, 20, 30, 40, [0.5, 0.5, 0.5, 1.]);
+    let mut image = create_test_image_u16(200, 200, ColorSpace::RGB);
+
+    let result = pad.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (240, 260));
+}
+
+#[test]
+fn pad_f32() {
+    let pad = Pad::new(10, 20, 30, 40, [0.5, 0.5, 0.5, 1.]);
+    let mut image = create_test_image_f32(200, 200, ColorSpace::RGBA);
+
+    let result = pad.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (240, 260));
+}
+
+#[test]
+fn pad_animated() {
+    let pad = Pad::new(1, 1, 1, 1, [0., 0., 0., 0.]);
+    let mut image = create_test_image_animated(200, 200, ColorSpace::RGB);
+
+    let result = pad.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (202, 202));
+}
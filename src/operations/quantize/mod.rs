@@ -1,16 +1,19 @@
-use imagequant::Histogram;
+use std::collections::HashSet;
+
+use imagequant::{Histogram, RGBA};
 use rgb::FromSlice;
 use zune_core::{bit_depth::BitType, colorspace::ColorSpace};
-use zune_image::{
-    channel::Channel,
-    errors::{ImageErrors, ImageOperationsErrors},
-    traits::OperationsTrait,
-};
+use zune_image::{channel::Channel, errors::ImageErrors, frame::Frame, traits::OperationsTrait};
+
+use crate::Error;
 
 /// Reduce image palette
 pub struct Quantize {
     quality: u8,
     dithering: Option<f32>,
+    max_colors: Option<u32>,
+    #[cfg_attr(not(feature = "threads"), allow(dead_code))]
+    deterministic: bool,
 }
 
 impl Quantize {
@@ -21,84 +24,158 @@ impl Quantize {
     /// - dithering: overall "smoothness" of the resulting image
     #[must_use]
     pub fn new(quality: u8, dithering: Option<f32>) -> Self {
-        Self { quality, dithering }
+        Self {
+            quality,
+            dithering,
+            max_colors: None,
+            deterministic: false,
+        }
     }
-}
 
-impl OperationsTrait for Quantize {
-    fn name(&self) -> &'static str {
-        "quantize"
+    /// Limits the palette to `max_colors` colors
+    ///
+    /// Images that already have at most `max_colors` unique colors are left untouched.
+    #[must_use]
+    pub fn with_max_colors(mut self, max_colors: u32) -> Self {
+        self.max_colors = Some(max_colors);
+        self
     }
 
-    fn execute_impl(&self, image: &mut zune_image::image::Image) -> Result<(), ImageErrors> {
+    /// Makes output reproducible between runs
+    ///
+    /// libimagequant has no random steps of its own, but merges partial results
+    /// of its threads in arbitrary order, so deterministic quantization runs on a single thread.
+    #[must_use]
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Checks if every frame of the image together has at most `max_colors` unique colors
+    fn fits_colors(image: &zune_image::image::Image, max_colors: u32) -> bool {
+        let mut colors = HashSet::new();
+
+        image.frames_ref().iter().all(|frame| {
+            let pixels = match image.depth().bit_type() {
+                BitType::U16 => frame.flatten::<u16>(image.colorspace()),
+                _ => frame
+                    .flatten::<u8>(image.colorspace())
+                    .into_iter()
+                    .map(u16::from)
+                    .collect(),
+            };
+
+            pixels.chunks_exact(4).all(|px| {
+                colors.insert([px[0], px[1], px[2], px[3]]);
+                colors.len() <= max_colors as usize
+            })
+        })
+    }
+
+    /// Quantizes image on the current thread pool
+    fn quantize(&self, image: &mut zune_image::image::Image) -> Result<(), ImageErrors> {
+        if let Some(max_colors) = self.max_colors {
+            if Self::fits_colors(image, max_colors) {
+                log::trace!("image fits into {max_colors} colors, skipping quantization");
+                return Ok(());
+            }
+        }
+
         let (src_width, src_height) = image.dimensions();
+        let depth = image.depth().bit_type();
         let channel_len = src_width * src_height * image.depth().size_of();
 
         let mut liq = imagequant::new();
 
-        liq.set_quality(0, self.quality)
-            .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
+        liq.set_quality(0, self.quality).map_err(Error::from)?;
 
-        let mut histogram = Histogram::new(&liq);
+        if let Some(max_colors) = self.max_colors {
+            liq.set_max_colors(max_colors).map_err(Error::from)?;
+        }
+
+        let new_image = |frame: &Frame| {
+            liq.new_image(
+                rgba8(frame, image.colorspace(), depth).as_rgba(),
+                src_width,
+                src_height,
+                0.0,
+            )
+            .map_err(|e| ImageErrors::from(Error::from(e)))
+        };
 
+        // frames are converted in parallel, but added to the histogram in order,
+        // so the palette doesn't depend on the number of threads
+        #[cfg(feature = "threads")]
+        let mut frames = {
+            use rayon::prelude::*;
+
+            image
+                .frames_ref()
+                .par_iter()
+                .map(new_image)
+                .collect::<Result<Vec<imagequant::Image>, ImageErrors>>()?
+        };
+        #[cfg(not(feature = "threads"))]
         let mut frames = image
             .frames_ref()
             .iter()
-            .map(|frame| {
-                let mut img = liq
-                    .new_image(
-                        frame.flatten(image.colorspace()).as_rgba(),
-                        src_width,
-                        src_height,
-                        0.0,
-                    )
-                    .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
-
-                histogram
-                    .add_image(&liq, &mut img)
-                    .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
-
-                Ok::<imagequant::Image, ImageErrors>(img)
-            })
+            .map(new_image)
             .collect::<Result<Vec<imagequant::Image>, ImageErrors>>()?;
 
-        let mut res = histogram
-            .quantize(&liq)
-            .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
+        let mut histogram = Histogram::new(&liq);
+
+        for img in &mut frames {
+            histogram.add_image(&liq, img).map_err(Error::from)?;
+        }
+
+        let mut res = histogram.quantize(&liq).map_err(Error::from)?;
 
         if let Some(dithering) = self.dithering {
-            res.set_dithering_level(dithering)
-                .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
+            res.set_dithering_level(dithering).map_err(Error::from)?;
         }
 
+        let image_colorspace = image.colorspace();
+
         frames
             .iter_mut()
             .zip(image.frames_mut())
             .try_for_each(|(img, frame)| {
-                let (palette, pixels) = res
-                    .remapped(img)
-                    .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
+                let (palette, pixels) = match (depth, self.dithering) {
+                    (BitType::U16, Some(dithering)) => {
+                        let palette = res.palette_vec();
+                        let pixels = dither_u16(
+                            &frame.flatten::<u16>(image_colorspace),
+                            src_width,
+                            &palette,
+                            dithering,
+                        );
+
+                        (palette, pixels)
+                    }
+                    _ => res.remapped(img).map_err(Error::from)?,
+                };
 
-                let channels = pixels
-                    .iter()
-                    .map(|px| {
+                let mut channels = vec![Channel::new_with_bit_type(channel_len, depth); 4];
+
+                for (idx, channel) in channels.iter_mut().enumerate() {
+                    let values = pixels.iter().map(|px| {
                         let px = palette[*px as usize];
-                        (px.r, px.g, px.b, px.a)
-                    })
-                    .enumerate()
-                    .fold(
-                        vec![Channel::new_with_bit_type(channel_len, BitType::U8); 4],
-                        |mut acc, (idx, px)| {
-                            unsafe {
-                                acc[0].alias_mut()[idx] = px.0;
-                                acc[1].alias_mut()[idx] = px.1;
-                                acc[2].alias_mut()[idx] = px.2;
-                                acc[3].alias_mut()[idx] = px.3;
-                            }
-
-                            acc
-                        },
-                    );
+                        [px.r, px.g, px.b, px.a][idx]
+                    });
+
+                    match depth {
+                        BitType::U16 => channel
+                            .reinterpret_as_mut::<u16>()?
+                            .iter_mut()
+                            .zip(values)
+                            .for_each(|(dst, value)| *dst = u16::from(value) * 257),
+                        _ => channel
+                            .reinterpret_as_mut::<u8>()?
+                            .iter_mut()
+                            .zip(values)
+                            .for_each(|(dst, value)| *dst = value),
+                    }
+                }
 
                 frame.set_channels(channels);
 
@@ -107,9 +184,94 @@ impl OperationsTrait for Quantize {
 
         Ok(())
     }
+}
+
+/// RGBA pixels of the frame in 8 bits, 16-bit samples are rounded to the nearest value
+fn rgba8(frame: &Frame, colorspace: ColorSpace, depth: BitType) -> Vec<u8> {
+    match depth {
+        BitType::U16 => frame
+            .flatten::<u16>(colorspace)
+            .into_iter()
+            .map(|v| ((u32::from(v) + 128) / 257) as u8)
+            .collect(),
+        _ => frame.flatten::<u8>(colorspace),
+    }
+}
+
+/// Remaps 16-bit RGBA pixels to the palette with Floyd-Steinberg dithering
+///
+/// The error is diffused in 16-bit space, so gradients finer than 8 bits
+/// are not banded before they are dithered.
+fn dither_u16(pixels: &[u16], width: usize, palette: &[RGBA], level: f32) -> Vec<u8> {
+    let palette = palette
+        .iter()
+        .map(|c| [c.r, c.g, c.b, c.a].map(|v| f32::from(v) * 257.))
+        .collect::<Vec<_>>();
+
+    let mut pixels = pixels
+        .chunks_exact(4)
+        .map(|px| [px[0], px[1], px[2], px[3]].map(f32::from))
+        .collect::<Vec<_>>();
+    let mut indices = vec![0; pixels.len()];
+
+    for idx in 0..pixels.len() {
+        let (x, y) = (idx % width, idx / width);
+        let px = pixels[idx].map(|v| v.clamp(0., 65535.));
+
+        let (nearest, color) = palette
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| distance(&px, a).total_cmp(&distance(&px, b)))
+            .expect("palette is not empty");
+
+        indices[idx] = nearest as u8;
+
+        let error: [f32; 4] = std::array::from_fn(|c| (px[c] - color[c]) * level);
+        let mut diffuse = |x: usize, y: usize, weight: f32| {
+            if let Some(px) = pixels.get_mut(y * width + x) {
+                px.iter_mut().zip(error).for_each(|(v, e)| *v += e * weight);
+            }
+        };
+
+        if x + 1 < width {
+            diffuse(x + 1, y, 7. / 16.);
+            diffuse(x + 1, y + 1, 1. / 16.);
+        }
+        if x > 0 {
+            diffuse(x - 1, y + 1, 3. / 16.);
+        }
+        diffuse(x, y + 1, 5. / 16.);
+    }
+
+    indices
+}
+
+/// Squared distance between two colors
+fn distance(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+impl OperationsTrait for Quantize {
+    fn name(&self) -> &'static str {
+        "quantize"
+    }
+
+    fn execute_impl(&self, image: &mut zune_image::image::Image) -> Result<(), ImageErrors> {
+        #[cfg(feature = "threads")]
+        if self.deterministic {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .build()
+                .map_err(Error::from)?;
+
+            return pool.install(|| self.quantize(image));
+        }
+
+        self.quantize(image)
+    }
 
     fn supported_types(&self) -> &'static [BitType] {
-        &[BitType::U8]
+        &[BitType::U8, BitType::U16]
     }
 
     fn supported_colorspaces(&self) -> &'static [ColorSpace] {
@@ -1,3 +1,6 @@
+use zune_core::bit_depth::BitDepth;
+use zune_image::image::Image;
+
 use crate::test_utils::*;
 
 use super::*;
@@ -25,3 +28,136 @@ fn dither_u8() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn max_colors() {
+    let quantize = Quantize::new(100, None).with_max_colors(4);
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGBA);
+
+    let result = quantize.execute(&mut image);
+
+    dbg!(&result);
+
+    assert!(result.is_ok());
+
+    let colors = image.flatten_to_u8()[0]
+        .chunks_exact(4)
+        .collect::<HashSet<_>>()
+        .len();
+
+    assert!(colors <= 4);
+}
+
+#[test]
+fn max_colors_untouched() {
+    let quantize = Quantize::new(100, None).with_max_colors(8);
+    let mut image = Image::from_fn(16, 16, ColorSpace::RGBA, |x, y, px: &mut [u8; 4]| {
+        *px = [((x + y) % 5 * 50) as u8, 10, 20, 255];
+    });
+    let original = image.clone();
+
+    let result = quantize.execute(&mut image);
+
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert!(image.frames_ref() == original.frames_ref());
+}
+
+#[test]
+fn typed_error() {
+    let quantize = Quantize::new(101, None);
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGBA);
+
+    let error = quantize.execute(&mut image).unwrap_err();
+    dbg!(&error);
+
+    assert!(matches!(
+        Error::from_image_errors(&error),
+        Some(Error::Quantize(imagequant::Error::ValueOutOfRange))
+    ));
+}
+
+#[test]
+fn deterministic_dithering_is_reproducible() {
+    let quantize = Quantize::new(50, Some(1.0)).deterministic(true);
+    let image = create_test_image_u8(200, 200, ColorSpace::RGBA);
+
+    let mut first = image.clone();
+    quantize.execute(&mut first).unwrap();
+
+    let mut second = image.clone();
+    quantize.execute(&mut second).unwrap();
+
+    assert!(first.frames_ref() == second.frames_ref());
+    assert!(first.frames_ref() != image.frames_ref());
+}
+
+#[test]
+fn dither_u16_gradient() {
+    let pixels = (0..16)
+        .flat_map(|_| 0..256)
+        .flat_map(|x| {
+            let v = (x * 257) as u16;
+            [v, v, v, 65535]
+        })
+        .collect::<Vec<_>>();
+    let image = Image::from_u16(&pixels, 256, 16, ColorSpace::RGBA);
+
+    // mean error of the column averages against the source gradient
+    let banding = |dithering: Option<f32>| {
+        let mut image = image.clone();
+        Quantize::new(100, dithering)
+            .with_max_colors(4)
+            .execute(&mut image)
+            .unwrap();
+
+        assert_eq!(image.depth(), BitDepth::Sixteen);
+
+        let pixels = image.frames_ref()[0].flatten::<u16>(ColorSpace::RGBA);
+        (0..256)
+            .map(|x| {
+                let column = (0..16)
+                    .map(|y| f64::from(pixels[(y * 256 + x) * 4]))
+                    .sum::<f64>()
+                    / 16.;
+
+                (column - (x * 257) as f64).abs()
+            })
+            .sum::<f64>()
+            / 256.
+    };
+
+    assert!(banding(Some(1.0)) < banding(Some(0.0)));
+}
+
+#[test]
+#[cfg(feature = "threads")]
+fn quantize_animated_frames() {
+    let frames = (0..10)
+        .map(|idx| {
+            let frame = Image::from_fn(32, 32, ColorSpace::RGBA, |y, x, px: &mut [u8; 4]| {
+                *px = [(x * 8) as u8, (y * 8) as u8, (idx * 25) as u8, 255];
+            });
+
+            frame.frames_ref()[0].clone()
+        })
+        .collect();
+    let image = Image::new_frames(frames, BitDepth::Eight, 32, 32, ColorSpace::RGBA);
+
+    let quantize = Quantize::new(75, Some(0.5)).with_max_colors(64);
+
+    let mut parallel = image.clone();
+    quantize.execute(&mut parallel).unwrap();
+
+    let mut sequential = image.clone();
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap()
+        .install(|| quantize.execute(&mut sequential))
+        .unwrap();
+
+    assert_eq!(parallel.frames_ref().len(), 10);
+    assert!(parallel.frames_ref() == sequential.frames_ref());
+}
@@ -7,14 +7,47 @@ use zune_image::{
     channel::Channel,
     errors::{ImageErrors, ImageOperationsErrors},
     image::Image,
+    metadata::AlphaState,
     traits::OperationsTrait,
 };
 
+use crate::{
+    operations::alpha::{alpha_index, is_opaque},
+    Error,
+};
+
+/// Default limit of output pixels for [`Resize`], 100 megapixels
+pub const DEFAULT_MAX_PIXELS: usize = 100_000_000;
+
+/// How the image is fitted into the new dimensions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResizeFit {
+    /// Stretch the image to exactly match the new dimensions, ignoring aspect ratio
+    #[default]
+    Stretch,
+    /// Preserve aspect ratio, scaling the image to cover the new dimensions
+    /// and cropping the overflow around the center
+    Cover,
+    /// Preserve aspect ratio, scaling the image down to fit within the new dimensions
+    ///
+    /// Images that already fit are left untouched, so they are never enlarged.
+    Shrink,
+}
+
 /// Resize an image to a new dimensions
 /// using the resize algorithm specified
+///
+/// Every channel is resized separately, so images of any colorspace keep it,
+/// including print colorspaces like CMYK and YCCK. Alpha handling only applies
+/// to colorspaces with an alpha channel.
 pub struct Resize {
     new_dimensions: (usize, usize),
-    algorithm: fr::ResizeAlg,
+    algorithm: Option<fr::ResizeAlg>,
+    max_pixels: usize,
+    fit: ResizeFit,
+    alpha_aware: bool,
+    #[cfg(feature = "metadata")]
+    preserve_metadata: bool,
 }
 
 impl Resize {
@@ -28,7 +61,291 @@ impl Resize {
     pub fn new(width: usize, height: usize, algorithm: fr::ResizeAlg) -> Self {
         Self {
             new_dimensions: (width, height),
-            algorithm,
+            algorithm: Some(algorithm),
+            max_pixels: DEFAULT_MAX_PIXELS,
+            fit: ResizeFit::default(),
+            alpha_aware: true,
+            #[cfg(feature = "metadata")]
+            preserve_metadata: false,
+        }
+    }
+
+    /// Set how the image is fitted into the new dimensions
+    ///
+    /// Defaults to [`ResizeFit::Stretch`].
+    #[must_use]
+    pub fn with_fit(mut self, fit: ResizeFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Create a new resize operation that only shrinks images exceeding the bounds
+    ///
+    /// The aspect ratio is preserved and images that already fit are left untouched,
+    /// see [`ResizeFit::Shrink`].
+    ///
+    /// # Argument
+    /// - max_width: The maximum image width
+    /// - max_height: The maximum image height
+    /// - algorithm: The resize algorithm to use
+    #[must_use]
+    pub fn downscale_only(max_width: usize, max_height: usize, algorithm: fr::ResizeAlg) -> Self {
+        Self::new(max_width, max_height, algorithm).with_fit(ResizeFit::Shrink)
+    }
+
+    /// Create a new resize operation that picks the resize algorithm automatically
+    ///
+    /// Downscaling uses Lanczos3, while upscaling uses Catmull-Rom
+    /// which produces less ringing artifacts.
+    ///
+    /// # Argument
+    /// - width: The new image width
+    /// - height: The new image height.
+    #[must_use]
+    pub fn auto(width: usize, height: usize) -> Self {
+        Self {
+            algorithm: None,
+            ..Self::new(width, height, fr::ResizeAlg::default())
+        }
+    }
+
+    /// Returns dimensions of an image with given dimensions after the resize
+    ///
+    /// Useful to know the final size ahead of execution, since
+    /// [`ResizeFit::Cover`] and [`ResizeFit::Shrink`] don't simply use
+    /// the dimensions passed to the constructor.
+    ///
+    /// # Argument
+    /// - (src_width, src_height): Dimensions of the image before resize
+    #[must_use]
+    pub fn compute_dimensions(&self, src_dimensions: (usize, usize)) -> (usize, usize) {
+        match self.fit {
+            ResizeFit::Cover => self.new_dimensions,
+            _ => self
+                .scaled_dimensions(src_dimensions)
+                .unwrap_or(src_dimensions),
+        }
+    }
+
+    /// Returns dimensions the image is scaled to before cropping,
+    /// or [`None`] if it's left untouched
+    fn scaled_dimensions(&self, (src_width, src_height): (usize, usize)) -> Option<(usize, usize)> {
+        let (target_width, target_height) = self.new_dimensions;
+
+        match self.fit {
+            ResizeFit::Stretch => Some((target_width, target_height)),
+            ResizeFit::Cover => {
+                let scale = f64::max(
+                    target_width as f64 / src_width as f64,
+                    target_height as f64 / src_height as f64,
+                );
+
+                Some((
+                    ((src_width as f64 * scale).round() as usize).max(target_width),
+                    ((src_height as f64 * scale).round() as usize).max(target_height),
+                ))
+            }
+            ResizeFit::Shrink => {
+                if src_width <= target_width && src_height <= target_height {
+                    return None;
+                }
+
+                let scale = f64::min(
+                    target_width as f64 / src_width as f64,
+                    target_height as f64 / src_height as f64,
+                );
+
+                Some((
+                    ((src_width as f64 * scale).round() as usize).clamp(1, target_width),
+                    ((src_height as f64 * scale).round() as usize).clamp(1, target_height),
+                ))
+            }
+        }
+    }
+
+    /// Returns resize algorithm used for an image with given dimensions
+    pub(crate) fn algorithm_for(&self, (src_width, src_height): (usize, usize)) -> fr::ResizeAlg {
+        self.algorithm.unwrap_or_else(|| {
+            let (dst_width, dst_height) = self.new_dimensions;
+
+            if dst_width * dst_height > src_width * src_height {
+                fr::ResizeAlg::Convolution(fr::FilterType::CatmullRom)
+            } else {
+                fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3)
+            }
+        })
+    }
+
+    /// Set the maximum number of pixels the resized image may have
+    ///
+    /// Resizing to bigger dimensions fails with an error instead of
+    /// attempting the allocation. Defaults to [`DEFAULT_MAX_PIXELS`].
+    #[must_use]
+    pub fn with_max_pixels(mut self, max_pixels: usize) -> Self {
+        self.max_pixels = max_pixels;
+        self
+    }
+
+    /// Premultiply colors by alpha while resizing images with straight alpha
+    ///
+    /// Resizing straight colors blends in colors of transparent pixels, which shows
+    /// up as fringes around soft edges. Colors are premultiplied before resizing and
+    /// divided by the resized alpha afterwards. Images without alpha or fully opaque
+    /// are resized as is. Images marked as premultiplied in their metadata are resized
+    /// as is too and stay premultiplied. Enabled by default.
+    #[must_use]
+    pub fn with_alpha_aware(mut self, alpha_aware: bool) -> Self {
+        self.alpha_aware = alpha_aware;
+        self
+    }
+
+    /// Keep EXIF metadata consistent with the resized image
+    ///
+    /// Dimension tags are updated to the new size and the embedded thumbnail,
+    /// which would show the image before resize, is removed.
+    #[cfg(feature = "metadata")]
+    #[must_use]
+    pub fn preserve_metadata(mut self, preserve: bool) -> Self {
+        self.preserve_metadata = preserve;
+        self
+    }
+}
+
+/// Calls `f` with every color channel of the image along with its alpha channel
+fn for_each_color(
+    image: &mut Image,
+    mut f: impl FnMut(&mut Channel, &Channel) -> Result<(), ImageErrors>,
+) -> Result<(), ImageErrors> {
+    let Some(alpha_idx) = alpha_index(image.colorspace()) else {
+        return Ok(());
+    };
+
+    for frame in image.frames_mut() {
+        let (colors, rest) = frame.channels_vec().split_at_mut(alpha_idx);
+        let (alpha, rest) = rest.split_first_mut().expect("alpha channel is present");
+
+        for color in colors.iter_mut().chain(rest) {
+            f(color, alpha)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Multiplies color channels by alpha, turning straight colors into premultiplied ones
+fn premultiply(image: &mut Image) -> Result<(), ImageErrors> {
+    let depth = image.depth().bit_type();
+
+    for_each_color(image, |color, alpha| {
+        match depth {
+            BitType::U8 => premultiply_int(
+                color.reinterpret_as_mut::<u8>()?,
+                alpha.reinterpret_as::<u8>()?,
+                u8::MAX.into(),
+            ),
+            BitType::U16 => premultiply_int(
+                color.reinterpret_as_mut::<u16>()?,
+                alpha.reinterpret_as::<u16>()?,
+                u16::MAX.into(),
+            ),
+            BitType::F32 => color
+                .reinterpret_as_mut::<f32>()?
+                .iter_mut()
+                .zip(alpha.reinterpret_as::<f32>()?)
+                .for_each(|(c, a)| *c *= a),
+            d => return Err(ImageErrors::ImageOperationNotImplemented("resize", d)),
+        }
+
+        Ok(())
+    })?;
+
+    image.metadata_mut().set_alpha(AlphaState::PreMultiplied);
+
+    Ok(())
+}
+
+fn premultiply_int<T>(color: &mut [T], alpha: &[T], max: u32)
+where
+    T: Copy + Into<u32> + TryFrom<u32>,
+{
+    for (c, a) in color.iter_mut().zip(alpha) {
+        let value = ((*c).into() * (*a).into() + max / 2) / max;
+
+        if let Ok(value) = T::try_from(value) {
+            *c = value;
+        }
+    }
+}
+
+/// Divides color channels by alpha, turning premultiplied colors into straight ones
+fn unpremultiply(image: &mut Image) -> Result<(), ImageErrors> {
+    let depth = image.depth().bit_type();
+
+    for_each_color(image, |color, alpha| {
+        match depth {
+            BitType::U8 => unpremultiply_int(
+                color.reinterpret_as_mut::<u8>()?,
+                alpha.reinterpret_as::<u8>()?,
+                u8::MAX.into(),
+            ),
+            BitType::U16 => unpremultiply_int(
+                color.reinterpret_as_mut::<u16>()?,
+                alpha.reinterpret_as::<u16>()?,
+                u16::MAX.into(),
+            ),
+            BitType::F32 => color
+                .reinterpret_as_mut::<f32>()?
+                .iter_mut()
+                .zip(alpha.reinterpret_as::<f32>()?)
+                .for_each(|(c, a)| *c = if *a > 0. { (*c / a).min(1.) } else { 0. }),
+            d => return Err(ImageErrors::ImageOperationNotImplemented("resize", d)),
+        }
+
+        Ok(())
+    })?;
+
+    image.metadata_mut().set_alpha(AlphaState::NonPreMultiplied);
+
+    Ok(())
+}
+
+fn unpremultiply_int<T>(color: &mut [T], alpha: &[T], max: u32)
+where
+    T: Copy + Into<u32> + TryFrom<u32>,
+{
+    for (c, a) in color.iter_mut().zip(alpha) {
+        let a = (*a).into();
+        let value = match a {
+            0 => 0,
+            a => ((*c).into() * max + a / 2) / a,
+        };
+
+        if let Ok(value) = T::try_from(value.min(max)) {
+            *c = value;
+        }
+    }
+}
+
+/// Updates EXIF dimension tags and strips the stale thumbnail
+#[cfg(feature = "metadata")]
+fn update_exif(image: &mut Image) {
+    use exif::{In, Tag, Value};
+
+    let (width, height) = image.dimensions();
+
+    if let Some(fields) = image.metadata_mut().exif_mut() {
+        fields.retain(|field| field.ifd_num != In::THUMBNAIL);
+
+        for field in fields.iter_mut() {
+            match field.tag {
+                Tag::PixelXDimension | Tag::ImageWidth => {
+                    field.value = Value::Long(vec![width as u32])
+                }
+                Tag::PixelYDimension | Tag::ImageLength => {
+                    field.value = Value::Long(vec![height as u32])
+                }
+                _ => {}
+            }
         }
     }
 }
@@ -40,15 +357,40 @@ impl OperationsTrait for Resize {
 
     fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
         let (src_width, src_height) = image.dimensions();
-        let (dst_width, dst_height) = self.new_dimensions;
-        if (dst_height == 0) || (dst_width == 0) {
+        let (target_width, target_height) = self.new_dimensions;
+        if (target_height == 0) || (target_width == 0) {
             return Err(ImageErrors::OperationsError(
                 ImageOperationsErrors::Generic("Width or Height cannot be 0"),
             ));
         }
 
+        let Some((dst_width, dst_height)) = self.scaled_dimensions((src_width, src_height)) else {
+            return Ok(());
+        };
+
+        if dst_width
+            .checked_mul(dst_height)
+            .is_none_or(|pixels| pixels > self.max_pixels)
+        {
+            return Err(Error::PixelLimit {
+                width: dst_width,
+                height: dst_height,
+                limit: self.max_pixels,
+            }
+            .into());
+        }
+
+        let algorithm = self.algorithm_for((src_width, src_height));
         let depth = image.depth().bit_type();
 
+        let premultiply_alpha = self.alpha_aware
+            && image.metadata().alpha() == AlphaState::NonPreMultiplied
+            && !is_opaque(image)?;
+
+        if premultiply_alpha {
+            premultiply(image)?;
+        }
+
         let new_length = dst_width * dst_height * image.depth().size_of();
 
         let width = NonZeroU32::new(src_width as u32).unwrap();
@@ -57,59 +399,7 @@ impl OperationsTrait for Resize {
         let dst_width = NonZeroU32::new(dst_width as u32).unwrap();
         let dst_height = NonZeroU32::new(dst_height as u32).unwrap();
 
-        #[cfg(feature = "threads")]
-        std::thread::scope(|f| {
-            let mut errors = vec![];
-
-            for old_channel in image.channels_mut(false) {
-                let result = f.spawn(|| {
-                    let mut new_channel = Channel::new_with_bit_type(new_length, depth);
-
-                    let src_image = fr::Image::from_slice_u8(
-                        width,
-                        height,
-                        unsafe { old_channel.alias_mut() },
-                        match depth {
-                            BitType::U8 => fr::PixelType::U8,
-                            BitType::U16 => fr::PixelType::U16,
-                            BitType::F32 => fr::PixelType::F32,
-
-                            d => {
-                                return Err(ImageErrors::ImageOperationNotImplemented("resize", d))
-                            }
-                        },
-                    )
-                    .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
-
-                    let mut dst_image =
-                        fr::Image::new(dst_width, dst_height, src_image.pixel_type());
-
-                    let mut dst_view = dst_image.view_mut();
-
-                    let mut resizer = fr::Resizer::new(self.algorithm);
-
-                    resizer
-                        .resize(&src_image.view(), &mut dst_view)
-                        .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
-
-                    unsafe {
-                        new_channel.alias_mut().copy_from_slice(dst_image.buffer());
-                    }
-
-                    *old_channel = new_channel;
-                    Ok(())
-                });
-                errors.push(result);
-            }
-
-            errors
-                .into_iter()
-                .map(|x| x.join().unwrap())
-                .collect::<Result<Vec<()>, ImageErrors>>()
-        })?;
-
-        #[cfg(not(feature = "threads"))]
-        for old_channel in image.channels_mut(false) {
+        let resize_channel = |old_channel: &mut &mut Channel| {
             let mut new_channel = Channel::new_with_bit_type(new_length, depth);
 
             let src_image = fr::Image::from_slice_u8(
@@ -124,26 +414,81 @@ impl OperationsTrait for Resize {
                     d => return Err(ImageErrors::ImageOperationNotImplemented("resize", d)),
                 },
             )
-            .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
+            .map_err(Error::from)?;
 
             let mut dst_image = fr::Image::new(dst_width, dst_height, src_image.pixel_type());
 
             let mut dst_view = dst_image.view_mut();
 
-            let mut resizer = fr::Resizer::new(self.algorithm);
+            let mut resizer = fr::Resizer::new(algorithm);
 
             resizer
                 .resize(&src_image.view(), &mut dst_view)
-                .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
+                .map_err(Error::from)?;
 
             unsafe {
                 new_channel.alias_mut().copy_from_slice(dst_image.buffer());
             }
 
-            *old_channel = new_channel;
+            **old_channel = new_channel;
+            Ok(())
+        };
+
+        // channels are resized on the current rayon pool, so they are bound by its number of threads
+        #[cfg(feature = "threads")]
+        {
+            use rayon::prelude::*;
+
+            image
+                .channels_mut(false)
+                .par_iter_mut()
+                .try_for_each(resize_channel)?;
         }
 
-        image.set_dimensions(dst_width.get() as usize, dst_height.get() as usize);
+        #[cfg(not(feature = "threads"))]
+        image
+            .channels_mut(false)
+            .iter_mut()
+            .try_for_each(resize_channel)?;
+
+        let (dst_width, dst_height) = (dst_width.get() as usize, dst_height.get() as usize);
+
+        image.set_dimensions(dst_width, dst_height);
+
+        if self.fit == ResizeFit::Cover && (dst_width, dst_height) != (target_width, target_height)
+        {
+            let x = (dst_width - target_width) / 2;
+            let y = (dst_height - target_height) / 2;
+            let size = image.depth().size_of();
+
+            for channel in image.channels_mut(false) {
+                let mut new_channel =
+                    Channel::new_with_bit_type(target_width * target_height * size, depth);
+
+                let src = unsafe { channel.alias() };
+                let dst = unsafe { new_channel.alias_mut() };
+
+                src.chunks_exact(dst_width * size)
+                    .skip(y)
+                    .zip(dst.chunks_exact_mut(target_width * size))
+                    .for_each(|(src_row, dst_row)| {
+                        dst_row.copy_from_slice(&src_row[x * size..(x + target_width) * size]);
+                    });
+
+                *channel = new_channel;
+            }
+
+            image.set_dimensions(target_width, target_height);
+        }
+
+        if premultiply_alpha {
+            unpremultiply(image)?;
+        }
+
+        #[cfg(feature = "metadata")]
+        if self.preserve_metadata {
+            update_exif(image);
+        }
 
         Ok(())
     }
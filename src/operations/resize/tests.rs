@@ -40,6 +40,51 @@ fn resize_f32() {
     assert_eq!(image.dimensions(), (100, 100));
 }
 
+#[test]
+fn resize_cmyk() {
+    let resize = Resize::new(50, 30, fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3));
+    let mut image = Image::fill(64_u8, ColorSpace::CMYK, 100, 60);
+
+    let result = resize.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (50, 30));
+    assert_eq!(image.colorspace(), ColorSpace::CMYK);
+    assert_eq!(image.metadata().alpha(), AlphaState::NonPreMultiplied);
+
+    let channels = image.channels_ref(false);
+    assert_eq!(channels.len(), 4);
+
+    for channel in channels {
+        let values = channel.reinterpret_as::<u8>().unwrap();
+
+        assert_eq!(values.len(), 50 * 30);
+        assert!(values.iter().all(|v| *v == 64));
+    }
+}
+
+#[test]
+fn resize_ycck_u16() {
+    let resize = Resize::new(40, 40, fr::ResizeAlg::Nearest);
+    let mut image = Image::fill(40_000_u16, ColorSpace::YCCK, 80, 80);
+
+    let result = resize.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (40, 40));
+    assert_eq!(image.colorspace(), ColorSpace::YCCK);
+
+    let channels = image.channels_ref(false);
+    assert_eq!(channels.len(), 4);
+    assert!(channels[3]
+        .reinterpret_as::<u16>()
+        .unwrap()
+        .iter()
+        .all(|v| *v == 40_000));
+}
+
 #[test]
 fn resize_animated() {
     let resize = Resize::new(100, 100, fr::ResizeAlg::Nearest);
@@ -51,3 +96,268 @@ fn resize_animated() {
     assert!(result.is_ok());
     assert_eq!(image.dimensions(), (100, 100));
 }
+
+#[test]
+fn resize_exceeds_max_pixels() {
+    let resize = Resize::new(200 * 1000, 200 * 1000, fr::ResizeAlg::Nearest);
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let result = resize.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_err());
+    assert_eq!(image.dimensions(), (200, 200));
+
+    let resize = Resize::new(100, 100, fr::ResizeAlg::Nearest).with_max_pixels(100 * 99);
+    let error = resize.execute(&mut image).unwrap_err();
+
+    assert!(matches!(
+        crate::Error::from_image_errors(&error),
+        Some(crate::Error::PixelLimit {
+            width: 100,
+            height: 100,
+            limit: 9900
+        })
+    ));
+}
+
+#[test]
+fn resize_cover() {
+    let resize = Resize::new(100, 50, fr::ResizeAlg::Nearest).with_fit(ResizeFit::Cover);
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let result = resize.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (100, 50));
+
+    let resize = Resize::new(100, 100, fr::ResizeAlg::Nearest).with_fit(ResizeFit::Cover);
+    let mut image = Image::from_u16(&vec![0; 100 * 300 * 4], 100, 300, ColorSpace::RGBA);
+
+    let result = resize.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (100, 100));
+}
+
+#[test]
+fn resize_cover_crops_center() {
+    let resize = Resize::new(1, 1, fr::ResizeAlg::Nearest).with_fit(ResizeFit::Cover);
+    let mut image = Image::from_u8(&[10, 20, 30], 3, 1, ColorSpace::Luma);
+
+    let result = resize.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (1, 1));
+    assert_eq!(image.flatten_to_u8()[0], [20]);
+}
+
+#[test]
+fn resize_downscale_only() {
+    let resize = Resize::downscale_only(100, 100, fr::ResizeAlg::Nearest);
+
+    let mut large = Image::fill(128u8, ColorSpace::RGB, 400, 200);
+    let result = resize.execute(&mut large);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(large.dimensions(), (100, 50));
+
+    let pixels = (0..80 * 40 * 3).map(|v| v as u8).collect::<Vec<_>>();
+    let mut small = Image::from_u8(&pixels, 80, 40, ColorSpace::RGB);
+    let original = small.clone();
+    let result = resize.execute(&mut small);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(small.dimensions(), (80, 40));
+    assert_eq!(small.flatten_to_u8(), original.flatten_to_u8());
+}
+
+#[test]
+fn resize_auto() {
+    let upscale = Resize::auto(400, 400);
+    let downscale = Resize::auto(100, 100);
+
+    assert!(matches!(
+        downscale.algorithm_for((200, 200)),
+        fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3)
+    ));
+    assert!(matches!(
+        upscale.algorithm_for((200, 200)),
+        fr::ResizeAlg::Convolution(fr::FilterType::CatmullRom)
+    ));
+
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let result = upscale.execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.dimensions(), (400, 400));
+}
+
+#[cfg(feature = "metadata")]
+#[test]
+fn resize_preserve_metadata() {
+    use exif::{Field, In, Tag, Value};
+
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let exif = raw_exif(&[
+        Field {
+            tag: Tag::PixelXDimension,
+            ifd_num: In::PRIMARY,
+            value: Value::Long(vec![200]),
+        },
+        Field {
+            tag: Tag::PixelYDimension,
+            ifd_num: In::PRIMARY,
+            value: Value::Long(vec![200]),
+        },
+        Field {
+            tag: Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value: Value::Short(vec![1]),
+        },
+        Field {
+            tag: Tag::Compression,
+            ifd_num: In::THUMBNAIL,
+            value: Value::Short(vec![6]),
+        },
+    ]);
+
+    image.metadata_mut().parse_raw_exif(&exif);
+
+    let resize = Resize::new(100, 50, fr::ResizeAlg::Nearest).preserve_metadata(true);
+
+    resize.execute(&mut image).unwrap();
+
+    let exif = image.metadata().exif().unwrap();
+
+    let value = |tag| {
+        exif.iter()
+            .find(|f| f.tag == tag)
+            .and_then(|f| f.value.get_uint(0))
+    };
+
+    assert_eq!(value(Tag::PixelXDimension), Some(100));
+    assert_eq!(value(Tag::PixelYDimension), Some(50));
+    assert_eq!(value(Tag::Orientation), Some(1));
+    assert!(exif.iter().all(|f| f.ifd_num != In::THUMBNAIL));
+}
+
+#[test]
+fn resize_alpha_premultiplied() {
+    // straight color 200 with alpha 128, premultiplied to 100
+    let pixels = [100, 100, 100, 128].repeat(64 * 64);
+    let source = Image::from_u8(&pixels, 64, 64, ColorSpace::RGBA);
+
+    let mut image = source.clone();
+    Resize::new(32, 32, fr::ResizeAlg::Nearest)
+        .execute(&mut image)
+        .unwrap();
+
+    assert_eq!(image.flatten_to_u8()[0][..4], [100, 100, 100, 128]);
+
+    // premultiplied images are resized as is, however many times
+    let mut image = source.clone();
+    image.metadata_mut().set_alpha(AlphaState::PreMultiplied);
+
+    for size in [32, 16] {
+        Resize::new(size, size, fr::ResizeAlg::Nearest)
+            .execute(&mut image)
+            .unwrap();
+    }
+
+    assert_eq!(image.dimensions(), (16, 16));
+    assert_eq!(image.flatten_to_u8()[0][..4], [100, 100, 100, 128]);
+    assert_eq!(image.metadata().alpha(), AlphaState::PreMultiplied);
+}
+
+#[test]
+fn resize_alpha_aware() {
+    // opaque red fading into transparent green
+    let source = Image::from_fn(64, 64, ColorSpace::RGBA, |_, x, px: &mut [u8; 4]| {
+        *px = match x {
+            0..=29 => [255, 0, 0, 255],
+            30..=33 => [255, 0, 0, (255 - (x - 29) * 51) as u8],
+            _ => [0, 255, 0, 0],
+        };
+    });
+
+    // mean distance from red of pixels that aren't fully transparent
+    let fringing = |resize: Resize| {
+        let mut image = source.clone();
+        resize.execute(&mut image).unwrap();
+
+        let visible = image.flatten_to_u8()[0]
+            .chunks_exact(4)
+            .filter(|px| px[3] > 0)
+            .map(|px| (255 - i32::from(px[0])).abs() + i32::from(px[1]) + i32::from(px[2]))
+            .collect::<Vec<_>>();
+
+        visible.iter().sum::<i32>() as f64 / visible.len() as f64
+    };
+
+    let resize = || Resize::new(16, 16, fr::ResizeAlg::Convolution(fr::FilterType::Bilinear));
+
+    let aware = fringing(resize());
+    let naive = fringing(resize().with_alpha_aware(false));
+
+    assert!(aware < 1., "{aware}");
+    assert!(aware < naive, "{aware} >= {naive}");
+}
+
+#[test]
+#[cfg(feature = "threads")]
+fn resize_single_thread_pool() {
+    let source = create_test_image_u8(200, 200, ColorSpace::RGBA);
+    let resize = || {
+        Resize::new(
+            100,
+            50,
+            fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3),
+        )
+    };
+
+    let mut image = source.clone();
+    resize().execute(&mut image).unwrap();
+
+    let mut single = source.clone();
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap()
+        .install(|| resize().execute(&mut single))
+        .unwrap();
+
+    assert_eq!(single.dimensions(), (100, 50));
+    assert!(single.frames_ref() == image.frames_ref());
+}
+
+#[test]
+fn resize_compute_dimensions() {
+    let algorithm = fr::ResizeAlg::Convolution(fr::FilterType::Bilinear);
+
+    for (resize, expected) in [
+        (Resize::new(30, 20, algorithm), (30, 20)),
+        (
+            Resize::new(30, 30, algorithm).with_fit(ResizeFit::Cover),
+            (30, 30),
+        ),
+        (Resize::downscale_only(50, 50, algorithm), (50, 25)),
+        (Resize::downscale_only(200, 200, algorithm), (100, 50)),
+    ] {
+        let mut image = create_test_image_u8(100, 50, ColorSpace::RGB);
+
+        assert_eq!(resize.compute_dimensions(image.dimensions()), expected);
+
+        resize.execute(&mut image).unwrap();
+
+        assert_eq!(image.dimensions(), expected);
+    }
+}
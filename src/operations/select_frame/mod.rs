@@ -0,0 +1,59 @@
+use zune_core::bit_depth::BitType;
+use zune_image::{errors::ImageErrors, image::Image, traits::OperationsTrait};
+
+use crate::Error;
+
+/// Reduce an animated image to a single frame
+pub struct SelectFrame {
+    index: usize,
+}
+
+impl SelectFrame {
+    /// Create a new select frame operation
+    ///
+    /// # Arguments
+    /// - index: Zero-based index of the frame to keep
+    #[must_use]
+    pub fn new(index: usize) -> Self {
+        Self { index }
+    }
+}
+
+impl OperationsTrait for SelectFrame {
+    fn name(&self) -> &'static str {
+        "select frame"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let frames = image.frames_len();
+
+        let Some(frame) = image.frames_ref().get(self.index).cloned() else {
+            return Err(Error::FrameOutOfRange {
+                index: self.index,
+                frames,
+            }
+            .into());
+        };
+
+        let (width, height) = image.dimensions();
+        let metadata = image.metadata().clone();
+
+        *image = Image::new_frames(
+            vec![frame],
+            image.depth(),
+            width,
+            height,
+            image.colorspace(),
+        );
+        *image.metadata_mut() = metadata;
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests;
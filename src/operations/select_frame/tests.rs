@@ -0,0 +1,41 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+
+use super::*;
+
+fn animation() -> Image {
+    let frames = (0..3)
+        .map(|idx| Image::fill(idx * 100u8, ColorSpace::RGB, 4, 4).frames_ref()[0].clone())
+        .collect();
+
+    Image::new_frames(frames, BitDepth::Eight, 4, 4, ColorSpace::RGB)
+}
+
+#[test]
+fn select_frame() {
+    let mut image = animation();
+
+    let result = SelectFrame::new(2).execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.frames_len(), 1);
+    assert_eq!(image.dimensions(), (4, 4));
+    assert_eq!(image.flatten_to_u8()[0], [200; 4 * 4 * 3]);
+}
+
+#[test]
+fn select_frame_out_of_range() {
+    let mut image = animation();
+
+    let error = SelectFrame::new(3).execute(&mut image).unwrap_err();
+    dbg!(&error);
+
+    assert!(matches!(
+        Error::from_image_errors(&error),
+        Some(Error::FrameOutOfRange {
+            index: 3,
+            frames: 3
+        })
+    ));
+    assert_eq!(image.frames_len(), 3);
+}
@@ -0,0 +1,98 @@
+use std::mem;
+
+use zune_core::{bit_depth::BitType, colorspace::ColorSpace};
+use zune_image::{
+    errors::{ImageErrors, ImageOperationsErrors},
+    image::Image,
+    traits::OperationsTrait,
+};
+
+use crate::Error;
+
+/// Permute channels of an image, like RGBA to BGRA
+///
+/// Images with fewer than 4 channels use only the leading part of the order,
+/// so `[2, 1, 0, 3]` swaps red and blue of both RGB and RGBA images.
+pub struct SwapChannels {
+    order: [usize; 4],
+    colorspace: Option<ColorSpace>,
+}
+
+impl SwapChannels {
+    /// Create a new swap channels operation
+    ///
+    /// # Arguments
+    /// - order: Index of the source channel for each output channel,
+    ///   must be a permutation of channel indices of the image
+    #[must_use]
+    pub fn new(order: [usize; 4]) -> Self {
+        Self {
+            order,
+            colorspace: None,
+        }
+    }
+
+    /// Reinterpret permuted channels as another colorspace with the same number of components
+    ///
+    /// By default the colorspace is kept, so only the byte order of the image changes.
+    #[must_use]
+    pub fn with_colorspace(mut self, colorspace: ColorSpace) -> Self {
+        self.colorspace = Some(colorspace);
+        self
+    }
+}
+
+impl OperationsTrait for SwapChannels {
+    fn name(&self) -> &'static str {
+        "swap channels"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        let channels = image.colorspace().num_components();
+        let order = &self.order[..channels.min(4)];
+
+        let mut sorted = order.to_vec();
+        sorted.sort_unstable();
+
+        if channels > 4 || sorted.iter().enumerate().any(|(idx, c)| idx != *c) {
+            return Err(Error::InvalidChannelOrder {
+                order: self.order,
+                channels,
+            }
+            .into());
+        }
+
+        if let Some(colorspace) = self.colorspace {
+            if colorspace.num_components() != channels {
+                return Err(ImageErrors::OperationsError(
+                    ImageOperationsErrors::WrongComponents(colorspace.num_components(), channels),
+                ));
+            }
+        }
+
+        for frame in image.frames_mut() {
+            let mut source = mem::take(frame.channels_vec())
+                .into_iter()
+                .map(Some)
+                .collect::<Vec<_>>();
+
+            *frame.channels_vec() = order
+                .iter()
+                .map(|idx| source[*idx].take().expect("order is a permutation"))
+                .collect();
+        }
+
+        if let Some(colorspace) = self.colorspace {
+            image.metadata_mut().set_colorspace(colorspace);
+        }
+
+        Ok(())
+    }
+
+    fn supported_types(&self) -> &'static [BitType] {
+        &[BitType::U8, BitType::U16, BitType::F32]
+    }
+}
+
+#[cfg(test)]
+mod tests;
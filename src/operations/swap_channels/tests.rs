@@ -0,0 +1,59 @@
+use zune_core::colorspace::ColorSpace;
+
+use super::*;
+
+fn test_image() -> Image {
+    Image::from_u8(&[10, 20, 30, 40, 50, 60, 70, 80], 2, 1, ColorSpace::RGBA)
+}
+
+#[test]
+fn swap_red_and_blue() {
+    let mut image = test_image();
+
+    let result = SwapChannels::new([2, 1, 0, 3]).execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.colorspace(), ColorSpace::RGBA);
+    assert_eq!(image.flatten_to_u8()[0], [30, 20, 10, 40, 70, 60, 50, 80]);
+}
+
+#[test]
+fn swap_rgb_as_bgr() {
+    let mut image = Image::from_u8(&[10, 20, 30, 40, 50, 60], 2, 1, ColorSpace::RGB);
+
+    let result = SwapChannels::new([2, 1, 0, 3])
+        .with_colorspace(ColorSpace::BGR)
+        .execute(&mut image);
+    dbg!(&result);
+
+    assert!(result.is_ok());
+    assert_eq!(image.colorspace(), ColorSpace::BGR);
+
+    let channels = image.channels_ref(false);
+    assert_eq!(channels[0].reinterpret_as::<u8>().unwrap(), [30, 60]);
+    assert_eq!(channels[2].reinterpret_as::<u8>().unwrap(), [10, 40]);
+}
+
+#[test]
+fn invalid_order() {
+    let mut image = test_image();
+
+    let error = SwapChannels::new([0, 0, 1, 2])
+        .execute(&mut image)
+        .unwrap_err();
+    assert!(matches!(
+        crate::Error::from_image_errors(&error),
+        Some(crate::Error::InvalidChannelOrder { channels: 4, .. })
+    ));
+
+    let mut image = Image::from_u8(&[10, 20, 30], 1, 1, ColorSpace::RGB);
+    assert!(SwapChannels::new([3, 1, 0, 2]).execute(&mut image).is_err());
+
+    let mut image = test_image();
+    assert!(SwapChannels::new([3, 2, 1, 0])
+        .with_colorspace(ColorSpace::BGR)
+        .execute(&mut image)
+        .is_err());
+    assert_eq!(image.flatten_to_u8()[0], [10, 20, 30, 40, 50, 60, 70, 80]);
+}
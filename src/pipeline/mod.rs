@@ -0,0 +1,140 @@
+use zune_core::{
+    bit_depth::{BitDepth, BitType},
+    bytestream::ZByteWriterTrait,
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    core_filters::{colorspace::ColorspaceConv, depth::Depth},
+    errors::ImageErrors,
+    image::Image,
+    traits::{EncoderTrait, OperationsTrait},
+};
+
+/// Builder of a [`Pipeline`] running operations in the order they are added
+///
+/// Before every operation, the image is converted to a colorspace and bit depth
+/// it supports, so operations like [`Quantize`](crate::operations::quantize::Quantize),
+/// which only works on RGBA images, can follow any other operation.
+///
+/// # Example
+///
+/// ```
+/// use rimage::{
+///     codecs::oxipng::OxiPngEncoder,
+///     operations::{
+///         quantize::Quantize,
+///         resize::{Resize, ResizeAlg},
+///     },
+///     pipeline::PipelineBuilder,
+/// };
+/// use zune_image::image::Image;
+///
+/// let img = Image::open("tests/files/jpg/f1t.jpg").unwrap();
+///
+/// let mut pipeline = PipelineBuilder::new()
+///     .with_operation(Resize::new(32, 32, ResizeAlg::Nearest))
+///     .with_operation(Quantize::new(75, None))
+///     .with_encoder(OxiPngEncoder::new());
+///
+/// let mut result: Vec<u8> = vec![];
+///
+/// pipeline.run(img, &mut result).unwrap();
+/// ```
+#[derive(Default)]
+pub struct PipelineBuilder {
+    operations: Vec<Box<dyn OperationsTrait>>,
+}
+
+impl PipelineBuilder {
+    /// Create a new builder without operations
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an operation, executed after all previously added ones
+    #[must_use]
+    pub fn with_operation<O: OperationsTrait + 'static>(mut self, operation: O) -> Self {
+        self.operations.push(Box::new(operation));
+        self
+    }
+
+    /// Finish the pipeline, encoding processed images with `encoder`
+    #[must_use]
+    pub fn with_encoder<E: EncoderTrait>(self, encoder: E) -> Pipeline<E> {
+        Pipeline {
+            operations: self.operations,
+            encoder,
+        }
+    }
+}
+
+/// Operations followed by an encoder, see [`PipelineBuilder`]
+pub struct Pipeline<E> {
+    operations: Vec<Box<dyn OperationsTrait>>,
+    encoder: E,
+}
+
+impl<E: EncoderTrait> Pipeline<E> {
+    /// Executes operations on the image without encoding it
+    pub fn process(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        for operation in &self.operations {
+            prepare(image, operation.as_ref())?;
+            operation.execute(image)?;
+        }
+
+        Ok(())
+    }
+
+    /// Executes operations on the image and encodes the result into `sink`
+    ///
+    /// Returns number of bytes written.
+    pub fn run<T: ZByteWriterTrait>(
+        &mut self,
+        mut image: Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        self.process(&mut image)?;
+
+        self.encoder.encode(&image, sink)
+    }
+}
+
+/// Converts the image to a bit depth and colorspace supported by the operation
+///
+/// RGBA is preferred among supported colorspaces, as it can represent any image.
+fn prepare(image: &mut Image, operation: &dyn OperationsTrait) -> Result<(), ImageErrors> {
+    let types = operation.supported_types();
+
+    if !types.contains(&image.depth().bit_type()) {
+        let depth = match types.first() {
+            Some(BitType::U8) => Some(BitDepth::Eight),
+            Some(BitType::U16) => Some(BitDepth::Sixteen),
+            Some(BitType::F32) => Some(BitDepth::Float32),
+            _ => None,
+        };
+
+        if let Some(depth) = depth {
+            Depth::new(depth).execute(image)?;
+        }
+    }
+
+    let colorspaces = operation.supported_colorspaces();
+
+    if !colorspaces.contains(&image.colorspace()) {
+        let colorspace = if colorspaces.contains(&ColorSpace::RGBA) {
+            Some(ColorSpace::RGBA)
+        } else {
+            colorspaces.first().copied()
+        };
+
+        if let Some(colorspace) = colorspace {
+            ColorspaceConv::new(colorspace).execute(image)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,92 @@
+use zune_core::{bytestream::ZCursor, options::DecoderOptions};
+use zune_image::codecs::png::PngEncoder;
+
+use crate::operations::{auto_contrast::AutoContrast, pad::Pad};
+
+use super::*;
+
+#[test]
+fn operations_in_order() {
+    let img = Image::fill(128_u8, ColorSpace::RGB, 16, 16);
+
+    let pad = || Pad::new(4, 4, 4, 4, [0., 0., 0., 1.]);
+    let contrast = || AutoContrast::new(0.);
+
+    // padding adds black, stretching contrast of the whole image afterwards
+    let mut padded_first = img.clone();
+    PipelineBuilder::new()
+        .with_operation(pad())
+        .with_operation(contrast())
+        .with_encoder(PngEncoder::new())
+        .process(&mut padded_first)
+        .unwrap();
+
+    let mut padded_last = img.clone();
+    PipelineBuilder::new()
+        .with_operation(contrast())
+        .with_operation(pad())
+        .with_encoder(PngEncoder::new())
+        .process(&mut padded_last)
+        .unwrap();
+
+    assert_eq!(padded_first.dimensions(), (24, 24));
+    assert_eq!(padded_last.dimensions(), (24, 24));
+
+    let center = |img: &Image| img.flatten_to_u8()[0][(12 * 24 + 12) * 3];
+
+    assert_eq!(center(&padded_first), 255);
+    assert_eq!(center(&padded_last), 128);
+}
+
+#[test]
+#[cfg(feature = "quantization")]
+fn prepare_colorspace() {
+    use crate::operations::quantize::Quantize;
+
+    let pixels = (0..64 * 64)
+        .map(|idx| (idx % 64 * 4) as u8)
+        .collect::<Vec<_>>();
+    let mut img = Image::from_u8(&pixels, 64, 64, ColorSpace::Luma);
+
+    PipelineBuilder::new()
+        .with_operation(Quantize::new(100, None))
+        .with_encoder(PngEncoder::new())
+        .process(&mut img)
+        .unwrap();
+
+    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+}
+
+#[test]
+fn prepare_depth() {
+    let mut img = Image::fill(0.5_f32, ColorSpace::RGB, 8, 8);
+
+    PipelineBuilder::new()
+        .with_operation(AutoContrast::new(0.))
+        .with_encoder(PngEncoder::new())
+        .process(&mut img)
+        .unwrap();
+
+    assert_eq!(img.depth(), BitDepth::Eight);
+}
+
+#[test]
+fn run_encodes() {
+    let img = Image::fill(100_u8, ColorSpace::RGB, 8, 8);
+
+    let mut pipeline = PipelineBuilder::new()
+        .with_operation(Pad::new(0, 0, 8, 0, [1., 0., 0., 1.]))
+        .with_encoder(PngEncoder::new());
+
+    let mut buf = vec![];
+    let written = pipeline.run(img, &mut buf).unwrap();
+
+    assert_eq!(written, buf.len());
+
+    let decoded = Image::read(ZCursor::new(&buf), DecoderOptions::default()).unwrap();
+    let pixels = &decoded.flatten_to_u8()[0];
+
+    assert_eq!(decoded.dimensions(), (16, 8));
+    assert_eq!(pixels[..3], [100, 100, 100]);
+    assert_eq!(pixels[15 * 3..16 * 3], [255, 0, 0]);
+}
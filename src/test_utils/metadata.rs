@@ -0,0 +1,12 @@
+use exif::{experimental::Writer, Field};
+
+/// Serializes EXIF `fields` the way decoders store them in image metadata
+pub fn raw_exif(fields: &[Field]) -> Vec<u8> {
+    let mut writer = Writer::new();
+    fields.iter().for_each(|f| writer.push_field(f));
+
+    let mut buf = std::io::Cursor::new(vec![]);
+    writer.write(&mut buf, false).unwrap();
+
+    buf.into_inner()
+}
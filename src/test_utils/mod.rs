@@ -4,6 +4,14 @@ use zune_core::{
 };
 use zune_image::{channel::Channel, frame::Frame, image::Image};
 
+#[cfg(feature = "metadata")]
+pub(crate) use self::metadata::raw_exif;
+pub(crate) use self::temp_dir::TempDir;
+
+#[cfg(feature = "metadata")]
+mod metadata;
+mod temp_dir;
+
 pub(crate) fn create_test_image_u8(width: usize, height: usize, colorspace: ColorSpace) -> Image {
     Image::from_fn(width, height, colorspace, |x, y, px: &mut [u8; 4]| {
         let r = (0.3 * x as f32) as u8;
@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use walkdir::{DirEntry, WalkDir};
+
+use crate::codecs::can_decode;
+
+/// Options of [`walk_images`]
+#[derive(Debug, Clone, Copy)]
+pub struct WalkOptions {
+    recursive: bool,
+    max_depth: Option<usize>,
+    images_only: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            max_depth: None,
+            images_only: true,
+        }
+    }
+}
+
+impl WalkOptions {
+    /// Create options walking directories recursively without depth limit
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Descend into subdirectories of the root
+    ///
+    /// Without recursion only direct children of the root are listed. Enabled by default.
+    #[must_use]
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Limit how deep files may be below the root
+    ///
+    /// 1 keeps only direct children of the root, 0 lists nothing inside of it.
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Yield only files decodable by enabled codecs, see [`can_decode`]
+    ///
+    /// Checking the format reads the start of every file. Enabled by default.
+    #[must_use]
+    pub fn with_images_only(mut self, images_only: bool) -> Self {
+        self.images_only = images_only;
+        self
+    }
+
+    /// Maximum depth of listed files, `None` if unlimited
+    fn depth_limit(&self) -> Option<usize> {
+        if self.recursive {
+            self.max_depth
+        } else {
+            Some(self.max_depth.map_or(1, |depth| depth.min(1)))
+        }
+    }
+}
+
+/// Lists images inside of `root`, walking it lazily
+///
+/// Entries of every directory are visited in the order of their names,
+/// files are yielded only if their format can be decoded, see [`can_decode`].
+/// Symbolic links aren't followed and unreadable directories are skipped
+/// with a warning. If `root` is a file, it's yielded if it's an image.
+///
+/// # Example
+///
+/// ```
+/// use rimage::{walk_images, WalkOptions};
+///
+/// for path in walk_images("tests/files", WalkOptions::new().with_max_depth(2)) {
+///     println!("{}", path.display());
+/// }
+/// ```
+pub fn walk_images<P: AsRef<Path>>(root: P, options: WalkOptions) -> impl Iterator<Item = PathBuf> {
+    walk_images_with(root, options, |_| true)
+}
+
+/// Lists images inside of `root` like [`walk_images`], skipping entries rejected by `filter`
+///
+/// `filter` gets paths relative to `root`. Rejected directories aren't descended into,
+/// so their content is skipped as well.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+///
+/// use rimage::{walk_images_with, WalkOptions};
+///
+/// let drafts = Path::new("drafts");
+///
+/// for path in walk_images_with("tests/files", WalkOptions::new(), |path| path != drafts) {
+///     println!("{}", path.display());
+/// }
+/// ```
+pub fn walk_images_with<P, F>(
+    root: P,
+    options: WalkOptions,
+    mut filter: F,
+) -> impl Iterator<Item = PathBuf>
+where
+    P: AsRef<Path>,
+    F: FnMut(&Path) -> bool,
+{
+    let root = root.as_ref().to_path_buf();
+
+    let max_depth = options.depth_limit();
+
+    // files are walked as a tree of a single entry at depth 0
+    let mut walker = WalkDir::new(&root)
+        .min_depth(usize::from(root.is_dir()))
+        .sort_by_file_name();
+
+    if let Some(depth) = max_depth {
+        walker = walker.max_depth(depth);
+    }
+
+    walker
+        .into_iter()
+        // walkdir raises the depth limit to the minimum depth, so 0 is enforced here
+        .filter_entry(move |entry| {
+            entry.depth() == 0
+                || (max_depth != Some(0)
+                    && filter(entry.path().strip_prefix(&root).unwrap_or(entry.path())))
+        })
+        .filter_map(|entry| entry.inspect_err(|e| log::warn!("{e}")).ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(DirEntry::into_path)
+        .filter(move |path| !options.images_only || is_image(path))
+}
+
+fn is_image(path: &Path) -> bool {
+    let image = can_decode(path);
+
+    if !image {
+        log::debug!("{}: not an image, skipping", path.display());
+    }
+
+    image
+}
+
+#[cfg(test)]
+mod tests;
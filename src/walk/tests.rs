@@ -0,0 +1,119 @@
+use std::fs;
+
+use super::*;
+use crate::test_utils::TempDir;
+
+/// Creates a fixture tree of images mixed with other files
+fn fixture(name: &str) -> TempDir {
+    let root = TempDir::new(name);
+
+    for (file, source) in [
+        ("a.png", Some("tests/files/png/f1t.png")),
+        ("notes.txt", None),
+        ("one/b.jpg", Some("tests/files/jpg/f1t.jpg")),
+        ("one/fake.png", None),
+        ("one/two/c.png", Some("tests/files/png/f1t.png")),
+    ] {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        match source {
+            Some(source) => fs::copy(source, path).map(|_| ()).unwrap(),
+            None => fs::write(path, "not an image").unwrap(),
+        }
+    }
+
+    root
+}
+
+fn relative(root: &Path, options: WalkOptions) -> Vec<PathBuf> {
+    walk_images(root, options)
+        .map(|p| p.strip_prefix(root).unwrap().to_path_buf())
+        .collect()
+}
+
+#[test]
+fn walk_recursive() {
+    let dir = fixture("walk_recursive");
+    let root = dir.path();
+
+    assert_eq!(
+        relative(root, WalkOptions::new()),
+        ["a.png", "one/b.jpg", "one/two/c.png"].map(PathBuf::from)
+    );
+    assert_eq!(
+        relative(root, WalkOptions::new().with_max_depth(2)),
+        ["a.png", "one/b.jpg"].map(PathBuf::from)
+    );
+    assert!(relative(root, WalkOptions::new().with_max_depth(0)).is_empty());
+}
+
+#[test]
+fn walk_flat() {
+    let dir = fixture("walk_flat");
+    let root = dir.path();
+
+    assert_eq!(
+        relative(root, WalkOptions::new().with_recursive(false)),
+        [PathBuf::from("a.png")]
+    );
+
+    // recursion is disabled regardless of the depth limit
+    assert_eq!(
+        relative(
+            root,
+            WalkOptions::new().with_recursive(false).with_max_depth(3)
+        ),
+        [PathBuf::from("a.png")]
+    );
+}
+
+#[test]
+fn walk_file() {
+    let dir = fixture("walk_file");
+    let root = dir.path();
+
+    assert_eq!(
+        walk_images(root.join("a.png"), WalkOptions::new()).collect::<Vec<_>>(),
+        [root.join("a.png")]
+    );
+    assert_eq!(
+        walk_images(root.join("notes.txt"), WalkOptions::new()).count(),
+        0
+    );
+    assert_eq!(
+        walk_images(root.join("missing"), WalkOptions::new()).count(),
+        0
+    );
+}
+
+#[test]
+fn walk_all_files() {
+    let dir = fixture("walk_all_files");
+    let root = dir.path();
+
+    assert_eq!(
+        relative(root, WalkOptions::new().with_images_only(false)),
+        [
+            "a.png",
+            "notes.txt",
+            "one/b.jpg",
+            "one/fake.png",
+            "one/two/c.png"
+        ]
+        .map(PathBuf::from)
+    );
+}
+
+#[test]
+fn walk_filtered() {
+    let dir = fixture("walk_filtered");
+    let root = dir.path();
+
+    let two = Path::new("one/two");
+    let files = walk_images_with(root, WalkOptions::new(), |path| path != two)
+        .map(|p| p.strip_prefix(root).unwrap().to_path_buf())
+        .collect::<Vec<_>>();
+
+    assert_eq!(files, ["a.png", "one/b.jpg"].map(PathBuf::from));
+}